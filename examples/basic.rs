@@ -13,8 +13,8 @@ fn main() {
 
     // Create a master extended key from the generated seed.
     let keys = MasterExtendedKeys::new(seed.entropy, None, Network::Testnet, false).unwrap();
-    // Derive a child keypair from master private key.
-    let kp = KeyPair::from_private(keys.privkey(), false).unwrap();
+    // Take the keypair held by the master keys.
+    let kp = KeyPair::from_extended(&keys);
     assert_eq!(kp.private().secret.len(), 32);
 
     // A normal public key's length is 65, while a compressed version is 33.
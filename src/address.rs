@@ -0,0 +1,736 @@
+use crate::bech32::{self, Variant};
+use crate::crypto;
+use crate::{AddressHash, DisplayLayout, Error, Network, PublicKey};
+use base58::{FromBase58, ToBase58};
+use std::fmt;
+use std::str;
+
+const OP_0: u8 = 0x00;
+pub(crate) const OP_1: u8 = 0x51;
+const OP_DUP: u8 = 0x76;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_HASH160: u8 = 0xa9;
+const OP_CHECKSIG: u8 = 0xac;
+
+/// The script an [Address](Address) pays to.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AddressType {
+    /// Pay-to-pubkey-hash, spent with a signature and the public key.
+    P2pkh,
+    /// Pay-to-script-hash, spent with the script hashed into the address.
+    P2sh,
+    /// Pay-to-witness-pubkey-hash, native SegWit version 0.
+    P2wpkh,
+    /// Pay-to-witness-script-hash, native SegWit version 0.
+    P2wsh,
+    /// Pay-to-taproot, SegWit version 1.
+    P2tr,
+    /// A native SegWit output of a version with no spending rules yet, or
+    /// of version 1 without a 32-byte program. Parsed so that funds can be
+    /// sent to it, as [BIP350] asks of wallets.
+    ///
+    /// [BIP350]: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+    WitnessUnknown(u8),
+}
+
+/// A Bitcoin address, serialized in Base58Check, or in bech32 for native
+/// SegWit.
+///
+/// Parsing detects the encoding and checks the checksum, the version byte or
+/// witness version, and the program length, so user input can be validated
+/// before funds are sent to it. Witness versions 2 to 16 are not yet defined
+/// and parse as [WitnessUnknown](AddressType::WitnessUnknown), with a program
+/// of 2 to 40 bytes.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{Address, AddressType, ExtendedPrivKey, Network, SeedBuilder};
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+///
+/// let address = Address::p2pkh(&keys.pubkey(), Network::Mainnet);
+/// assert!(address.to_string().starts_with('1'));
+/// assert_eq!(address.to_string().parse::<Address>().unwrap(), address);
+///
+/// let input: Address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".parse().unwrap();
+/// assert_eq!(input.address_type(), AddressType::P2wpkh);
+/// assert!(input.require_network(Network::Testnet).is_err());
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Address {
+    network: Network,
+    address_type: AddressType,
+    program: Vec<u8>,
+}
+
+impl Address {
+    /// The P2PKH address of `public`. The key is hashed in the form it is
+    /// stored in, so a compressed and an uncompressed key have different
+    /// addresses.
+    pub fn p2pkh(public: &PublicKey, network: Network) -> Self {
+        Address {
+            network,
+            address_type: AddressType::P2pkh,
+            program: public.address_hash().to_vec(),
+        }
+    }
+
+    /// The single-key address of `address_type` paying to `public`: P2SH
+    /// stands for P2SH-P2WPKH, and P2WSH has no single-key form.
+    pub(crate) fn from_public(
+        public: &PublicKey,
+        address_type: AddressType,
+        network: Network,
+    ) -> Result<Self, Error> {
+        match address_type {
+            AddressType::P2pkh => Ok(Self::p2pkh(public, network)),
+            AddressType::P2sh => Self::p2sh_wpkh(public, network),
+            AddressType::P2wpkh => Self::p2wpkh(public, network),
+            AddressType::P2tr => Self::p2tr(public, None, network),
+            AddressType::P2wsh | AddressType::WitnessUnknown(_) => Err(Error::InvalidAddress),
+        }
+    }
+
+    pub(crate) fn from_parts(network: Network, address_type: AddressType, hash: &[u8]) -> Self {
+        Address {
+            network,
+            address_type,
+            program: hash.to_vec(),
+        }
+    }
+
+    /// The P2SH address of a script whose `hash160` is `script_hash`.
+    pub fn p2sh(script_hash: AddressHash, network: Network) -> Self {
+        Address {
+            network,
+            address_type: AddressType::P2sh,
+            program: script_hash.to_vec(),
+        }
+    }
+
+    /// The P2SH-P2WPKH (nested SegWit) address of `public`, as used by BIP49
+    /// accounts. SegWit only allows compressed keys, so an uncompressed key
+    /// is compressed first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Address, ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Testnet, true).unwrap();
+    ///
+    /// let address = Address::p2sh_wpkh(&keys.pubkey(), Network::Testnet).unwrap();
+    /// assert!(address.to_string().starts_with('2'));
+    /// ```
+    pub fn p2sh_wpkh(public: &PublicKey, network: Network) -> Result<Self, Error> {
+        hrp(network)?;
+        // The redeem script is the version 0 witness program `OP_0 <20 bytes>`.
+        let mut redeem_script = vec![0x00, 0x14];
+        redeem_script.extend_from_slice(&public.identifier()?);
+        Ok(Self::p2sh(crypto::hash160(&redeem_script), network))
+    }
+
+    /// The native SegWit P2WPKH address of `public`, as used by BIP84
+    /// accounts. An uncompressed key is compressed first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Address, ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let address = Address::p2wpkh(&keys.pubkey(), Network::Mainnet).unwrap();
+    /// assert!(address.to_string().starts_with("bc1q"));
+    /// ```
+    pub fn p2wpkh(public: &PublicKey, network: Network) -> Result<Self, Error> {
+        hrp(network)?;
+        Ok(Address {
+            network,
+            address_type: AddressType::P2wpkh,
+            program: public.identifier()?.to_vec(),
+        })
+    }
+
+    /// The native SegWit P2WSH address of a witness script whose `sha256` is
+    /// `script_hash`.
+    pub fn p2wsh(script_hash: [u8; 32], network: Network) -> Result<Self, Error> {
+        hrp(network)?;
+        Ok(Address {
+            network,
+            address_type: AddressType::P2wsh,
+            program: script_hash.to_vec(),
+        })
+    }
+
+    /// The taproot P2TR address of `internal_key`, committing to the script
+    /// tree with `merkle_root`, or to no scripts at all if it is `None`.
+    ///
+    /// The output key is the internal key tweaked as [BIP341] specifies, so
+    /// the address can be spent with the internal key after the same tweak.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Address, ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let address = Address::p2tr(&keys.pubkey(), None, Network::Mainnet).unwrap();
+    /// assert!(address.to_string().starts_with("bc1p"));
+    /// ```
+    ///
+    /// [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    pub fn p2tr(
+        internal_key: &PublicKey,
+        merkle_root: Option<&[u8; 32]>,
+        network: Network,
+    ) -> Result<Self, Error> {
+        hrp(network)?;
+        let (internal_key, _) = internal_key.x_only()?;
+        let (output_key, _) = internal_key.tap_tweak(merkle_root)?;
+
+        Ok(Address {
+            network,
+            address_type: AddressType::P2tr,
+            program: output_key.serialize().to_vec(),
+        })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn address_type(&self) -> AddressType {
+        self.address_type
+    }
+
+    /// Return the address if it can be used on `network`, or
+    /// `Error::InvalidNetwork`. Base58Check addresses do not distinguish
+    /// regtest from testnet, so testnet ones are accepted for both.
+    pub fn require_network(self, network: Network) -> Result<Self, Error> {
+        let matches = self.network == network
+            || (!self.is_segwit()
+                && self.network == Network::Testnet
+                && network == Network::Regtest);
+        if matches {
+            Ok(self)
+        } else {
+            Err(Error::InvalidNetwork)
+        }
+    }
+
+    /// The hash the address pays to, the output key of a taproot address,
+    /// or the program of an unknown witness version.
+    pub fn hash(&self) -> &[u8] {
+        &self.program
+    }
+
+    /// The locking script of an output paying to this address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::Address;
+    ///
+    /// let address: Address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".parse().unwrap();
+    /// assert_eq!(
+    ///     hex::encode(address.script_pubkey()),
+    ///     "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+    /// );
+    /// ```
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        let push = self.program.len() as u8;
+        let mut script = match self.address_type {
+            // OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG
+            AddressType::P2pkh => vec![OP_DUP, OP_HASH160, push],
+            // OP_HASH160 <hash> OP_EQUAL
+            AddressType::P2sh => vec![OP_HASH160, push],
+            // OP_n <program>
+            _ => match self.witness_version() {
+                0 => vec![OP_0, push],
+                n => vec![OP_1 - 1 + n, push],
+            },
+        };
+        script.extend_from_slice(&self.program);
+        match self.address_type {
+            AddressType::P2pkh => script.extend_from_slice(&[OP_EQUALVERIFY, OP_CHECKSIG]),
+            AddressType::P2sh => script.push(OP_EQUAL),
+            _ => {}
+        }
+        script
+    }
+
+    /// The Base58Check version bytes, or the witness version of a SegWit
+    /// address.
+    fn version(&self) -> Vec<u8> {
+        let params = self.network.params();
+        match self.address_type {
+            AddressType::P2pkh => params.p2pkh.to_vec(),
+            AddressType::P2sh => params.p2sh.to_vec(),
+            _ => vec![self.witness_version()],
+        }
+    }
+
+    fn witness_version(&self) -> u8 {
+        match self.address_type {
+            AddressType::P2tr => 1,
+            AddressType::WitnessUnknown(version) => version,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn is_segwit(&self) -> bool {
+        matches!(
+            self.address_type,
+            AddressType::P2wpkh
+                | AddressType::P2wsh
+                | AddressType::P2tr
+                | AddressType::WitnessUnknown(_)
+        )
+    }
+
+    fn from_bech32(hrp: &str, data: &[u8], variant: Variant) -> Result<Self, Error> {
+        let network =
+            Network::find(|params| params.bech32_hrp == Some(hrp)).ok_or(Error::InvalidNetwork)?;
+        let (version, program) = data.split_first().ok_or(Error::InvalidAddress)?;
+        let program =
+            bech32::convert_bits(program, 5, 8, false).map_err(|_| Error::InvalidAddress)?;
+
+        // Version 0 must use bech32 and later versions bech32m.
+        let address_type = match (*version, variant, program.len()) {
+            (0, Variant::Bech32, 20) => AddressType::P2wpkh,
+            (0, Variant::Bech32, 32) => AddressType::P2wsh,
+            (1, Variant::Bech32m, 32) => AddressType::P2tr,
+            (1..=16, Variant::Bech32m, 2..=40) => AddressType::WitnessUnknown(*version),
+            _ => return Err(Error::InvalidAddress),
+        };
+
+        Ok(Address {
+            network,
+            address_type,
+            program,
+        })
+    }
+}
+
+/// The bech32 human-readable part of `network`, if it has SegWit.
+fn hrp(network: Network) -> Result<&'static str, Error> {
+    network.params().bech32_hrp.ok_or(Error::InvalidNetwork)
+}
+
+impl DisplayLayout for Address {
+    type Target = Vec<u8>;
+
+    /// The Base58Check payload, or for a SegWit address the witness version
+    /// followed by the program, which bech32 encodes.
+    fn layout(&self) -> Self::Target {
+        let mut result = self.version();
+        result.extend_from_slice(&self.program);
+        if !self.is_segwit() {
+            let cs = crypto::checksum(&result);
+            result.extend_from_slice(&cs);
+        }
+        result
+    }
+
+    fn from_layout(data: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        // The version, a 20-byte hash and a 4-byte checksum.
+        if data.len() < 25 {
+            return Err(Error::InvalidAddress);
+        }
+        let (payload, cs) = data.split_at(data.len() - 4);
+        if !crypto::verify_checksum(payload, cs) {
+            return Err(Error::InvalidChecksum);
+        }
+
+        let (version, program) = payload.split_at(payload.len() - 20);
+        let (address_type, network) = Network::find(|params| params.p2pkh == version)
+            .map(|network| (AddressType::P2pkh, network))
+            .or_else(|| {
+                Network::find(|params| params.p2sh == version)
+                    .map(|network| (AddressType::P2sh, network))
+            })
+            .ok_or(Error::InvalidAddress)?;
+
+        Ok(Address {
+            network,
+            address_type,
+            program: program.to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_segwit() {
+            return self.layout().to_base58().fmt(f);
+        }
+        let layout = self.layout();
+        let mut data = vec![layout[0]];
+        data.extend(bech32::convert_bits(&layout[1..], 8, 5, true).map_err(|_| fmt::Error)?);
+        let variant = match self.witness_version() {
+            0 => Variant::Bech32,
+            _ => Variant::Bech32m,
+        };
+        let hrp = hrp(self.network).map_err(|_| fmt::Error)?;
+        bech32::encode(hrp, &data, variant)
+            .map_err(|_| fmt::Error)?
+            .fmt(f)
+    }
+}
+
+impl str::FromStr for Address {
+    type Err = Error;
+
+    /// Parse a bech32 or bech32m address, or failing that a Base58Check one.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if let Ok((hrp, data, variant)) = bech32::decode(s) {
+            return Self::from_bech32(&hrp, &data, variant);
+        }
+        let data = s.from_base58().map_err(|_| Error::InvalidAddress)?;
+        Self::from_layout(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ExtendedPrivKey;
+    use secp256k1::key;
+    use std::convert::TryInto;
+
+    #[test]
+    fn p2pkh_addresses() -> Result<(), Error> {
+        // The public key of secret 1, i.e. the generator point.
+        let compressed =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let public = key::PublicKey::from_slice(&compressed)?;
+        let mut inner = [0u8; 33];
+        inner.copy_from_slice(&compressed);
+
+        let short = Address::p2pkh(&PublicKey::Compressed(inner), Network::Mainnet);
+        assert_eq!(short.to_string(), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+        let long = Address::p2pkh(
+            &PublicKey::Standard(public.serialize_uncompressed()),
+            Network::Mainnet,
+        );
+        assert_eq!(long.to_string(), "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm");
+
+        let testnet = Address::p2pkh(&PublicKey::Compressed(inner), Network::Testnet);
+        assert_eq!(testnet.to_string(), "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r");
+        assert_eq!(testnet.to_string().parse::<Address>()?, testnet);
+        assert_eq!(testnet.hash(), short.hash());
+
+        assert!("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMJ"
+            .parse::<Address>()
+            .is_err());
+        assert!("0OIl".parse::<Address>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn p2sh_wpkh_addresses() -> Result<(), Error> {
+        let compressed =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let public = key::PublicKey::from_slice(&compressed)?;
+        let long = PublicKey::Standard(public.serialize_uncompressed());
+
+        let mainnet = Address::p2sh_wpkh(&long, Network::Mainnet)?;
+        assert_eq!(mainnet.address_type(), AddressType::P2sh);
+        assert_eq!(mainnet.to_string(), "3JvL6Ymt8MVWiCNHC7oWU6nLeHNJKLZGLN");
+
+        let testnet: Address = "2NAUYAHhujozruyzpsFRP63mbrdaU5wnEpN".parse()?;
+        let script_hash = mainnet.hash().try_into().unwrap();
+        assert_eq!(testnet, Address::p2sh(script_hash, Network::Testnet));
+        Ok(())
+    }
+
+    #[test]
+    fn p2wpkh_addresses() -> Result<(), Error> {
+        // BIP173 example key.
+        let compressed =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let mut inner = [0u8; 33];
+        inner.copy_from_slice(&compressed);
+        let public = PublicKey::Compressed(inner);
+
+        let cases = [
+            (
+                Network::Mainnet,
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            ),
+            (
+                Network::Testnet,
+                "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+            ),
+            (
+                Network::Regtest,
+                "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+            ),
+        ];
+        for (network, expected) in cases.iter() {
+            let address = Address::p2wpkh(&public, *network)?;
+            assert_eq!(address.to_string(), *expected);
+            assert_eq!(expected.parse::<Address>()?, address);
+            assert_eq!(expected.to_uppercase().parse::<Address>()?, address);
+        }
+
+        for bad in &[
+            // Bad checksum, mixed case, unknown HRP.
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+            "bc1qw508d6qejxtdg4y5r3zarvarY0c5xw7kv8f3t4",
+            "bt1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            // Version 0 with a 16-byte program.
+            "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+        ] {
+            assert!(bad.parse::<Address>().is_err(), "{}", bad);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn altcoin_addresses() -> Result<(), Error> {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let litecoin = crate::PrivateKey {
+            network: Network::Litecoin,
+            secret,
+            compressed: true,
+        };
+        let dogecoin = crate::PrivateKey {
+            network: Network::Dogecoin,
+            ..litecoin.clone()
+        };
+        assert_eq!(
+            litecoin.to_string(),
+            "T33ydQRKp4FCW5LCLLUB7deioUMoveiwekdwUwyfRDeGZm76aUjV"
+        );
+        assert_eq!(
+            dogecoin.to_string(),
+            "QNcdLVw8fHkixm6NNyN6nVwxKek4u7qrioRbQmjxac5TVoTtZuot"
+        );
+
+        let public = litecoin.public_key()?;
+        let cases = [
+            (
+                Address::p2pkh(&public, Network::Litecoin),
+                "LVuDpNCSSj6pQ7t9Pv6d6sUkLKoqDEVUnJ",
+            ),
+            (
+                Address::p2wpkh(&public, Network::Litecoin)?,
+                "ltc1qw508d6qejxtdg4y5r3zarvary0c5xw7kgmn4n9",
+            ),
+            (
+                Address::p2pkh(&public, Network::Dogecoin),
+                "DFpN6QqFfUm3gKNaxN6tNcab1FArL9cZLE",
+            ),
+        ];
+        for (address, expected) in cases.iter() {
+            assert_eq!(address.to_string(), *expected);
+            assert_eq!(expected.parse::<Address>()?, *address);
+        }
+
+        // Dogecoin has no SegWit.
+        assert!(Address::p2wpkh(&public, Network::Dogecoin).is_err());
+        assert!(Address::p2tr(&public, None, Network::Dogecoin).is_err());
+        Ok(())
+    }
+
+    #[cfg(all(feature = "dash", feature = "zcash"))]
+    #[test]
+    fn dash_and_zcash_addresses() -> Result<(), Error> {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let dash = crate::PrivateKey {
+            network: Network::Dash,
+            secret,
+            compressed: true,
+        };
+        assert_eq!(
+            dash.to_string(),
+            "XBHddvWWiMu3nZhhpTXBQWJMmdz5JNKJD85b9fgKAckCT2coW3Y4"
+        );
+
+        let public = dash.public_key()?;
+        let hash = public.address_hash();
+        let cases = [
+            (
+                Address::p2pkh(&public, Network::Dash),
+                "XmN7PQYWKn5MJFna5fRYgP6mxT2F7xpekE",
+            ),
+            (
+                Address::p2sh(hash, Network::Dash),
+                "7d5vJtfDixGnEFRNcVSRarmaCBZeScHACn",
+            ),
+            (
+                Address::p2pkh(&public, Network::Zcash),
+                "t1UYsZVJkLPeMjxEtACvSxfWuNmddpWfxzs",
+            ),
+            (
+                Address::p2sh(hash, Network::Zcash),
+                "t3VEtV2oBtHxjq7wKHJb3PHsqXHvMRgUmVw",
+            ),
+        ];
+        for (address, expected) in cases.iter() {
+            assert_eq!(address.to_string(), *expected);
+            assert_eq!(expected.parse::<Address>()?, *address);
+        }
+
+        assert!(Address::p2wpkh(&public, Network::Zcash).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn p2tr_addresses() -> anyhow::Result<()> {
+        // BIP86 test vector: first receive address of the all-abandon mnemonic.
+        let root: ExtendedPrivKey = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu"
+            .parse()?;
+        let keys = root.derive_path(&"m/86'/0'/0'/0/0".parse()?)?;
+
+        let address = Address::p2tr(&keys.pubkey(), None, Network::Mainnet)?;
+        assert_eq!(
+            address.to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert_eq!(address.to_string().parse::<Address>()?, address);
+
+        // The same program under the wrong checksum variant.
+        let mut data = vec![1];
+        data.extend(bech32::convert_bits(address.hash(), 8, 5, true)?);
+        let wrong = bech32::encode("bc", &data, Variant::Bech32)?;
+        assert!(wrong.parse::<Address>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn script_pubkeys() -> Result<(), Error> {
+        let cases = [
+            (
+                "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH",
+                "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac",
+            ),
+            (
+                "3JvL6Ymt8MVWiCNHC7oWU6nLeHNJKLZGLN",
+                "a914bcfeb728b584253d5f3f70bcb780e9ef218a68f487",
+            ),
+            (
+                "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            ),
+            (
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+            ),
+            // BIP350 test vectors for undefined witness versions.
+            (
+                "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+                "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            ("BC1SW50QGDZ25J", "6002751e"),
+            (
+                "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+                "5210751e76e8199196d454941c45d1b3a323",
+            ),
+        ];
+        for (address, script) in cases.iter() {
+            let address: Address = address.parse()?;
+            assert_eq!(hex::encode(address.script_pubkey()), *script);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_detects_address_type() -> Result<(), Error> {
+        let cases = [
+            (
+                "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH",
+                AddressType::P2pkh,
+                Network::Mainnet,
+            ),
+            (
+                "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r",
+                AddressType::P2pkh,
+                Network::Testnet,
+            ),
+            (
+                "3JvL6Ymt8MVWiCNHC7oWU6nLeHNJKLZGLN",
+                AddressType::P2sh,
+                Network::Mainnet,
+            ),
+            (
+                "2NAUYAHhujozruyzpsFRP63mbrdaU5wnEpN",
+                AddressType::P2sh,
+                Network::Testnet,
+            ),
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                AddressType::P2wpkh,
+                Network::Mainnet,
+            ),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                AddressType::P2wsh,
+                Network::Testnet,
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                AddressType::P2tr,
+                Network::Mainnet,
+            ),
+            (
+                "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+                AddressType::WitnessUnknown(2),
+                Network::Mainnet,
+            ),
+            (
+                "BC1SW50QGDZ25J",
+                AddressType::WitnessUnknown(16),
+                Network::Mainnet,
+            ),
+        ];
+        for (s, address_type, network) in cases.iter() {
+            let address: Address = s.parse()?;
+            assert_eq!(address.address_type(), *address_type, "{}", s);
+            assert_eq!(address.network(), *network, "{}", s);
+            assert_eq!(address.to_string().parse::<Address>()?, address);
+        }
+
+        for bad in &[
+            // BIP350 invalid addresses: bech32 for versions 1 to 16, and
+            // bech32m for version 0.
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+            "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+            // Programs of 1 and 41 bytes, and a version 0 program of 16.
+            "bc1pw5dgrnzv",
+            "bc10w508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kw5rljs90",
+            "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+            "bc1qr508d6qejxtdg4y5r3zarvaryvaxxpcs",
+            // Unknown HRP and bad Base58Check checksum.
+            "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut",
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMJ",
+        ] {
+            assert!(bad.parse::<Address>().is_err(), "{}", bad);
+        }
+
+        let testnet: Address = "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r".parse()?;
+        assert!(testnet.clone().require_network(Network::Regtest).is_ok());
+        assert!(testnet.require_network(Network::Mainnet).is_err());
+        Ok(())
+    }
+}
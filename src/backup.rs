@@ -0,0 +1,582 @@
+//! Passphrase-encrypted backups of a [Seed](crate::Seed).
+//!
+//! A backup is a versioned binary blob. Version 3, which every export
+//! writes, is laid out as:
+//!
+//! | bytes | content                                          |
+//! |-------|--------------------------------------------------|
+//! | 4     | magic `KMEB`                                     |
+//! | 1     | format version (`3`)                             |
+//! | 1     | KDF, `1` for scrypt or `2` for Argon2id          |
+//! | ..    | KDF parameters                                   |
+//! | 16    | KDF salt                                         |
+//! | ..    | [crypto::seal] output: nonce, ciphertext and tag |
+//!
+//! The scrypt parameters are `log2(N)` in 1 byte, then `r` and `p` in 4
+//! bytes big-endian each. The Argon2id parameters are its memory cost in
+//! KiB, its number of passes and its parallelism, each 4 bytes big-endian.
+//!
+//! Versions 1 and 2 are still read. They encrypt with AES-256-GCM instead,
+//! and have no KDF byte: version 1 uses scrypt and version 2 Argon2id, and
+//! a 12-byte AES-256-GCM nonce follows the salt.
+//!
+//! The KDF parameters come from the unauthenticated header, so they are
+//! bounded before any key is derived: at most 1 GiB of memory, scrypt
+//! `log2(N) <= 20`, `r <= 32` and `p <= 16`, and Argon2id at most 16 passes
+//! and 16 lanes. Larger parameters are refused on export as well.
+//!
+//! The 32-byte key is derived from the passphrase with the [Kdf], and the
+//! header is authenticated as associated data. The plaintext is the 64-byte
+//! seed followed by the UTF-8 mnemonic sentence, so a seed built with a custom
+//! salt or passphrase restores exactly.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand_core::{OsRng, RngCore};
+use std::convert::TryInto;
+use std::str;
+use thiserror::Error;
+
+use crate::bip39::{Bip39Error, Mnemonic, Seed};
+use crate::crypto;
+
+const MAGIC: &[u8; 4] = b"KMEB";
+const VERSION_SCRYPT: u8 = 1;
+const VERSION_ARGON2ID: u8 = 2;
+const VERSION_SEALED: u8 = 3;
+const KDF_SCRYPT: u8 = 1;
+const KDF_ARGON2ID: u8 = 2;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+const SEED_LEN: usize = 64;
+const PREFIX_LEN: usize = MAGIC.len() + 1;
+const DEFAULT_SCRYPT_LOG_N: u8 = 15;
+const DEFAULT_SCRYPT_R: u32 = 8;
+const DEFAULT_SCRYPT_P: u32 = 1;
+const DEFAULT_ARGON2_MEMORY_KIB: u32 = 64 * 1024;
+const DEFAULT_ARGON2_ITERATIONS: u32 = 3;
+const DEFAULT_ARGON2_PARALLELISM: u32 = 4;
+const MAX_KDF_MEMORY: u64 = 1 << 30;
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+const MAX_ARGON2_ITERATIONS: u32 = 16;
+const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+/// Error originating from [backup](backup) module.
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("Not a keymaker backup")]
+    InvalidFormat,
+
+    #[error("Unsupported backup version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Unknown key derivation function {0}")]
+    UnknownKdf(u8),
+
+    #[error("Invalid scrypt parameters log_n = {0}, r = {1}, p = {2}")]
+    InvalidKdfParams(u8, u32, u32),
+
+    #[error("Invalid Argon2id parameters m = {0} KiB, t = {1}, p = {2}")]
+    InvalidArgon2Params(u32, u32, u32),
+
+    #[error("Wrong passphrase or corrupted backup")]
+    DecryptionFailed,
+
+    #[error("Backup does not contain a valid mnemonic: {0}")]
+    InvalidMnemonic(#[from] Bip39Error),
+}
+
+/// The function deriving the encryption key from the passphrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+    /// scrypt with `N = 2^log_n`.
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    /// Argon2id, memory-hard against GPU and ASIC guessing.
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+impl Kdf {
+    /// scrypt with `N = 2^15`, `r = 8` and `p = 1`.
+    pub fn scrypt() -> Self {
+        Kdf::Scrypt {
+            log_n: DEFAULT_SCRYPT_LOG_N,
+            r: DEFAULT_SCRYPT_R,
+            p: DEFAULT_SCRYPT_P,
+        }
+    }
+
+    /// Argon2id with 64 MiB of memory, 3 passes and 4 lanes, the second
+    /// recommended option of RFC 9106.
+    pub fn argon2id() -> Self {
+        Kdf::Argon2id {
+            memory_kib: DEFAULT_ARGON2_MEMORY_KIB,
+            iterations: DEFAULT_ARGON2_ITERATIONS,
+            parallelism: DEFAULT_ARGON2_PARALLELISM,
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Kdf::Scrypt { .. } => KDF_SCRYPT,
+            Kdf::Argon2id { .. } => KDF_ARGON2ID,
+        }
+    }
+
+    fn write_params(&self, blob: &mut Vec<u8>) {
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                blob.push(log_n);
+                blob.extend_from_slice(&r.to_be_bytes());
+                blob.extend_from_slice(&p.to_be_bytes());
+            }
+            Kdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                blob.extend_from_slice(&memory_kib.to_be_bytes());
+                blob.extend_from_slice(&iterations.to_be_bytes());
+                blob.extend_from_slice(&parallelism.to_be_bytes());
+            }
+        }
+    }
+
+    /// Read the parameters of the KDF `id` from the start of `data`,
+    /// returning how many bytes they take.
+    fn read_params(id: u8, data: &[u8]) -> Result<(Kdf, usize), BackupError> {
+        let be_u32 = |at: usize| -> Result<u32, BackupError> {
+            let bytes = data.get(at..at + 4).ok_or(BackupError::InvalidFormat)?;
+            Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+        };
+        match id {
+            KDF_SCRYPT => {
+                let log_n = *data.first().ok_or(BackupError::InvalidFormat)?;
+                let kdf = Kdf::Scrypt {
+                    log_n,
+                    r: be_u32(1)?,
+                    p: be_u32(5)?,
+                };
+                Ok((kdf, 9))
+            }
+            KDF_ARGON2ID => {
+                let kdf = Kdf::Argon2id {
+                    memory_kib: be_u32(0)?,
+                    iterations: be_u32(4)?,
+                    parallelism: be_u32(8)?,
+                };
+                Ok((kdf, 12))
+            }
+            id => Err(BackupError::UnknownKdf(id)),
+        }
+    }
+
+    /// Refuse parameters that would take more memory or time than any
+    /// sensible backup, before a crafted header can make us spend them.
+    fn check_bounds(&self) -> Result<(), BackupError> {
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                // scrypt uses 128 * r * N bytes.
+                let in_bounds = log_n <= MAX_SCRYPT_LOG_N
+                    && r <= MAX_SCRYPT_R
+                    && p <= MAX_SCRYPT_P
+                    && (128 * u64::from(r)) << log_n <= MAX_KDF_MEMORY;
+                if !in_bounds {
+                    return Err(BackupError::InvalidKdfParams(log_n, r, p));
+                }
+            }
+            Kdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                let in_bounds = u64::from(memory_kib) * 1024 <= MAX_KDF_MEMORY
+                    && iterations <= MAX_ARGON2_ITERATIONS
+                    && parallelism <= MAX_ARGON2_PARALLELISM;
+                if !in_bounds {
+                    return Err(BackupError::InvalidArgon2Params(
+                        memory_kib,
+                        iterations,
+                        parallelism,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], BackupError> {
+        self.check_bounds()?;
+        let mut key = [0u8; KEY_LEN];
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, KEY_LEN)
+                    .map_err(|_| BackupError::InvalidKdfParams(log_n, r, p))?;
+                scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+                    .map_err(|_| BackupError::InvalidKdfParams(log_n, r, p))?;
+            }
+            Kdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                let invalid =
+                    |_| BackupError::InvalidArgon2Params(memory_kib, iterations, parallelism);
+                let params =
+                    argon2::Params::new(memory_kib, iterations, parallelism, Some(KEY_LEN))
+                        .map_err(invalid)?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(invalid)?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf::scrypt()
+    }
+}
+
+impl Seed {
+    /// Encrypt the seed and its mnemonic under `passphrase`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::bip39::{Seed, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let blob = seed.export_encrypted("correct horse").unwrap();
+    ///
+    /// let restored = Seed::import_encrypted(&blob, "correct horse").unwrap();
+    /// assert_eq!(restored.mnemonic, seed.mnemonic);
+    /// assert!(Seed::import_encrypted(&blob, "battery staple").is_err());
+    /// ```
+    pub fn export_encrypted(&self, passphrase: &str) -> Result<Vec<u8>, BackupError> {
+        encrypt(self, passphrase, Kdf::default())
+    }
+
+    /// Encrypt the seed and its mnemonic under `passphrase`, with the key
+    /// derived by `kdf`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::backup::Kdf;
+    /// use keymaker::bip39::{Seed, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let kdf = Kdf::Argon2id { memory_kib: 1024, iterations: 2, parallelism: 1 };
+    /// let blob = seed.export_encrypted_with("correct horse", kdf).unwrap();
+    ///
+    /// let restored = Seed::import_encrypted(&blob, "correct horse").unwrap();
+    /// assert_eq!(restored.mnemonic, seed.mnemonic);
+    /// ```
+    pub fn export_encrypted_with(
+        &self,
+        passphrase: &str,
+        kdf: Kdf,
+    ) -> Result<Vec<u8>, BackupError> {
+        encrypt(self, passphrase, kdf)
+    }
+
+    /// Decrypt a backup created by [export_encrypted](Seed::export_encrypted),
+    /// of the current or an earlier version.
+    pub fn import_encrypted(data: &[u8], passphrase: &str) -> Result<Seed, BackupError> {
+        if data.len() < PREFIX_LEN || &data[..MAGIC.len()] != MAGIC {
+            return Err(BackupError::InvalidFormat);
+        }
+
+        let plaintext = match data[4] {
+            VERSION_SEALED => open_sealed(data, passphrase)?,
+            VERSION_SCRYPT | VERSION_ARGON2ID => open_aes_gcm(data, passphrase)?,
+            version => return Err(BackupError::UnsupportedVersion(version)),
+        };
+        if plaintext.len() <= SEED_LEN {
+            return Err(BackupError::InvalidFormat);
+        }
+
+        let (entropy, sentence) = plaintext.split_at(SEED_LEN);
+        let sentence = str::from_utf8(sentence).map_err(|_| BackupError::InvalidFormat)?;
+
+        let seed = Seed {
+            mnemonic: sentence.parse::<Mnemonic>()?,
+            entropy: entropy.try_into().unwrap(),
+            hex: hex::encode(entropy),
+        };
+        Ok(seed)
+    }
+}
+
+/// Decrypt the plaintext of a version 3 backup.
+fn open_sealed(data: &[u8], passphrase: &str) -> Result<Vec<u8>, BackupError> {
+    let id = *data.get(PREFIX_LEN).ok_or(BackupError::InvalidFormat)?;
+    let (kdf, params_len) = Kdf::read_params(id, &data[PREFIX_LEN + 1..])?;
+    let header_len = PREFIX_LEN + 1 + params_len + SALT_LEN;
+    if data.len() < header_len {
+        return Err(BackupError::InvalidFormat);
+    }
+    let (header, sealed) = data.split_at(header_len);
+
+    let key = kdf.derive_key(passphrase, &header[header_len - SALT_LEN..])?;
+    crypto::open(&key, sealed, header).map_err(|_| BackupError::DecryptionFailed)
+}
+
+/// Decrypt the plaintext of a version 1 or 2 backup, whose version picks the
+/// KDF.
+fn open_aes_gcm(data: &[u8], passphrase: &str) -> Result<Vec<u8>, BackupError> {
+    let kdf_id = match data[4] {
+        VERSION_SCRYPT => KDF_SCRYPT,
+        _ => KDF_ARGON2ID,
+    };
+    let (kdf, params_len) = Kdf::read_params(kdf_id, &data[PREFIX_LEN..])?;
+    let salt_start = PREFIX_LEN + params_len;
+    let header_len = salt_start + SALT_LEN + NONCE_LEN;
+    if data.len() < header_len {
+        return Err(BackupError::InvalidFormat);
+    }
+    let salt = &data[salt_start..salt_start + SALT_LEN];
+    let nonce = &data[salt_start + SALT_LEN..header_len];
+
+    let key = kdf.derive_key(passphrase, salt)?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|_| BackupError::InvalidFormat)?;
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: &data[header_len..],
+                aad: &data[..header_len],
+            },
+        )
+        .map_err(|_| BackupError::DecryptionFailed)
+}
+
+fn encrypt(seed: &Seed, passphrase: &str, kdf: Kdf) -> Result<Vec<u8>, BackupError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let mut blob = Vec::with_capacity(PREFIX_LEN + 13 + SALT_LEN + SEED_LEN + 256);
+    blob.extend_from_slice(MAGIC);
+    blob.push(VERSION_SEALED);
+    blob.push(kdf.id());
+    kdf.write_params(&mut blob);
+    blob.extend_from_slice(&salt);
+
+    let mut plaintext = seed.entropy.to_vec();
+    plaintext.extend_from_slice(seed.mnemonic.to_string().as_bytes());
+
+    let key = kdf.derive_key(passphrase, &salt)?;
+    let sealed = crypto::seal(&key, &plaintext, &blob);
+    blob.extend(sealed);
+    Ok(blob)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::bip39::SeedBuilder;
+
+    /// A version 1 or 2 backup, as written before version 3.
+    fn encrypt_aes_gcm(seed: &Seed, passphrase: &str, kdf: Kdf) -> Result<Vec<u8>, BackupError> {
+        let salt = [5u8; SALT_LEN];
+        let nonce = [6u8; NONCE_LEN];
+        let mut blob = MAGIC.to_vec();
+        blob.push(match kdf {
+            Kdf::Scrypt { .. } => VERSION_SCRYPT,
+            Kdf::Argon2id { .. } => VERSION_ARGON2ID,
+        });
+        kdf.write_params(&mut blob);
+        blob.extend_from_slice(&salt);
+        blob.extend_from_slice(&nonce);
+
+        let mut plaintext = seed.entropy.to_vec();
+        plaintext.extend_from_slice(seed.mnemonic.to_string().as_bytes());
+        let key = kdf.derive_key(passphrase, &salt)?;
+        let ciphertext = Aes256Gcm::new_from_slice(&key)
+            .unwrap()
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &blob,
+                },
+            )
+            .unwrap();
+        blob.extend(ciphertext);
+        Ok(blob)
+    }
+
+    #[test]
+    fn backup_round_trip() -> Result<(), BackupError> {
+        let seed = SeedBuilder::new().passphrase("extra").build()?;
+        let blob = encrypt(
+            &seed,
+            "hunter2",
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 8,
+                p: 1,
+            },
+        )?;
+
+        let restored = Seed::import_encrypted(&blob, "hunter2")?;
+        assert_eq!(restored.mnemonic, seed.mnemonic);
+        assert_eq!(restored.entropy[..], seed.entropy[..]);
+
+        assert!(matches!(
+            Seed::import_encrypted(&blob, "hunter3"),
+            Err(BackupError::DecryptionFailed)
+        ));
+
+        let mut tampered = blob.clone();
+        tampered[5] ^= 1;
+        assert!(Seed::import_encrypted(&tampered, "hunter2").is_err());
+
+        // Header parameters far beyond the bounds are refused before any key
+        // is derived, which would otherwise take gigabytes or hours.
+        assert_eq!(blob[4], VERSION_SEALED);
+        let params = PREFIX_LEN + 1;
+        let mut oversized = blob.clone();
+        oversized[params] = 40;
+        assert!(matches!(
+            Seed::import_encrypted(&oversized, "hunter2"),
+            Err(BackupError::InvalidKdfParams(40, 8, 1))
+        ));
+        let mut oversized = blob.clone();
+        oversized[params + 5..params + 9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            Seed::import_encrypted(&oversized, "hunter2"),
+            Err(BackupError::InvalidKdfParams(4, 8, u32::MAX))
+        ));
+        // Each within its own bound, but 4 GiB together.
+        let kdf = Kdf::Scrypt {
+            log_n: 20,
+            r: 32,
+            p: 1,
+        };
+        assert!(matches!(
+            encrypt(&seed, "hunter2", kdf),
+            Err(BackupError::InvalidKdfParams(20, 32, 1))
+        ));
+
+        let mut unknown = blob.clone();
+        unknown[PREFIX_LEN] = 3;
+        assert!(matches!(
+            Seed::import_encrypted(&unknown, "hunter2"),
+            Err(BackupError::UnknownKdf(3))
+        ));
+
+        let mut future = blob;
+        future[4] = 4;
+        assert!(matches!(
+            Seed::import_encrypted(&future, "hunter2"),
+            Err(BackupError::UnsupportedVersion(4))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn argon2id_backup() -> Result<(), BackupError> {
+        let seed = SeedBuilder::new().build()?;
+        let kdf = Kdf::Argon2id {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let blob = encrypt(&seed, "hunter2", kdf)?;
+        assert_eq!(blob[4], VERSION_SEALED);
+        assert_eq!(blob[PREFIX_LEN], KDF_ARGON2ID);
+        let params = PREFIX_LEN + 1;
+        assert_eq!(
+            Kdf::read_params(blob[PREFIX_LEN], &blob[params..])?,
+            (kdf, 12)
+        );
+
+        let restored = Seed::import_encrypted(&blob, "hunter2")?;
+        assert_eq!(restored.mnemonic, seed.mnemonic);
+        assert!(matches!(
+            Seed::import_encrypted(&blob, "hunter3"),
+            Err(BackupError::DecryptionFailed)
+        ));
+
+        // Too little memory for the lanes.
+        let kdf = Kdf::Argon2id {
+            memory_kib: 1,
+            iterations: 1,
+            parallelism: 1,
+        };
+        assert!(matches!(
+            encrypt(&seed, "hunter2", kdf),
+            Err(BackupError::InvalidArgon2Params(1, 1, 1))
+        ));
+
+        // Header parameters far beyond the bounds are refused before any key
+        // is derived, which would otherwise take gigabytes or hours.
+        let mut oversized = blob.clone();
+        oversized[params..params + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            Seed::import_encrypted(&oversized, "hunter2"),
+            Err(BackupError::InvalidArgon2Params(u32::MAX, 1, 1))
+        ));
+        let mut oversized = blob.clone();
+        oversized[params + 4..params + 8].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            Seed::import_encrypted(&oversized, "hunter2"),
+            Err(BackupError::InvalidArgon2Params(64, u32::MAX, 1))
+        ));
+
+        // A header cut short.
+        assert!(matches!(
+            Seed::import_encrypted(&blob[..params + 20], "hunter2"),
+            Err(BackupError::InvalidFormat)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn reads_earlier_versions() -> Result<(), BackupError> {
+        let seed = SeedBuilder::new().passphrase("extra").build()?;
+        let kdfs = [
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Argon2id {
+                memory_kib: 64,
+                iterations: 1,
+                parallelism: 1,
+            },
+        ];
+        for (version, kdf) in [VERSION_SCRYPT, VERSION_ARGON2ID].iter().zip(kdfs.iter()) {
+            let blob = encrypt_aes_gcm(&seed, "hunter2", *kdf)?;
+            assert_eq!(blob[4], *version);
+
+            let restored = Seed::import_encrypted(&blob, "hunter2")?;
+            assert_eq!(restored.mnemonic, seed.mnemonic);
+            assert_eq!(restored.entropy[..], seed.entropy[..]);
+            assert!(matches!(
+                Seed::import_encrypted(&blob, "hunter3"),
+                Err(BackupError::DecryptionFailed)
+            ));
+
+            let mut tampered = blob.clone();
+            tampered[blob.len() - 1] ^= 1;
+            assert!(matches!(
+                Seed::import_encrypted(&tampered, "hunter2"),
+                Err(BackupError::DecryptionFailed)
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+//! [BIP173] bech32 and [BIP350] bech32m encoding, used by native SegWit
+//! addresses.
+//!
+//! Decoding is strict: mixed case, characters outside the charset, strings
+//! over 90 characters and bad checksums are all rejected.
+//!
+//! [BIP173]: https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+//! [BIP350]: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+
+use thiserror::Error;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const SEPARATOR: char = '1';
+const CHECKSUM_LEN: usize = 6;
+const MAX_LEN: usize = 90;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+/// Error originating from [bech32](crate::bech32) module.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Bech32Error {
+    #[error("Missing separator")]
+    MissingSeparator,
+    #[error("Invalid human-readable part")]
+    InvalidHrp,
+    #[error("Invalid character {0:?}")]
+    InvalidChar(char),
+    #[error("Mixed-case string")]
+    MixedCase,
+    #[error("Invalid length {0}")]
+    InvalidLength(usize),
+    #[error("Invalid checksum")]
+    InvalidChecksum,
+    #[error("Invalid padding")]
+    InvalidPadding,
+}
+
+/// The checksum variant of a bech32 string.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Variant {
+    /// BIP173, used by SegWit version 0.
+    Bech32,
+    /// BIP350, used by SegWit version 1 and later.
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => BECH32M_CONST,
+        }
+    }
+}
+
+/// Encode 5-bit `data` under the human-readable part `hrp`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::bech32::{self, Variant};
+///
+/// let data = bech32::convert_bits(&[0xff; 4], 8, 5, true).unwrap();
+/// let encoded = bech32::encode("test", &data, Variant::Bech32m).unwrap();
+/// assert_eq!(
+///     bech32::decode(&encoded).unwrap(),
+///     ("test".to_string(), data, Variant::Bech32m)
+/// );
+/// ```
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> Result<String, Bech32Error> {
+    let len = hrp.len() + 1 + data.len() + CHECKSUM_LEN;
+    if len > MAX_LEN {
+        return Err(Bech32Error::InvalidLength(len));
+    }
+    encode_unbounded(hrp, data, variant)
+}
+
+/// Encode as [encode](encode) does, but past the 90-character limit, as
+/// Cardano addresses need.
+pub(crate) fn encode_unbounded(
+    hrp: &str,
+    data: &[u8],
+    variant: Variant,
+) -> Result<String, Bech32Error> {
+    let hrp = hrp.to_lowercase();
+    check_hrp(&hrp)?;
+    if let Some(&b) = data.iter().find(|b| **b >= 32) {
+        return Err(Bech32Error::InvalidChar(b as char));
+    }
+
+    let mut encoded = hrp.clone();
+    encoded.push(SEPARATOR);
+    for b in data.iter().chain(&checksum(&hrp, data, variant)) {
+        encoded.push(CHARSET[*b as usize] as char);
+    }
+    Ok(encoded)
+}
+
+/// Decode a bech32 or bech32m string into its lowercase human-readable part,
+/// 5-bit data without the checksum, and the variant its checksum matched.
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), Bech32Error> {
+    if s.len() > MAX_LEN {
+        return Err(Bech32Error::InvalidLength(s.len()));
+    }
+    let has_lower = s.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = s.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(Bech32Error::MixedCase);
+    }
+    let s = s.to_lowercase();
+
+    let split = s.rfind(SEPARATOR).ok_or(Bech32Error::MissingSeparator)?;
+    let (hrp, rest) = s.split_at(split);
+    check_hrp(hrp)?;
+    if rest.len() < 1 + CHECKSUM_LEN {
+        return Err(Bech32Error::InvalidLength(s.len()));
+    }
+
+    let data = rest[1..]
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|b| *b as char == c)
+                .map(|i| i as u8)
+                .ok_or(Bech32Error::InvalidChar(c))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let variant = match polymod(&[expand_hrp(hrp), data.clone()].concat()) {
+        1 => Variant::Bech32,
+        BECH32M_CONST => Variant::Bech32m,
+        _ => return Err(Bech32Error::InvalidChecksum),
+    };
+    let data = data[..data.len() - CHECKSUM_LEN].to_vec();
+    Ok((hrp.to_string(), data, variant))
+}
+
+/// Regroup `data` from `from`-bit to `to`-bit values, e.g. bytes to the 5-bit
+/// values bech32 encodes. When converting back, `pad` must be `false` and any
+/// leftover bits must be zero.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Bech32Error> {
+    let max = (1u32 << to) - 1;
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+    for value in data {
+        let value = u32::from(*value);
+        if value >> from != 0 {
+            return Err(Bech32Error::InvalidChar(value as u8 as char));
+        }
+        acc = (acc << from) | value;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+    Ok(out)
+}
+
+fn check_hrp(hrp: &str) -> Result<(), Bech32Error> {
+    if hrp.is_empty() || hrp.len() > 83 || !hrp.bytes().all(|b| (33..=126).contains(&b)) {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    Ok(())
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    values.iter().fold(1, |chk, value| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(*value);
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, g)| chk ^ g)
+    })
+}
+
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    let mut expanded: Vec<u8> = bytes.iter().map(|b| b >> 5).collect();
+    expanded.push(0);
+    expanded.extend(bytes.iter().map(|b| b & 31));
+    expanded
+}
+
+fn checksum(hrp: &str, data: &[u8], variant: Variant) -> [u8; CHECKSUM_LEN] {
+    let values = [expand_hrp(hrp), data.to_vec(), vec![0; CHECKSUM_LEN]].concat();
+    let modulus = polymod(&values) ^ variant.constant();
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    for (i, c) in checksum.iter_mut().enumerate() {
+        *c = ((modulus >> (5 * (5 - i))) & 31) as u8;
+    }
+    checksum
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn bip173_vectors() {
+        for valid in &[
+            "A12UEL5L",
+            "a12uel5l",
+            "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+            "?1ezyfcl",
+        ] {
+            let (hrp, data, variant) = decode(valid).unwrap();
+            assert_eq!(variant, Variant::Bech32);
+            assert_eq!(encode(&hrp, &data, variant).unwrap(), valid.to_lowercase());
+        }
+        let longest = format!("11{}c8247j", "q".repeat(82));
+        assert_eq!(decode(&longest).unwrap().1, vec![0; 82]);
+
+        let invalid = [
+            ("\u{20}1nwldj5", Bech32Error::InvalidHrp),
+            ("\u{7f}1axkwrx", Bech32Error::InvalidHrp),
+            ("pzry9x0s0muk", Bech32Error::MissingSeparator),
+            ("1pzry9x0s0muk", Bech32Error::InvalidHrp),
+            ("x1b4n0q5v", Bech32Error::InvalidChar('b')),
+            ("li1dgmt3", Bech32Error::InvalidLength(8)),
+            ("A1G7SGD8", Bech32Error::InvalidChecksum),
+            ("10a06t8", Bech32Error::InvalidHrp),
+            ("1qzzfhee", Bech32Error::InvalidHrp),
+            (
+                "Abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+                Bech32Error::MixedCase,
+            ),
+        ];
+        for (s, err) in invalid.iter() {
+            assert_eq!(decode(s).unwrap_err(), *err, "{}", s);
+        }
+        assert!(matches!(
+            decode("an84characterslonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1569pvx"),
+            Err(Bech32Error::InvalidLength(_))
+        ));
+    }
+
+    #[test]
+    fn bip350_vectors() {
+        for valid in &[
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ] {
+            let (hrp, data, variant) = decode(valid).unwrap();
+            assert_eq!(variant, Variant::Bech32m);
+            assert_eq!(encode(&hrp, &data, variant).unwrap(), valid.to_lowercase());
+        }
+        assert_eq!(
+            decode("M1VUXWEZ").unwrap_err(),
+            Bech32Error::InvalidChecksum
+        );
+    }
+
+    #[test]
+    fn convert_bits_round_trip() {
+        let bytes = [0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91];
+        let five = convert_bits(&bytes, 8, 5, true).unwrap();
+        assert_eq!(convert_bits(&five, 5, 8, false).unwrap(), bytes);
+        // A trailing group with non-zero padding bits is rejected.
+        let mut bad = five;
+        *bad.last_mut().unwrap() |= 1;
+        assert_eq!(
+            convert_bits(&bad, 5, 8, false),
+            Err(Bech32Error::InvalidPadding)
+        );
+    }
+}
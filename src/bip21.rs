@@ -0,0 +1,147 @@
+//! [BIP21] payment URIs, such as
+//! `bitcoin:bc1q...?amount=0.001&label=Coffee`, which wallets open to fill in
+//! a payment.
+//!
+//! [BIP21]: https://github.com/bitcoin/bips/blob/master/bip-0021.mediawiki
+
+use std::fmt;
+
+use crate::{Address, Network};
+
+const SATOSHIS_PER_COIN: u64 = 100_000_000;
+
+/// A payment request for an [Address](crate::Address).
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::bip21::Uri;
+/// use keymaker::{Address, Network};
+///
+/// let address: Address = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH".parse().unwrap();
+/// let uri = Uri::new(address).amount(150_000).label("Coffee & cake");
+/// assert_eq!(
+///     uri.to_string(),
+///     "bitcoin:1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH?amount=0.0015&label=Coffee%20%26%20cake"
+/// );
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Uri {
+    address: Address,
+    amount: Option<u64>,
+    label: Option<String>,
+    message: Option<String>,
+}
+
+impl Uri {
+    pub fn new(address: Address) -> Self {
+        Uri {
+            address,
+            amount: None,
+            label: None,
+            message: None,
+        }
+    }
+
+    /// Request `amount` in satoshis.
+    pub fn amount(mut self, amount: u64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Name the recipient.
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Describe the payment to the payer.
+    pub fn message(mut self, message: &str) -> Self {
+        self.message = Some(message.to_string());
+        self
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The URI scheme of the address's coin.
+    fn scheme(&self) -> &'static str {
+        match self.address.network() {
+            Network::Litecoin => "litecoin",
+            Network::Dogecoin => "dogecoin",
+            _ => "bitcoin",
+        }
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.scheme(), self.address)?;
+
+        let mut params = vec![];
+        if let Some(amount) = self.amount {
+            let fraction = format!("{:08}", amount % SATOSHIS_PER_COIN);
+            let fraction = fraction.trim_end_matches('0');
+            let whole = amount / SATOSHIS_PER_COIN;
+            params.push(if fraction.is_empty() {
+                format!("amount={}", whole)
+            } else {
+                format!("amount={}.{}", whole, fraction)
+            });
+        }
+        if let Some(label) = &self.label {
+            params.push(format!("label={}", percent_encode(label)));
+        }
+        if let Some(message) = &self.message {
+            params.push(format!("message={}", percent_encode(message)));
+        }
+
+        if !params.is_empty() {
+            write!(f, "?{}", params.join("&"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Escape every byte outside the RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn uri_parameters() {
+        let address: Address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+            .parse()
+            .unwrap();
+        let uri = Uri::new(address.clone());
+        assert_eq!(
+            uri.to_string(),
+            "bitcoin:bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+
+        let uri = uri.amount(2 * SATOSHIS_PER_COIN).message("Zürich");
+        assert_eq!(
+            uri.to_string(),
+            "bitcoin:bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4?amount=2&message=Z%C3%BCrich"
+        );
+
+        let litecoin: Address = "LVuDpNCSSj6pQ7t9Pv6d6sUkLKoqDEVUnJ".parse().unwrap();
+        assert_eq!(
+            Uri::new(litecoin).amount(1).to_string(),
+            "litecoin:LVuDpNCSSj6pQ7t9Pv6d6sUkLKoqDEVUnJ?amount=0.00000001"
+        );
+    }
+}
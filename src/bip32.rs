@@ -1,14 +1,33 @@
 use anyhow::{Context, Result};
-use ring::hmac::{self, HMAC_SHA512};
+use base58::{FromBase58, ToBase58};
+use lru::LruCache;
+use rand_core::{OsRng, RngCore};
+use ring::signature::{Ed25519KeyPair, KeyPair as _};
 use secp256k1::{self, key};
 use std::convert::TryInto;
 use std::fmt;
+use std::num::NonZeroUsize;
+use std::ops::Range;
 use std::str;
+use std::sync::{Arc, Mutex, PoisonError};
 use thiserror::Error;
 
-use crate::{ChainCode, Network, PrivateKey, PublicKey, SECP256K1};
+use crate::bip39::{self, Bip39Error, Language, Mnemonic};
+use crate::confirmation::{Acknowledgement, Confirmation};
+use crate::crypto;
+use crate::path::HARDENED_OFFSET;
+use crate::{
+    Address, AddressHash, AddressType, ChainCode, ChildNumber, CoinType, DerivationPath,
+    DisplayLayout, Error, Fingerprint, Network, PrivateKey, PublicKey, Purpose, Secret, SECP256K1,
+};
 
-const DEFAULT_KEY: &str = "default_seed";
+const DEFAULT_KEY: &str = "Bitcoin seed";
+const ED25519_KEY: &str = "ed25519 seed";
+const BACKUP_HALF_LEN: usize = 24;
+const BACKUP_CHECKSUM_LEN: usize = 3;
+const BACKUP_PHRASE_LEN: usize = 2 * BACKUP_HALF_LEN + BACKUP_CHECKSUM_LEN;
+const BACKUP_CHECKSUM_TAG: &str = "keymaker/backup-phrase";
+const EXTENDED_KEY_LEN: usize = 82;
 
 /// Error originating from [bip32](bip32) module.
 #[derive(Error, Debug)]
@@ -18,6 +37,20 @@ pub enum Bip32Error {
     EmptyKey,
     #[error("Could not convert from slice")]
     TryFromSliceError,
+    #[error("Hardened child {0} cannot be derived from a public key")]
+    HardenedPublicDerivation(u32),
+    #[error("Child {0} is not a valid key, skip to the next index")]
+    InvalidChild(u32),
+    #[error("Invalid derivation path {0}")]
+    InvalidPath(String),
+    #[error("Child index {0} is not below 2^31")]
+    InvalidChildNumber(u32),
+    #[error("Child {0} must be hardened, ed25519 only supports hardened derivation")]
+    NormalEd25519Derivation(u32),
+    #[error("Unknown coin type {0}")]
+    UnknownCoinType(String),
+    #[error("Only a master key has a backup phrase, not a key at depth {0}")]
+    NotMasterKey(u8),
 }
 
 /// Define a pair of private and public keys.
@@ -57,36 +90,152 @@ impl KeyPair {
         &self.private
     }
 
+    /// Pair `private` with its public key in the `compressed` or
+    /// uncompressed form. The private key's own flag is set to match, so its
+    /// WIF and compact signatures agree with the public key.
+    ///
+    /// Fails with `Error::InvalidPrivate` if the secret is zero or not below
+    /// the curve order.
     pub fn from_private(private: PrivateKey, compressed: bool) -> Result<Self> {
-        let secret_key: key::SecretKey = key::SecretKey::from_slice(&private.secret[..])
-            .with_context(|| Bip32Error::TryFromSliceError)?;
-        let pub_key = key::PublicKey::from_secret_key(&SECP256K1, &secret_key);
+        let private = PrivateKey {
+            compressed,
+            ..private
+        };
+        let public = private.public_key().map_err(|_| Error::InvalidPrivate)?;
 
-        let public: PublicKey;
-        if compressed {
-            public = PublicKey::Standard(pub_key.serialize_uncompressed());
-        } else {
-            public = PublicKey::Compressed(pub_key.serialize());
+        Ok(Self { private, public })
+    }
+
+    /// The key pair held by `keys`, in the form they were created with.
+    pub fn from_extended(keys: &ExtendedPrivKey) -> Self {
+        Self {
+            private: keys.privkey(),
+            public: keys.pubkey(),
         }
+    }
+}
 
-        Ok(Self { private, public })
+/// Builds a [KeyPair](KeyPair) from a chosen source, or from a fresh random
+/// secret if none is given.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::bip32::KeyPairBuilder;
+/// use keymaker::{ExtendedPrivKey, Network, SeedBuilder};
+///
+/// let fresh = KeyPairBuilder::new()
+///     .network(Network::Testnet)
+///     .build()
+///     .unwrap();
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+/// let uncompressed = KeyPairBuilder::new()
+///     .extended(&keys)
+///     .compressed(false)
+///     .build()
+///     .unwrap();
+/// assert_eq!(uncompressed.private().secret, keys.privkey().secret);
+/// ```
+pub struct KeyPairBuilder {
+    network: Network,
+    compressed: bool,
+    secret: Option<Secret>,
+}
+
+impl Default for KeyPairBuilder {
+    fn default() -> Self {
+        KeyPairBuilder {
+            network: Network::Mainnet,
+            compressed: true,
+            secret: None,
+        }
     }
 }
 
-/// Represents a derivable master key for all child keys.
-pub struct MasterExtendedKeys {
-    public: PublicKey,
+impl KeyPairBuilder {
+    /// Instantiate a default [KeyPairBuilder](KeyPairBuilder), for a
+    /// compressed mainnet key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    /// Use `secret` as the private key.
+    pub fn secret(mut self, secret: Secret) -> Self {
+        self.secret = Some(secret);
+        self
+    }
+
+    /// Use the private key of `keys`, along with its network and form.
+    pub fn extended(self, keys: &ExtendedPrivKey) -> Self {
+        let private = keys.privkey();
+        self.secret(private.secret)
+            .network(private.network)
+            .compressed(private.compressed)
+    }
+
+    pub fn build(self) -> Result<KeyPair> {
+        let secret = match self.secret {
+            Some(secret) => secret,
+            None => loop {
+                let mut secret = Secret::default();
+                OsRng.fill_bytes(&mut secret);
+                if key::SecretKey::from_slice(&secret).is_ok() {
+                    break secret;
+                }
+            },
+        };
+        let private = PrivateKey {
+            network: self.network,
+            secret,
+            compressed: self.compressed,
+        };
+        KeyPair::from_private(private, self.compressed)
+    }
+}
+
+/// Represents an extended private key: a key pair and chain code from which
+/// child keys are derived, along with its position in the derivation tree.
+///
+/// Serializes to and from the standard Base58Check `xprv`/`tprv` format.
+#[derive(Clone)]
+pub struct ExtendedPrivKey {
+    public: key::PublicKey,
     private: PrivateKey,
     chain_code: ChainCode,
+    depth: u8,
+    parent_fingerprint: Fingerprint,
+    child_number: ChildNumber,
+    cache: Option<DerivationCache<ExtendedPrivKey>>,
 }
 
-impl MasterExtendedKeys {
-    /// Create a new [MasterExtendedKeys](MasterExtendedKeys).
+/// Keys derived from an extended key, shared by its clones.
+type DerivationCache<K> = Arc<Mutex<LruCache<DerivationPath, K>>>;
+
+/// The master extended keys are the root [ExtendedPrivKey](ExtendedPrivKey)
+/// of a derivation tree.
+pub type MasterExtendedKeys = ExtendedPrivKey;
+
+impl ExtendedPrivKey {
+    /// Create new master keys from a seed.
     ///
     /// # Arguments
     ///
     /// * `msg` - 64-byte array of a seed message derived from [bip32::Seed](bip32::Seed).
-    /// * `key` - Optional key. Default to "default_seed".
+    /// * `key` - Optional HMAC key. Default to "Bitcoin seed" as BIP32
+    ///   specifies. Any other key is non-standard and derives keys that no
+    ///   other wallet will find.
     ///
     pub fn new(
         msg: [u8; 64],
@@ -100,9 +249,13 @@ impl MasterExtendedKeys {
 
         let key = key.with_context(|| Bip32Error::EmptyKey)?;
 
-        let k = hmac::Key::new(HMAC_SHA512, key.as_bytes());
-        let tag = hmac::sign(&k, &msg[..]);
-        let inner_t = tag.as_ref();
+        let mut tag = crypto::hmac_sha512(key.as_bytes(), &msg[..]);
+        // IL must be a valid secret key. In the rare case it is 0 or >= n,
+        // hash the output again, as SLIP-0010 specifies.
+        while key::SecretKey::from_slice(&tag[..32]).is_err() {
+            tag = crypto::hmac_sha512(key.as_bytes(), &tag);
+        }
+        let inner_t = &tag[..];
 
         let private_key: [u8; 32] = inner_t[..inner_t.len() / 2]
             .try_into()
@@ -112,65 +265,1059 @@ impl MasterExtendedKeys {
             .try_into()
             .with_context(|| Bip32Error::TryFromSliceError)?;
 
+        Self::from_parts(private_key, chain_code, network, compressed)
+    }
+
+    fn from_parts(
+        secret: Secret,
+        chain_code: ChainCode,
+        network: Network,
+        compressed: bool,
+    ) -> Result<Self> {
         let private = PrivateKey {
             network,
-            secret: private_key,
-            compressed: false,
+            secret,
+            compressed,
         };
+        let public =
+            key::PublicKey::from_secret_key(&SECP256K1, &key::SecretKey::from_slice(&secret)?);
 
-        let secret_key: key::SecretKey = key::SecretKey::from_slice(&private_key[..])?;
+        Ok(ExtendedPrivKey {
+            public,
+            private,
+            chain_code,
+            depth: 0,
+            parent_fingerprint: Fingerprint::default(),
+            child_number: ChildNumber::Normal(0),
+            cache: None,
+        })
+    }
+
+    /// Encode the master private key and chain code as a 51-word backup phrase.
+    ///
+    /// This is a keymaker-specific scheme for master keys that were not created
+    /// from a BIP39 mnemonic. The first 24 words are the BIP39 encoding of the
+    /// 32-byte private key and the next 24 words are the BIP39 encoding of the
+    /// 32-byte chain code, each half carrying its own 8-bit checksum. The last
+    /// 3 words are a 33-bit checksum over the key and chain code together, so
+    /// halves that are swapped or come from different backups are rejected.
+    /// Unlike a BIP39 mnemonic, the phrase is not run through PBKDF2: it
+    /// restores the exact same master key.
+    ///
+    /// Fails with `Bip32Error::NotMasterKey` for a derived key, whose phrase
+    /// would restore it as a master key without its place in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{MasterExtendedKeys, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let keys = MasterExtendedKeys::new(seed.entropy, None, Network::Testnet, false).unwrap();
+    ///
+    /// let phrase = keys.to_backup_phrase().unwrap();
+    /// assert_eq!(phrase.len(), 51);
+    ///
+    /// let restored = MasterExtendedKeys::from_backup_phrase(&phrase, Network::Testnet, false).unwrap();
+    /// assert_eq!(restored.chain_code(), keys.chain_code());
+    /// ```
+    pub fn to_backup_phrase(&self) -> Result<Vec<String>> {
+        if self.depth != 0
+            || self.parent_fingerprint != Fingerprint::default()
+            || self.child_number != ChildNumber::Normal(0)
+        {
+            return Err(Bip32Error::NotMasterKey(self.depth).into());
+        }
+
+        let secret = Mnemonic::from_entropy(&self.private.secret)?;
+        let chain_code = Mnemonic::from_entropy(&self.chain_code)?;
+        let checksum = backup_checksum(&self.private.secret, &self.chain_code)?;
+        Ok(secret
+            .iter()
+            .chain(&chain_code)
+            .map(str::to_string)
+            .chain(checksum)
+            .collect())
+    }
 
-        let public: PublicKey;
-        if compressed {
-            let public_key = key::PublicKey::from_secret_key(&SECP256K1, &secret_key).serialize();
-            public = PublicKey::Compressed(public_key);
+    /// Restore master keys from a phrase created by
+    /// [to_backup_phrase](MasterExtendedKeys::to_backup_phrase).
+    pub fn from_backup_phrase(
+        phrase: &[String],
+        network: Network,
+        compressed: bool,
+    ) -> Result<Self> {
+        if phrase.len() != BACKUP_PHRASE_LEN {
+            return Err(Bip39Error::InvalidWordCount(phrase.len()).into());
+        }
+
+        let (key_words, rest) = phrase.split_at(BACKUP_HALF_LEN);
+        let (chain_code_words, checksum) = rest.split_at(BACKUP_HALF_LEN);
+        let secret: Secret = Mnemonic::from_phrase_in(Language::English, &key_words.join(" "))?
+            .entropy()
+            .try_into()
+            .with_context(|| Bip32Error::TryFromSliceError)?;
+        let chain_code: ChainCode =
+            Mnemonic::from_phrase_in(Language::English, &chain_code_words.join(" "))?
+                .entropy()
+                .try_into()
+                .with_context(|| Bip32Error::TryFromSliceError)?;
+        if checksum != &backup_checksum(&secret, &chain_code)?[..] {
+            return Err(Bip39Error::ChecksumMismatch.into());
+        }
+
+        Self::from_parts(secret, chain_code, network, compressed)
+    }
+
+    pub fn pubkey(&self) -> PublicKey {
+        serialize_public(&self.public, self.private.compressed)
+    }
+
+    pub fn privkey(&self) -> PrivateKey {
+        self.private.clone()
+    }
+
+    pub fn chain_code(&self) -> ChainCode {
+        self.chain_code
+    }
+
+    /// Number of derivation steps from the master key, which is at depth 0.
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Fingerprint of the parent key, all zeros for the master key.
+    pub fn parent_fingerprint(&self) -> Fingerprint {
+        self.parent_fingerprint
+    }
+
+    /// The step this key was derived with, `Normal(0)` for the master key.
+    pub fn child_number(&self) -> ChildNumber {
+        self.child_number
+    }
+
+    /// The [identifier](PublicKey::identifier) of the public key.
+    pub fn identifier(&self) -> Result<AddressHash, Error> {
+        self.pubkey().identifier()
+    }
+
+    /// The [fingerprint](PublicKey::fingerprint) of the public key, which
+    /// children record as their parent fingerprint.
+    pub fn fingerprint(&self) -> Result<Fingerprint, Error> {
+        self.pubkey().fingerprint()
+    }
+
+    /// Derive the child keys at `child` (CKDpriv).
+    ///
+    /// The derived keys are the root of their own subtree and can be derived
+    /// from further.
+    pub fn derive_child(&self, child: ChildNumber) -> Result<Self> {
+        let index = child.validate()?.to_index();
+        let compressed = self.private.compressed;
+
+        let mut data = Vec::with_capacity(37);
+        if child.is_hardened() {
+            data.push(0);
+            data.extend_from_slice(&self.private.secret);
         } else {
-            let uncompressed_public_key =
-                key::PublicKey::from_secret_key(&SECP256K1, &secret_key).serialize_uncompressed();
-            public = PublicKey::Standard(uncompressed_public_key);
+            data.extend_from_slice(&self.public.serialize());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let tag = crypto::hmac_sha512(&self.chain_code, &data);
+        let (tweak, chain_code) = tag.split_at(32);
+        let tweak: [u8; 32] = tweak
+            .try_into()
+            .with_context(|| Bip32Error::TryFromSliceError)?;
+
+        let secret = self
+            .private
+            .add_tweak(&tweak)
+            .with_context(|| Bip32Error::InvalidChild(index))?
+            .secret;
+        let chain_code = chain_code
+            .try_into()
+            .with_context(|| Bip32Error::TryFromSliceError)?;
+
+        let depth = self
+            .depth
+            .checked_add(1)
+            .with_context(|| Bip32Error::InvalidChild(index))?;
+
+        Ok(Self {
+            depth,
+            parent_fingerprint: self.fingerprint()?,
+            child_number: child,
+            ..Self::from_parts(secret, chain_code, self.private.network, compressed)?
+        })
+    }
+
+    /// Derive the keys at `path`, relative to these keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{DerivationPath, MasterExtendedKeys, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let master = MasterExtendedKeys::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let path: DerivationPath = "m/44'/0'/0'/0/1".parse().unwrap();
+    /// let keys = master.derive_path(&path).unwrap();
+    /// ```
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self> {
+        derive_cached(self.cache.as_ref(), self, path, |keys, child| {
+            keys.derive_child(*child)
+        })
+    }
+
+    /// [derive_path](ExtendedPrivKey::derive_path) from the master key,
+    /// requiring `ack` to acknowledge
+    /// [Confirmation::non_standard_path](Confirmation::non_standard_path) if
+    /// `path` is not a standard path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::confirmation::Confirmation;
+    /// use keymaker::{DerivationPath, MasterExtendedKeys, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let master = MasterExtendedKeys::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let standard: DerivationPath = "m/84'/0'/0'/0/1".parse().unwrap();
+    /// assert!(master.derive_path_confirmed(&standard, None).is_ok());
+    ///
+    /// let custom: DerivationPath = "m/7'/1".parse().unwrap();
+    /// assert!(master.derive_path_confirmed(&custom, None).is_err());
+    /// let ack = Confirmation::non_standard_path(&custom).unwrap().acknowledge();
+    /// assert!(master.derive_path_confirmed(&custom, Some(ack)).is_ok());
+    /// ```
+    pub fn derive_path_confirmed(
+        &self,
+        path: &DerivationPath,
+        ack: Option<Acknowledgement>,
+    ) -> Result<Self> {
+        if let Some(confirmation) = Confirmation::non_standard_path(path) {
+            confirmation.require(ack)?;
+        }
+        self.derive_path(path)
+    }
+
+    /// Serialize as an `xprv`, as `to_string` does, once `ack` acknowledges
+    /// [Confirmation::export_xprv](Confirmation::export_xprv) for these keys.
+    pub fn export(&self, ack: Acknowledgement) -> Result<String> {
+        Confirmation::export_xprv(self).require(Some(ack))?;
+        Ok(self.to_string())
+    }
+
+    /// Derive the key at `path`, relative to these keys, bundled with the
+    /// public key, fingerprints and path a wallet needs alongside it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{AddressType, ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let master = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let key = master.derive_key(&"m/84'/0'/0'/0/0".parse().unwrap()).unwrap();
+    /// assert!(key.private().is_some());
+    /// assert!(key.address(AddressType::P2wpkh).unwrap().to_string().starts_with("bc1q"));
+    /// assert!(key.key_origin().ends_with("/84'/0'/0'/0/0]"));
+    /// ```
+    pub fn derive_key(&self, path: &DerivationPath) -> Result<DerivedKey> {
+        let keys = self.derive_path(path)?;
+        Ok(DerivedKey {
+            path: path.clone(),
+            origin_fingerprint: self.fingerprint()?,
+            private: Some(keys.privkey()),
+            public: keys.pubkey(),
+            fingerprint: keys.fingerprint()?,
+            network: keys.private.network,
+        })
+    }
+
+    /// Remember up to `capacity` keys derived by
+    /// [derive_path](ExtendedPrivKey::derive_path), so repeated derivations
+    /// under the same account skip the HMAC work. Clones share the cache; the
+    /// derived keys do not inherit it. A capacity of 0 disables caching.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let master = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+    ///     .unwrap()
+    ///     .with_cache(64);
+    ///
+    /// for i in 0..10 {
+    ///     // Only the last step is derived after the first iteration.
+    ///     let path = format!("m/84'/0'/0'/0/{}", i).parse().unwrap();
+    ///     master.derive_path(&path).unwrap();
+    /// }
+    /// ```
+    pub fn with_cache(self, capacity: usize) -> Self {
+        Self {
+            cache: new_cache(capacity),
+            ..self
         }
+    }
+
+    /// Lazily derive the key pairs at `prefix/i` for every `i` in `range`.
+    ///
+    /// Keys are derived one at a time as the iterator advances, so a large
+    /// range of receive addresses can be streamed. An index whose key is
+    /// invalid is skipped, as BIP32 prescribes. The range stops short of the
+    /// hardened indices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{DerivationPath, ExtendedPrivKey, Network, Purpose, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let master = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let receive = DerivationPath::account(Purpose::Bip84, Network::Mainnet, 0)
+    ///     .unwrap()
+    ///     .child(0.into());
+    /// for (index, pair) in master.derive_range(&receive, 0..1000).unwrap().take(3) {
+    ///     println!("{}: {}", index, pair.public());
+    /// }
+    /// ```
+    pub fn derive_range(
+        &self,
+        prefix: &DerivationPath,
+        range: Range<u32>,
+    ) -> Result<impl Iterator<Item = (u32, KeyPair)>> {
+        let parent = self.derive_path(prefix)?;
+        let range = range.start..range.end.min(HARDENED_OFFSET);
+        Ok(range.filter_map(move |index| {
+            let keys = parent.derive_child(ChildNumber::Normal(index)).ok()?;
+            Some((index, KeyPair::from_extended(&keys)))
+        }))
+    }
 
-        Ok(MasterExtendedKeys {
+    /// Drop the private key, keeping what is needed for public derivation.
+    ///
+    /// The resulting [ExtendedPubKey](ExtendedPubKey) has no signing
+    /// capability, so watch-only code can be handed one safely.
+    pub fn neuter(&self) -> ExtendedPubKey {
+        ExtendedPubKey {
+            public: self.public,
+            compressed: self.private.compressed,
+            chain_code: self.chain_code,
+            network: self.private.network,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            cache: None,
+        }
+    }
+}
+
+impl DisplayLayout for ExtendedPrivKey {
+    type Target = Vec<u8>;
+
+    fn layout(&self) -> Self::Target {
+        let version = self.private.network.params().xprv;
+        let mut key = vec![0];
+        key.extend_from_slice(&self.private.secret);
+        extended_key_layout(
+            version,
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &key,
+        )
+    }
+
+    fn from_layout(data: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let parts = ExtendedKeyParts::parse(data)?;
+        let network =
+            Network::find(|params| params.xprv == parts.version).ok_or(Error::InvalidNetwork)?;
+
+        // Private keys are padded with a zero byte to the 33-byte key field.
+        if parts.key[0] != 0 {
+            return Err(Error::InvalidPrivate);
+        }
+
+        let mut secret = Secret::default();
+        secret.copy_from_slice(&parts.key[1..]);
+        let keys = Self::from_parts(secret, parts.chain_code, network, true)
+            .map_err(|_| Error::InvalidSecret)?;
+
+        Ok(Self {
+            depth: parts.depth,
+            parent_fingerprint: parts.parent_fingerprint,
+            child_number: parts.child_number,
+            ..keys
+        })
+    }
+}
+
+impl fmt::Display for ExtendedPrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.layout().to_base58().fmt(f)
+    }
+}
+
+impl str::FromStr for ExtendedPrivKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let data = s.from_base58().map_err(|_| Error::InvalidPrivate)?;
+        Self::from_layout(&data)
+    }
+}
+
+/// Represents an extended public key, which derives non-hardened child
+/// public keys without any private key material (CKDpub).
+///
+/// Obtained with [ExtendedPrivKey::neuter](ExtendedPrivKey::neuter) or by
+/// parsing an `xpub`. It holds no private key, so it cannot sign and cannot
+/// be turned back into an [ExtendedPrivKey](ExtendedPrivKey).
+///
+/// Serializes to and from the standard Base58Check `xpub`/`tpub` format.
+#[derive(Clone, Debug)]
+pub struct ExtendedPubKey {
+    public: key::PublicKey,
+    compressed: bool,
+    chain_code: ChainCode,
+    network: Network,
+    depth: u8,
+    parent_fingerprint: Fingerprint,
+    child_number: ChildNumber,
+    cache: Option<DerivationCache<ExtendedPubKey>>,
+}
+
+impl ExtendedPubKey {
+    /// Derive the non-hardened child public key at `index`.
+    ///
+    /// The child keeps the parent's compressed or uncompressed form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{MasterExtendedKeys, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let keys = MasterExtendedKeys::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let xpub = keys.neuter();
+    /// let receive = xpub.derive_child(0).unwrap().derive_child(7).unwrap();
+    /// assert!(xpub.derive_child(1 << 31).is_err());
+    /// ```
+    pub fn derive_child(&self, index: u32) -> Result<Self> {
+        if index >= HARDENED_OFFSET {
+            return Err(Bip32Error::HardenedPublicDerivation(index).into());
+        }
+
+        let mut data = self.public.serialize().to_vec();
+        data.extend_from_slice(&index.to_be_bytes());
+        let tag = crypto::hmac_sha512(&self.chain_code, &data);
+        let (tweak, chain_code) = tag.split_at(32);
+        let tweak: [u8; 32] = tweak
+            .try_into()
+            .with_context(|| Bip32Error::TryFromSliceError)?;
+
+        let mut public = self.public;
+        public
+            .add_exp_assign(&SECP256K1, &tweak)
+            .with_context(|| Bip32Error::InvalidChild(index))?;
+        let chain_code = chain_code
+            .try_into()
+            .with_context(|| Bip32Error::TryFromSliceError)?;
+        let depth = self
+            .depth
+            .checked_add(1)
+            .with_context(|| Bip32Error::InvalidChild(index))?;
+
+        Ok(Self {
             public,
-            private,
+            compressed: self.compressed,
             chain_code,
+            network: self.network,
+            depth,
+            parent_fingerprint: self.fingerprint()?,
+            child_number: ChildNumber::Normal(index),
+            cache: None,
         })
     }
 
+    /// Derive the public key at the non-hardened `path`, relative to this key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let account = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+    ///     .unwrap()
+    ///     .derive_path(&"m/84'/0'/0'".parse().unwrap())
+    ///     .unwrap();
+    ///
+    /// let watch_only = account.neuter();
+    /// let receive = watch_only.derive_path(&"0/3".parse().unwrap()).unwrap();
+    /// assert!(watch_only.derive_path(&"0'/3".parse().unwrap()).is_err());
+    /// ```
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self> {
+        derive_cached(self.cache.as_ref(), self, path, |key, child| match child {
+            ChildNumber::Normal(_) => key.derive_child(child.validate()?.to_index()),
+            ChildNumber::Hardened(_) => {
+                Err(Bip32Error::HardenedPublicDerivation(child.to_index()).into())
+            }
+        })
+    }
+
+    /// Derive the public key at the non-hardened `path`, relative to this
+    /// key, as a [DerivedKey] without a private key.
+    pub fn derive_key(&self, path: &DerivationPath) -> Result<DerivedKey> {
+        let keys = self.derive_path(path)?;
+        Ok(DerivedKey {
+            path: path.clone(),
+            origin_fingerprint: self.fingerprint()?,
+            private: None,
+            public: keys.pubkey(),
+            fingerprint: keys.fingerprint()?,
+            network: keys.network,
+        })
+    }
+
+    /// Remember up to `capacity` keys derived by
+    /// [derive_path](ExtendedPubKey::derive_path), as
+    /// [ExtendedPrivKey::with_cache](ExtendedPrivKey::with_cache) does.
+    pub fn with_cache(self, capacity: usize) -> Self {
+        Self {
+            cache: new_cache(capacity),
+            ..self
+        }
+    }
+
+    /// Lazily derive the `script_type` addresses at `change/i` for every
+    /// `i` in `range`, as a watch-only wallet hands them out. `self` is
+    /// expected to be an account key such as `m/84'/0'/0'`.
+    ///
+    /// P2SH addresses are P2SH-P2WPKH, as used by BIP49 accounts. An index
+    /// whose key is invalid is skipped, and the range stops short of the
+    /// hardened indices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{AddressType, ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let xpub = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+    ///     .unwrap()
+    ///     .derive_path(&"m/84'/0'/0'".parse().unwrap())
+    ///     .unwrap()
+    ///     .neuter();
+    ///
+    /// for (index, address) in xpub.addresses(AddressType::P2wpkh, false, 0..5).unwrap() {
+    ///     assert!(address.to_string().starts_with("bc1q"));
+    ///     println!("{}: {}", index, address);
+    /// }
+    /// ```
+    pub fn addresses(
+        &self,
+        script_type: AddressType,
+        change: bool,
+        range: Range<u32>,
+    ) -> Result<impl Iterator<Item = (u32, Address)>> {
+        let branch = self.derive_child(change as u32)?;
+        let network = self.network;
+        // Fail now rather than on every index if the script type cannot be
+        // used on this network.
+        Address::from_public(&branch.pubkey(), script_type, network)?;
+
+        let range = range.start..range.end.min(HARDENED_OFFSET);
+        Ok(range.filter_map(move |index| {
+            let child = branch.derive_child(index).ok()?;
+            let address = Address::from_public(&child.pubkey(), script_type, network).ok()?;
+            Some((index, address))
+        }))
+    }
+
     pub fn pubkey(&self) -> PublicKey {
-        self.public.clone()
+        serialize_public(&self.public, self.compressed)
     }
 
-    pub fn privkey(&self) -> PrivateKey {
-        self.private.clone()
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn chain_code(&self) -> ChainCode {
+        self.chain_code
+    }
+
+    /// Number of derivation steps from the master key, which is at depth 0.
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Fingerprint of the parent key, all zeros for the master key.
+    pub fn parent_fingerprint(&self) -> Fingerprint {
+        self.parent_fingerprint
+    }
+
+    /// The step this key was derived with, `Normal(0)` for the master key.
+    pub fn child_number(&self) -> ChildNumber {
+        self.child_number
+    }
+
+    /// The [identifier](PublicKey::identifier) of the public key.
+    pub fn identifier(&self) -> Result<AddressHash, Error> {
+        self.pubkey().identifier()
+    }
+
+    /// The [fingerprint](PublicKey::fingerprint) of the public key, which
+    /// children record as their parent fingerprint.
+    pub fn fingerprint(&self) -> Result<Fingerprint, Error> {
+        self.pubkey().fingerprint()
+    }
+}
+
+/// The last words of a backup phrase: the first 33 bits of a tagged hash of
+/// the key and chain code, as three wordlist indices.
+fn backup_checksum(secret: &Secret, chain_code: &ChainCode) -> Result<Vec<String>, Bip39Error> {
+    let hash = crypto::tagged_hash(
+        BACKUP_CHECKSUM_TAG,
+        &[&secret[..], &chain_code[..]].concat(),
+    );
+    let indices: Vec<usize> = hash
+        .chunks(2)
+        .take(BACKUP_CHECKSUM_LEN)
+        .map(|pair| usize::from(u16::from_be_bytes([pair[0], pair[1]]) >> 5))
+        .collect();
+    bip39::indices_to_mnemonic(&indices, Language::English)
+}
+
+/// A key derived by [ExtendedPrivKey::derive_key] or
+/// [ExtendedPubKey::derive_key], with what call sites would otherwise stitch
+/// together from the parent key and the path.
+#[derive(Debug, Clone)]
+pub struct DerivedKey {
+    path: DerivationPath,
+    origin_fingerprint: Fingerprint,
+    private: Option<PrivateKey>,
+    public: PublicKey,
+    fingerprint: Fingerprint,
+    network: Network,
+}
+
+impl DerivedKey {
+    /// The path the key was derived at, relative to the key it was derived
+    /// from.
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+
+    /// The [fingerprint](PublicKey::fingerprint) of the key the path starts
+    /// from, which is the master fingerprint when derived from the master
+    /// key.
+    pub fn origin_fingerprint(&self) -> Fingerprint {
+        self.origin_fingerprint
+    }
+
+    /// The private key, or `None` if derived from an extended public key.
+    pub fn private(&self) -> Option<&PrivateKey> {
+        self.private.as_ref()
+    }
+
+    pub fn public(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// The [fingerprint](PublicKey::fingerprint) of the derived key itself.
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// The `address_type` address of the key, with P2SH standing for
+    /// P2SH-P2WPKH as in [ExtendedPubKey::addresses].
+    pub fn address(&self, address_type: AddressType) -> Result<Address, Error> {
+        Address::from_public(&self.public, address_type, self.network)
+    }
+
+    /// The key origin of output descriptors and PSBTs,
+    /// `[origin_fingerprint/path]` with the path written without its `m`.
+    pub fn key_origin(&self) -> String {
+        let path = self.path.to_string();
+        format!(
+            "[{}{}]",
+            hex::encode(self.origin_fingerprint),
+            path.trim_start_matches('m')
+        )
+    }
+}
+
+/// The key in the compressed or uncompressed form, which extended keys
+/// remember apart from the point so that serializing them cannot fail.
+fn serialize_public(public: &key::PublicKey, compressed: bool) -> PublicKey {
+    if compressed {
+        PublicKey::Compressed(public.serialize())
+    } else {
+        PublicKey::Standard(public.serialize_uncompressed())
+    }
+}
+
+impl From<&ExtendedPrivKey> for ExtendedPubKey {
+    fn from(keys: &ExtendedPrivKey) -> Self {
+        keys.neuter()
+    }
+}
+
+impl DisplayLayout for ExtendedPubKey {
+    type Target = Vec<u8>;
+
+    fn layout(&self) -> Self::Target {
+        let version = self.network.params().xpub;
+        // The 33-byte compressed form is serialized even for uncompressed keys.
+        extended_key_layout(
+            version,
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &self.public.serialize(),
+        )
+    }
+
+    fn from_layout(data: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let parts = ExtendedKeyParts::parse(data)?;
+        let network =
+            Network::find(|params| params.xpub == parts.version).ok_or(Error::InvalidNetwork)?;
+
+        let public = key::PublicKey::from_slice(&parts.key)?;
+
+        Ok(Self {
+            public,
+            compressed: true,
+            chain_code: parts.chain_code,
+            network,
+            depth: parts.depth,
+            parent_fingerprint: parts.parent_fingerprint,
+            child_number: parts.child_number,
+            cache: None,
+        })
+    }
+}
+
+impl fmt::Display for ExtendedPubKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.layout().to_base58().fmt(f)
+    }
+}
+
+impl str::FromStr for ExtendedPubKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let data = s.from_base58().map_err(|_| Error::InvalidPublic)?;
+        Self::from_layout(&data)
+    }
+}
+
+/// A BIP44 account `m/44'/coin_type'/account'` and its extended keys.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::bip32::Account;
+/// use keymaker::{CoinType, ExtendedPrivKey, Network, SeedBuilder};
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let master = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+///
+/// let account = Account::new(&master, CoinType::Ethereum, 0).unwrap();
+/// assert_eq!(account.path().to_string(), "m/44'/60'/0'");
+/// let receive = account.address(false, 0).unwrap();
+/// ```
+pub struct Account {
+    keys: ExtendedPrivKey,
+    path: DerivationPath,
+}
+
+impl Account {
+    /// Derive account `index` of `coin` from the master keys.
+    pub fn new<C: Into<CoinType>>(master: &ExtendedPrivKey, coin: C, index: u32) -> Result<Self> {
+        let path = DerivationPath::account(Purpose::Bip44, coin, index)?;
+        Ok(Account {
+            keys: master.derive_path(&path)?,
+            path,
+        })
+    }
+
+    pub fn keys(&self) -> &ExtendedPrivKey {
+        &self.keys
+    }
+
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+
+    /// Derive the keys of the receive or `change` address at `index`.
+    pub fn address(&self, change: bool, index: u32) -> Result<ExtendedPrivKey> {
+        self.keys
+            .derive_child(ChildNumber::Normal(change as u32))?
+            .derive_child(ChildNumber::Normal(index))
     }
 }
 
+fn new_cache<K>(capacity: usize) -> Option<DerivationCache<K>> {
+    NonZeroUsize::new(capacity).map(|capacity| Arc::new(Mutex::new(LruCache::new(capacity))))
+}
+
+/// Derive `path` from `root` one `step` at a time, starting from the longest
+/// prefix of the path found in `cache` and caching every key derived on the way.
+fn derive_cached<K, F>(
+    cache: Option<&DerivationCache<K>>,
+    root: &K,
+    path: &DerivationPath,
+    step: F,
+) -> Result<K>
+where
+    K: Clone,
+    F: Fn(K, &ChildNumber) -> Result<K>,
+{
+    let cache = match cache {
+        Some(cache) => cache,
+        None => return path.iter().try_fold(root.clone(), step),
+    };
+    let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let steps = path.as_ref();
+    let (start, mut key) = (1..=steps.len())
+        .rev()
+        .find_map(|n| {
+            let prefix = DerivationPath::from(steps[..n].to_vec());
+            cache.get(&prefix).map(|key| (n, key.clone()))
+        })
+        .unwrap_or_else(|| (0, root.clone()));
+
+    for n in start..steps.len() {
+        key = step(key, &steps[n])?;
+        cache.put(DerivationPath::from(steps[..=n].to_vec()), key.clone());
+    }
+    Ok(key)
+}
+
+/// Represents an ed25519 extended private key derived per [SLIP-0010], for
+/// chains such as Solana and Stellar.
+///
+/// ed25519 has no public derivation, so every step must be hardened.
+///
+/// [SLIP-0010]: https://github.com/satoshilabs/slips/blob/master/slip-0010.md
+#[derive(Clone)]
+pub struct Ed25519ExtendedKey {
+    secret: Secret,
+    chain_code: ChainCode,
+    depth: u8,
+    child_number: ChildNumber,
+}
+
+impl Ed25519ExtendedKey {
+    /// Create the master key from a seed, such as a BIP39 [Seed](crate::Seed).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::bip32::Ed25519ExtendedKey;
+    /// use keymaker::SeedBuilder;
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let master = Ed25519ExtendedKey::new(&seed.entropy).unwrap();
+    ///
+    /// // Solana's default account.
+    /// let account = master.derive_path(&"m/44'/501'/0'/0'".parse().unwrap()).unwrap();
+    /// assert_eq!(account.public_key().len(), 32);
+    /// ```
+    pub fn new(seed: &[u8]) -> Result<Self> {
+        let tag = crypto::hmac_sha512(ED25519_KEY.as_bytes(), seed);
+        Self::from_tag(&tag, 0, ChildNumber::Normal(0))
+    }
+
+    fn from_tag(tag: &[u8], depth: u8, child_number: ChildNumber) -> Result<Self> {
+        let (secret, chain_code) = tag.split_at(32);
+        Ok(Self {
+            secret: secret
+                .try_into()
+                .with_context(|| Bip32Error::TryFromSliceError)?,
+            chain_code: chain_code
+                .try_into()
+                .with_context(|| Bip32Error::TryFromSliceError)?,
+            depth,
+            child_number,
+        })
+    }
+
+    /// Derive the hardened child key at `child`.
+    pub fn derive_child(&self, child: ChildNumber) -> Result<Self> {
+        let index = child.validate()?.to_index();
+        if !child.is_hardened() {
+            return Err(Bip32Error::NormalEd25519Derivation(index).into());
+        }
+
+        let mut data = Vec::with_capacity(37);
+        data.push(0);
+        data.extend_from_slice(&self.secret);
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let depth = self
+            .depth
+            .checked_add(1)
+            .with_context(|| Bip32Error::InvalidChild(index))?;
+        Self::from_tag(&crypto::hmac_sha512(&self.chain_code, &data), depth, child)
+    }
+
+    /// Derive the key at `path`, relative to this key.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self> {
+        path.iter()
+            .try_fold(self.clone(), |key, child| key.derive_child(*child))
+    }
+
+    /// The 32-byte ed25519 private key seed.
+    pub fn secret(&self) -> Secret {
+        self.secret
+    }
+
+    /// The 32-byte ed25519 public key.
+    pub fn public_key(&self) -> [u8; 32] {
+        let mut public = [0u8; 32];
+        // Any 32 bytes are a valid ed25519 seed.
+        if let Ok(pair) = Ed25519KeyPair::from_seed_unchecked(&self.secret) {
+            public.copy_from_slice(pair.public_key().as_ref());
+        }
+        public
+    }
+
+    pub fn chain_code(&self) -> ChainCode {
+        self.chain_code
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn child_number(&self) -> ChildNumber {
+        self.child_number
+    }
+}
+
+/// The fields of a 78-byte serialized extended key, after the checksum.
+struct ExtendedKeyParts {
+    version: [u8; 4],
+    depth: u8,
+    parent_fingerprint: Fingerprint,
+    child_number: ChildNumber,
+    chain_code: ChainCode,
+    key: [u8; 33],
+}
+
+impl ExtendedKeyParts {
+    /// Split a serialized key into its fields, rejecting a bad checksum and
+    /// master keys that claim a parent.
+    fn parse(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != EXTENDED_KEY_LEN {
+            return Err(Error::InvalidExtendedKey);
+        }
+
+        let (payload, cs) = data.split_at(EXTENDED_KEY_LEN - 4);
+        if !crypto::verify_checksum(payload, cs) {
+            return Err(Error::InvalidChecksum);
+        }
+
+        let mut parts = ExtendedKeyParts {
+            version: [0; 4],
+            depth: payload[4],
+            parent_fingerprint: Fingerprint::default(),
+            child_number: ChildNumber::Normal(0),
+            chain_code: ChainCode::default(),
+            key: [0; 33],
+        };
+        parts.version.copy_from_slice(&payload[..4]);
+        parts.parent_fingerprint.copy_from_slice(&payload[5..9]);
+        let mut index = [0u8; 4];
+        index.copy_from_slice(&payload[9..13]);
+        parts.child_number = ChildNumber::from(u32::from_be_bytes(index));
+        parts.chain_code.copy_from_slice(&payload[13..45]);
+        parts.key.copy_from_slice(&payload[45..]);
+
+        let is_master = parts.depth == 0;
+        let has_parent = parts.parent_fingerprint != Fingerprint::default()
+            || parts.child_number != ChildNumber::Normal(0);
+        if is_master && has_parent {
+            return Err(Error::InvalidExtendedKey);
+        }
+
+        Ok(parts)
+    }
+}
+
+fn extended_key_layout(
+    version: [u8; 4],
+    depth: u8,
+    parent_fingerprint: Fingerprint,
+    child_number: ChildNumber,
+    chain_code: &ChainCode,
+    key: &[u8],
+) -> Vec<u8> {
+    let mut result = Vec::with_capacity(EXTENDED_KEY_LEN);
+    result.extend_from_slice(&version);
+    result.push(depth);
+    result.extend_from_slice(&parent_fingerprint);
+    result.extend_from_slice(&child_number.to_index().to_be_bytes());
+    result.extend_from_slice(chain_code);
+    result.extend_from_slice(key);
+    let cs = crypto::checksum(&result);
+    result.extend_from_slice(&cs);
+    result
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use crate::bip39::{Seed, SeedBuilder};
     use anyhow::Result;
+    use base58::FromBase58;
 
     #[test]
     fn key_gen_test() {
         let Seed { entropy, .. } = SeedBuilder::new().build().unwrap();
         let keys = MasterExtendedKeys::new(entropy, None, Network::Testnet, false);
 
+        let keys = keys.unwrap();
         let MasterExtendedKeys {
-            public,
             private,
             chain_code,
-        } = keys.unwrap();
+            ..
+        } = keys.clone();
 
-        if let PublicKey::Standard(pub_key) = public {
+        if let PublicKey::Standard(pub_key) = keys.pubkey() {
             // Pointless assertions for now.
             assert_eq!(pub_key.len(), 65);
             assert_eq!(private.secret.len(), 32);
             assert_eq!(chain_code.len(), 32);
         } else {
-            assert!(false);
+            panic!("expected an uncompressed public key");
         }
     }
 
@@ -182,6 +1329,124 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn keypair_rejects_invalid_secret() -> Result<()> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        for secret in &[[0u8; 32], [0xffu8; 32]] {
+            let invalid = PrivateKey {
+                secret: *secret,
+                ..private.clone()
+            };
+            let err = KeyPair::from_private(invalid, true).unwrap_err();
+            assert_eq!(err.downcast_ref::<Error>(), Some(&Error::InvalidPrivate));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn compression_flag_is_consistent() -> Result<()> {
+        let keys = MasterExtendedKeys::new([7u8; 64], None, Network::Mainnet, true)?;
+        assert!(keys.privkey().compressed);
+        assert!(matches!(keys.pubkey(), PublicKey::Compressed(_)));
+        assert!(
+            keys.derive_child(ChildNumber::Normal(0))?
+                .privkey()
+                .compressed
+        );
+
+        let compressed = KeyPair::from_private(keys.privkey(), true)?;
+        assert!(compressed.private().compressed);
+        assert!(matches!(compressed.public(), PublicKey::Compressed(_)));
+        // A compressed WIF starts with K or L on mainnet, an uncompressed one with 5.
+        assert!(compressed.private().to_string().starts_with(['K', 'L']));
+
+        let standard = KeyPair::from_private(keys.privkey(), false)?;
+        assert!(!standard.private().compressed);
+        assert!(matches!(standard.public(), PublicKey::Standard(_)));
+        assert!(standard.private().to_string().starts_with('5'));
+        Ok(())
+    }
+
+    #[test]
+    fn derive_key_bundle() -> Result<()> {
+        let root: ExtendedPrivKey = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu"
+            .parse()?;
+        let path: DerivationPath = "m/84'/0'/0'/0/0".parse()?;
+
+        let key = root.derive_key(&path)?;
+        let keys = root.derive_path(&path)?;
+        assert_eq!(key.path(), &path);
+        assert_eq!(hex::encode(key.origin_fingerprint()), "73c5da0a");
+        assert_eq!(key.fingerprint(), keys.fingerprint()?);
+        assert_eq!(key.public().to_string(), keys.pubkey().to_string());
+        assert_eq!(key.private().unwrap().secret, keys.privkey().secret);
+        assert_eq!(
+            key.address(AddressType::P2wpkh)?.to_string(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(key.key_origin(), "[73c5da0a/84'/0'/0'/0/0]");
+
+        let account = root.derive_path(&"m/84'/0'/0'".parse()?)?.neuter();
+        let watch_only = account.derive_key(&"0/0".parse()?)?;
+        assert!(watch_only.private().is_none());
+        assert_eq!(watch_only.public().to_string(), key.public().to_string());
+        assert_eq!(watch_only.origin_fingerprint(), account.fingerprint()?);
+        Ok(())
+    }
+
+    #[test]
+    fn xpub_addresses() -> Result<()> {
+        // BIP84 test vectors, from the all-abandon mnemonic.
+        let root: ExtendedPrivKey = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu"
+            .parse()?;
+        let xpub = root.derive_path(&"m/84'/0'/0'".parse()?)?.neuter();
+
+        let receive: Vec<_> = xpub
+            .addresses(AddressType::P2wpkh, false, 0..2)?
+            .map(|(index, address)| (index, address.to_string()))
+            .collect();
+        assert_eq!(
+            receive,
+            vec![
+                (0, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu".to_string()),
+                (1, "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g".to_string()),
+            ]
+        );
+
+        let (index, change) = xpub
+            .addresses(AddressType::P2wpkh, true, 0..1)?
+            .next()
+            .unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(
+            change.to_string(),
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
+
+        assert!(xpub.addresses(AddressType::P2wsh, false, 0..1).is_err());
+        assert_eq!(
+            xpub.addresses(AddressType::P2pkh, false, HARDENED_OFFSET - 1..u32::MAX)?
+                .count(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn keypair_builder() -> Result<()> {
+        let keys = MasterExtendedKeys::new([2u8; 64], None, Network::Testnet, false)?;
+        let from_keys = KeyPair::from_extended(&keys);
+        let built = KeyPairBuilder::new().extended(&keys).build()?;
+        assert_eq!(built.private(), from_keys.private());
+        assert_eq!(built.public().to_string(), from_keys.public().to_string());
+
+        let random = KeyPairBuilder::new().build()?;
+        assert_eq!(random.private().network, Network::Mainnet);
+        assert!(matches!(random.public(), PublicKey::Compressed(_)));
+        assert!(KeyPairBuilder::new().secret([0u8; 32]).build().is_err());
+        Ok(())
+    }
+
     #[test]
     fn display_keys() -> Result<()> {
         let Seed { entropy, .. } = SeedBuilder::new().build()?;
@@ -197,4 +1462,358 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn public_child_derivation() -> Result<()> {
+        // BIP32 test vector 2, chain M/0.
+        let seed = hex::decode(
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a2\
+             9f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+        )?;
+        let keys =
+            MasterExtendedKeys::new(seed.as_slice().try_into()?, None, Network::Mainnet, true)?;
+        let child = keys.neuter().derive_child(0)?;
+
+        let expected = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH"
+            .from_base58()
+            .unwrap();
+        assert_eq!(child.chain_code[..], expected[13..45]);
+        assert_eq!(child.pubkey().to_string(), hex::encode(&expected[45..78]));
+
+        assert!(child.derive_child(HARDENED_OFFSET).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn uncompressed_xpub_serializes_the_compressed_key() -> Result<()> {
+        let standard = ExtendedPrivKey::new([7u8; 64], None, Network::Mainnet, false)?.neuter();
+        let compressed = ExtendedPrivKey::new([7u8; 64], None, Network::Mainnet, true)?.neuter();
+        assert!(matches!(standard.pubkey(), PublicKey::Standard(_)));
+        assert_eq!(standard.to_string(), compressed.to_string());
+
+        let child = standard.derive_child(3)?;
+        assert!(matches!(child.pubkey(), PublicKey::Standard(_)));
+        assert_eq!(child.to_string(), compressed.derive_child(3)?.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range_child_number() -> Result<()> {
+        let master = ExtendedPrivKey::new([7u8; 64], None, Network::Mainnet, true)?;
+        let child = ChildNumber::Normal(HARDENED_OFFSET + 5);
+        assert!(master.derive_child(child).is_err());
+        assert!(master
+            .neuter()
+            .derive_path(&DerivationPath::master().child(child))
+            .is_err());
+        assert!(Ed25519ExtendedKey::new(&[7u8; 64])?
+            .derive_child(child)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn private_path_derivation() -> Result<()> {
+        // BIP32 test vector 2.
+        let seed = hex::decode(
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a2\
+             9f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+        )?;
+        let master =
+            MasterExtendedKeys::new(seed.as_slice().try_into()?, None, Network::Mainnet, true)?;
+
+        let vectors = [
+            ("m/0", "xprv9vHkqa6EV4sPZHYqZznhT2NPtPCjKuDKGY38FBWLvgaDx45zo9WQRUT3dKYnjwih2yJD9mkrocEZXo1ex8G81dwSM1fwqWpWkeS3v86pgKt"),
+            ("m/0/2147483647h/1/2147483646'/2", "xprvA2nrNbFZABcdryreWet9Ea4LvTJcGsqrMzxHx98MMrotbir7yrKCEXw7nadnHM8Dq38EGfSh6dqA9QWTyefMLEcBYJUuekgW4BYPJcr9E7j"),
+        ];
+        for (path, xprv) in vectors.iter() {
+            let keys = master.derive_path(&path.parse()?)?;
+            let expected = xprv.from_base58().unwrap();
+            assert_eq!(keys.chain_code[..], expected[13..45]);
+            assert_eq!(keys.private.secret[..], expected[46..78]);
+        }
+
+        // Public derivation agrees with private derivation on normal steps.
+        let path: DerivationPath = "m/0/1".parse()?;
+        assert_eq!(
+            master.derive_path(&path)?.pubkey().to_string(),
+            master
+                .neuter()
+                .derive_child(0)?
+                .derive_child(1)?
+                .pubkey()
+                .to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn extended_key_serialization() -> Result<()> {
+        // BIP32 test vector 2.
+        let seed = hex::decode(
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a2\
+             9f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+        )?;
+        let master =
+            ExtendedPrivKey::new(seed.as_slice().try_into()?, None, Network::Mainnet, true)?;
+
+        let vectors = [
+            (
+                "m",
+                "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB",
+                "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U",
+            ),
+            (
+                "m/0",
+                "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH",
+                "xprv9vHkqa6EV4sPZHYqZznhT2NPtPCjKuDKGY38FBWLvgaDx45zo9WQRUT3dKYnjwih2yJD9mkrocEZXo1ex8G81dwSM1fwqWpWkeS3v86pgKt",
+            ),
+            (
+                "m/0/2147483647'/1/2147483646'/2",
+                "xpub6FnCn6nSzZAw5Tw7cgR9bi15UV96gLZhjDstkXXxvCLsUXBGXPdSnLFbdpq8p9HmGsApME5hQTZ3emM2rnY5agb9rXpVGyy3bdW6EEgAtqt",
+                "xprvA2nrNbFZABcdryreWet9Ea4LvTJcGsqrMzxHx98MMrotbir7yrKCEXw7nadnHM8Dq38EGfSh6dqA9QWTyefMLEcBYJUuekgW4BYPJcr9E7j",
+            ),
+        ];
+        for (path, xpub, xprv) in vectors.iter() {
+            let keys = master.derive_path(&path.parse()?)?;
+            assert_eq!(keys.to_string(), *xprv);
+            assert_eq!(keys.neuter().to_string(), *xpub);
+
+            assert_eq!(xprv.parse::<ExtendedPrivKey>()?.to_string(), *xprv);
+            assert_eq!(xpub.parse::<ExtendedPubKey>()?.to_string(), *xpub);
+        }
+
+        let child = master.derive_path(&"m/0/2147483647'".parse()?)?;
+        assert_eq!(child.depth(), 2);
+        assert_eq!(child.child_number(), ChildNumber::Hardened(2147483647));
+        assert_eq!(
+            child.parent_fingerprint(),
+            master.derive_child(ChildNumber::Normal(0))?.fingerprint()?
+        );
+        assert_eq!(hex::encode(master.fingerprint()?), "bd16bee5");
+        assert_eq!(master.depth(), 0);
+        assert_eq!(master.parent_fingerprint(), [0u8; 4]);
+
+        let neutered = ExtendedPubKey::from(&master.derive_path(&"m/0".parse()?)?);
+        assert_eq!(
+            neutered.derive_path(&"0/1".parse()?)?.to_string(),
+            master
+                .derive_path(&"m/0/0/1".parse()?)?
+                .neuter()
+                .to_string()
+        );
+
+        // Public derivation tracks the same depth, fingerprint and index.
+        let xpub = master.neuter().derive_child(0)?;
+        assert_eq!(xpub.to_string(), vectors[1].1);
+
+        let testnet = ExtendedPrivKey::new([7u8; 64], None, Network::Testnet, true)?;
+        let tprv = testnet.to_string();
+        assert!(tprv.starts_with("tprv"));
+        assert!(testnet.neuter().to_string().starts_with("tpub"));
+        assert_eq!(
+            tprv.parse::<ExtendedPrivKey>()?.privkey(),
+            testnet.privkey()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reject_malformed_extended_keys() -> Result<()> {
+        let keys = ExtendedPrivKey::new([7u8; 64], None, Network::Mainnet, true)?;
+
+        // Re-encode a tampered 78-byte payload with a valid checksum.
+        let encode = |mut data: Vec<u8>, tamper: &dyn Fn(&mut Vec<u8>)| {
+            data.truncate(EXTENDED_KEY_LEN - 4);
+            tamper(&mut data);
+            let cs = crypto::checksum(&data);
+            data.extend_from_slice(&cs);
+            data.to_base58()
+        };
+        let xprv = keys.layout();
+        let xpub = keys.neuter().layout();
+
+        let invalid_private = [
+            // Public key data under a private version.
+            encode(xprv.clone(), &|d| d[45..].copy_from_slice(&xpub[45..78])),
+            // Secret of zero, and the curve order n.
+            encode(xprv.clone(), &|d| d[46..].copy_from_slice(&[0u8; 32])),
+            encode(xprv.clone(), &|d| {
+                d[46..].copy_from_slice(
+                    &hex::decode(
+                        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+                    )
+                    .unwrap(),
+                )
+            }),
+            // Master key with a parent fingerprint or a child number.
+            encode(xprv.clone(), &|d| d[5] = 1),
+            encode(xprv.clone(), &|d| d[12] = 1),
+            // Unknown version.
+            encode(xprv.clone(), &|d| d[0] = 0xff),
+        ];
+        for s in invalid_private.iter() {
+            assert!(s.parse::<ExtendedPrivKey>().is_err(), "{}", s);
+        }
+
+        let invalid_public = [
+            // Private key data under a public version.
+            encode(xpub.clone(), &|d| d[45..].copy_from_slice(&xprv[45..78])),
+            // Uncompressed prefix, and a point not on the curve.
+            encode(xpub.clone(), &|d| d[45] = 4),
+            encode(xpub.clone(), &|d| {
+                d[45..].copy_from_slice(&[2u8; 33]);
+                d[77] = 7;
+            }),
+            encode(xpub.clone(), &|d| d[8] = 1),
+        ];
+        for s in invalid_public.iter() {
+            assert!(s.parse::<ExtendedPubKey>().is_err(), "{}", s);
+        }
+
+        // A key of the other kind is rejected by its version bytes.
+        assert_eq!(
+            keys.to_string().parse::<ExtendedPubKey>().unwrap_err(),
+            Error::InvalidNetwork
+        );
+        assert_eq!(
+            keys.neuter().to_string().parse::<ExtendedPrivKey>().err(),
+            Some(Error::InvalidNetwork)
+        );
+
+        let mut corrupted = keys.layout();
+        corrupted[81] ^= 1;
+        assert_eq!(
+            corrupted.to_base58().parse::<ExtendedPrivKey>().err(),
+            Some(Error::InvalidChecksum)
+        );
+        assert_eq!(
+            xprv[..40].to_base58().parse::<ExtendedPrivKey>().err(),
+            Some(Error::InvalidExtendedKey)
+        );
+        assert!("xprv0OIl".parse::<ExtendedPrivKey>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn slip10_ed25519_derivation() -> Result<()> {
+        // SLIP-0010 ed25519 test vector 1.
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f")?;
+        let master = Ed25519ExtendedKey::new(&seed)?;
+
+        let vectors = [
+            (
+                "m",
+                "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+            ),
+            (
+                "m/0'",
+                "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+            ),
+            (
+                "m/0'/1'/2'/2'/1000000000'",
+                "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+                "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+                "3c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+            ),
+        ];
+        for (path, chain_code, secret, public) in vectors.iter() {
+            let key = master.derive_path(&path.parse()?)?;
+            assert_eq!(hex::encode(key.chain_code()), *chain_code);
+            assert_eq!(hex::encode(key.secret()), *secret);
+            assert_eq!(hex::encode(key.public_key()), *public);
+        }
+
+        assert!(master.derive_child(ChildNumber::Normal(0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn derive_range_is_lazy_and_matches_paths() -> Result<()> {
+        let master = ExtendedPrivKey::new([3u8; 64], None, Network::Mainnet, true)?;
+        let prefix: DerivationPath = "m/84'/0'/0'/0".parse()?;
+
+        let pairs: Vec<(u32, KeyPair)> = master.derive_range(&prefix, 5..8)?.collect();
+        assert_eq!(
+            pairs.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![5, 6, 7]
+        );
+        for (index, pair) in &pairs {
+            let keys = master.derive_path(&prefix.child(ChildNumber::Normal(*index)))?;
+            assert_eq!(pair.private().secret, keys.private.secret);
+            assert_eq!(pair.public().to_string(), keys.pubkey().to_string());
+        }
+
+        // Only the keys that are asked for get derived.
+        let mut range = master.derive_range(&prefix, 0..u32::MAX)?;
+        assert_eq!(range.nth(2).map(|(i, _)| i), Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn cached_derivation_matches_uncached() -> Result<()> {
+        let master = ExtendedPrivKey::new([5u8; 64], None, Network::Mainnet, true)?;
+        let cached = master.clone().with_cache(4);
+
+        for path in &["m/84'/0'/0'/0/1", "m/84'/0'/0'/0/2", "m/84'/0'/0'/0/1"] {
+            let path: DerivationPath = path.parse()?;
+            assert_eq!(
+                cached.derive_path(&path)?.to_string(),
+                master.derive_path(&path)?.to_string()
+            );
+        }
+        // The least recently used prefixes were evicted.
+        let cache = cached.cache.as_ref().unwrap().lock().unwrap();
+        assert_eq!(cache.len(), 4);
+        assert!(cache.contains(&"m/84'/0'/0'/0".parse()?));
+        assert!(!cache.contains(&"m/84'".parse()?));
+
+        let xpub = master.neuter().with_cache(8);
+        let path: DerivationPath = "0/7".parse()?;
+        assert_eq!(
+            xpub.derive_path(&path)?.to_string(),
+            master.neuter().derive_path(&path)?.to_string()
+        );
+        assert!(xpub.derive_path(&"0'/7".parse()?).is_err());
+        assert!(master.with_cache(0).cache.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn backup_phrase_round_trip() -> Result<()> {
+        let Seed { entropy, .. } = SeedBuilder::new().build()?;
+        let keys = MasterExtendedKeys::new(entropy, None, Network::Testnet, true)?;
+
+        let phrase = keys.to_backup_phrase()?;
+        assert_eq!(phrase.len(), 51);
+
+        let restored = MasterExtendedKeys::from_backup_phrase(&phrase, Network::Testnet, true)?;
+        assert_eq!(restored.private.secret, keys.private.secret);
+        assert_eq!(restored.chain_code, keys.chain_code);
+
+        let mut swapped = phrase[24..48].to_vec();
+        swapped.extend_from_slice(&phrase[..24]);
+        swapped.extend_from_slice(&phrase[48..]);
+        assert!(MasterExtendedKeys::from_backup_phrase(&swapped, Network::Testnet, true).is_err());
+
+        let Seed { entropy, .. } = SeedBuilder::new().build()?;
+        let other = MasterExtendedKeys::new(entropy, None, Network::Testnet, true)?;
+        let mut mixed = phrase[..24].to_vec();
+        mixed.extend_from_slice(&other.to_backup_phrase()?[24..]);
+        assert!(MasterExtendedKeys::from_backup_phrase(&mixed, Network::Testnet, true).is_err());
+
+        assert!(keys
+            .derive_child(ChildNumber::Normal(0))?
+            .to_backup_phrase()
+            .is_err());
+
+        assert!(
+            MasterExtendedKeys::from_backup_phrase(&phrase[..24], Network::Testnet, true).is_err()
+        );
+        Ok(())
+    }
 }
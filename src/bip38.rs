@@ -0,0 +1,394 @@
+//! [BIP38] passphrase-protected private keys, the `6P...` strings of paper
+//! wallets.
+//!
+//! Two modes exist:
+//!
+//! * Without EC multiplication, the key owner encrypts a key they already
+//!   hold with [PrivateKey::to_bip38](crate::PrivateKey::to_bip38).
+//! * With EC multiplication, the owner hands an [intermediate_code] to a
+//!   third party, who can then [generate](encrypt_from_intermediate)
+//!   encrypted keys without ever learning them or the passphrase.
+//!
+//! [PrivateKey::from_bip38](crate::PrivateKey::from_bip38) decrypts both.
+//! Keys are tied to their Bitcoin mainnet P2PKH address, whose hash is part
+//! of the encrypted string, so a decrypted key is always on
+//! [Mainnet](crate::Network::Mainnet).
+//!
+//! [BIP38]: https://github.com/bitcoin/bips/blob/master/bip-0038.mediawiki
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::Aes256;
+use base58::{FromBase58, ToBase58};
+use rand_core::{OsRng, RngCore};
+use secp256k1::key;
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::crypto;
+use crate::{Address, Error, Network, PrivateKey, PublicKey, Secret, SECP256K1};
+
+const PREFIX_NON_EC: [u8; 2] = [0x01, 0x42];
+const PREFIX_EC: [u8; 2] = [0x01, 0x43];
+const INTERMEDIATE_MAGIC: [u8; 7] = [0x2c, 0xe9, 0xb3, 0xe1, 0xff, 0x39, 0xe2];
+const FLAG_NON_EC: u8 = 0xc0;
+const FLAG_COMPRESSED: u8 = 0x20;
+const FLAG_LOT_SEQUENCE: u8 = 0x04;
+const ENCRYPTED_LEN: usize = 39;
+const INTERMEDIATE_LEN: usize = 49;
+
+/// Error originating from [bip38](crate::bip38) module.
+#[derive(Error, Debug)]
+pub enum Bip38Error {
+    #[error("Not a BIP38 encrypted key or intermediate code")]
+    InvalidFormat,
+    #[error("Wrong passphrase")]
+    InvalidPassphrase,
+    #[error("Lot {0} or sequence {1} out of range")]
+    InvalidLotSequence(u32, u32),
+    #[error(transparent)]
+    Key(#[from] Error),
+}
+
+impl PrivateKey {
+    /// Encrypt the key with `passphrase`, without EC multiplication.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use keymaker::PrivateKey;
+    ///
+    /// let private: PrivateKey = "L44B5gGEpqEDRS9vVPz7QT35jcBG2r3CZwSwQ4fCewXAhAhqGVpP".parse().unwrap();
+    /// let encrypted = private.to_bip38("TestingOneTwoThree").unwrap();
+    /// assert_eq!(encrypted, "6PYNKZ1EAgYgmQfmNVamxyXVWHzK5s6DGhwP4J5o44cvXdoY7sRzhtpUeo");
+    /// assert_eq!(PrivateKey::from_bip38(&encrypted, "TestingOneTwoThree").unwrap(), private);
+    /// ```
+    pub fn to_bip38(&self, passphrase: &str) -> Result<String, Bip38Error> {
+        let address_hash = address_hash(&self.public_key()?);
+        let derived = scrypt(normalize(passphrase).as_bytes(), &address_hash, 14, 8, 8);
+        let cipher = Aes256::new(GenericArray::from_slice(&derived[32..]));
+
+        let mut data = PREFIX_NON_EC.to_vec();
+        data.push(FLAG_NON_EC | if self.compressed { FLAG_COMPRESSED } else { 0 });
+        data.extend_from_slice(&address_hash);
+        for half in 0..2 {
+            let range = half * 16..half * 16 + 16;
+            data.extend_from_slice(&encrypt_block(
+                &cipher,
+                &xor(&self.secret[range.clone()], &derived[range]),
+            ));
+        }
+        Ok(to_base58check(&data))
+    }
+
+    /// Decrypt a BIP38 key made with or without EC multiplication.
+    ///
+    /// Fails with `Bip38Error::InvalidPassphrase` if the key does not match
+    /// the address hash it carries, and with `Bip38Error::InvalidFormat`,
+    /// before running scrypt, if a key without EC multiplication has a flag
+    /// byte other than `0xc0` or `0xe0`.
+    pub fn from_bip38(encrypted: &str, passphrase: &str) -> Result<Self, Bip38Error> {
+        let data = from_base58check(encrypted)?;
+        if data.len() != ENCRYPTED_LEN {
+            return Err(Bip38Error::InvalidFormat);
+        }
+        let flag = data[2];
+        let compressed = flag & FLAG_COMPRESSED != 0;
+        let expected_hash = &data[3..7];
+        let passphrase = normalize(passphrase);
+
+        let secret = match [data[0], data[1]] {
+            PREFIX_NON_EC => {
+                if flag & !FLAG_COMPRESSED != FLAG_NON_EC {
+                    return Err(Bip38Error::InvalidFormat);
+                }
+                let derived = scrypt(passphrase.as_bytes(), expected_hash, 14, 8, 8);
+                let cipher = Aes256::new(GenericArray::from_slice(&derived[32..]));
+                let mut secret = Secret::default();
+                for half in 0..2 {
+                    let range = half * 16..half * 16 + 16;
+                    let block = decrypt_block(&cipher, &data[7 + half * 16..23 + half * 16]);
+                    secret[range.clone()].copy_from_slice(&xor(&block, &derived[range]));
+                }
+                secret
+            }
+            PREFIX_EC => {
+                let owner_entropy = &data[7..15];
+                let lot_sequence = flag & FLAG_LOT_SEQUENCE != 0;
+                let pass_factor = pass_factor(&passphrase, owner_entropy, lot_sequence);
+                let pass_point = pass_point(&pass_factor)?;
+
+                let mut salt = expected_hash.to_vec();
+                salt.extend_from_slice(owner_entropy);
+                let derived = scrypt(&pass_point, &salt, 10, 1, 1);
+                let cipher = Aes256::new(GenericArray::from_slice(&derived[32..]));
+
+                // The second half of the first part is inside the second part.
+                let part2 = xor(&decrypt_block(&cipher, &data[23..39]), &derived[16..32]);
+                let mut part1 = data[15..23].to_vec();
+                part1.extend_from_slice(&part2[..8]);
+                let mut seed_b = xor(&decrypt_block(&cipher, &part1), &derived[..16]);
+                seed_b.extend_from_slice(&part2[8..]);
+
+                let mut secret = key::SecretKey::from_slice(&pass_factor).map_err(Error::from)?;
+                secret
+                    .mul_assign(&crypto::dhash256(&seed_b))
+                    .map_err(Error::from)?;
+                let mut bytes = Secret::default();
+                bytes.copy_from_slice(&secret[..]);
+                bytes
+            }
+            _ => return Err(Bip38Error::InvalidFormat),
+        };
+
+        let private = PrivateKey {
+            network: Network::Mainnet,
+            secret,
+            compressed,
+        };
+        if address_hash(&private.public_key()?) != expected_hash {
+            return Err(Bip38Error::InvalidPassphrase);
+        }
+        Ok(private)
+    }
+}
+
+/// Create the intermediate code a key owner gives to whoever generates
+/// encrypted keys for them.
+///
+/// With `lot_sequence`, the lot number (below 1048576) and sequence number
+/// (below 4096) are embedded in every key generated from the code.
+pub fn intermediate_code(
+    passphrase: &str,
+    lot_sequence: Option<(u32, u32)>,
+) -> Result<String, Bip38Error> {
+    let mut owner_entropy = [0u8; 8];
+    match lot_sequence {
+        Some((lot, sequence)) => {
+            if lot >= 1 << 20 || sequence >= 1 << 12 {
+                return Err(Bip38Error::InvalidLotSequence(lot, sequence));
+            }
+            OsRng.fill_bytes(&mut owner_entropy[..4]);
+            owner_entropy[4..].copy_from_slice(&(lot << 12 | sequence).to_be_bytes());
+        }
+        None => OsRng.fill_bytes(&mut owner_entropy),
+    }
+
+    let pass_factor = pass_factor(
+        &normalize(passphrase),
+        &owner_entropy,
+        lot_sequence.is_some(),
+    );
+    let mut data = INTERMEDIATE_MAGIC.to_vec();
+    data.push(if lot_sequence.is_some() { 0x51 } else { 0x53 });
+    data.extend_from_slice(&owner_entropy);
+    data.extend_from_slice(&pass_point(&pass_factor)?);
+    Ok(to_base58check(&data))
+}
+
+/// Generate a new encrypted key from an owner's `intermediate` code,
+/// returning it along with its address.
+pub fn encrypt_from_intermediate(
+    intermediate: &str,
+    compressed: bool,
+) -> Result<(String, Address), Bip38Error> {
+    let data = from_base58check(intermediate)?;
+    if data.len() != INTERMEDIATE_LEN
+        || data[..7] != INTERMEDIATE_MAGIC
+        || (data[7] != 0x51 && data[7] != 0x53)
+    {
+        return Err(Bip38Error::InvalidFormat);
+    }
+    let lot_sequence = data[7] == 0x51;
+    let owner_entropy = &data[8..16];
+    let pass_point = &data[16..];
+
+    let mut seed_b = [0u8; 24];
+    OsRng.fill_bytes(&mut seed_b);
+    let mut point = key::PublicKey::from_slice(pass_point).map_err(Error::from)?;
+    point
+        .mul_assign(&SECP256K1, &crypto::dhash256(&seed_b))
+        .map_err(Error::from)?;
+    let public = if compressed {
+        PublicKey::Compressed(point.serialize())
+    } else {
+        PublicKey::Standard(point.serialize_uncompressed())
+    };
+    let address = Address::p2pkh(&public, Network::Mainnet);
+    let address_hash = address_hash(&public);
+
+    let mut salt = address_hash.to_vec();
+    salt.extend_from_slice(owner_entropy);
+    let derived = scrypt(pass_point, &salt, 10, 1, 1);
+    let cipher = Aes256::new(GenericArray::from_slice(&derived[32..]));
+
+    let part1 = encrypt_block(&cipher, &xor(&seed_b[..16], &derived[..16]));
+    let mut block = part1[8..].to_vec();
+    block.extend_from_slice(&seed_b[16..]);
+    let part2 = encrypt_block(&cipher, &xor(&block, &derived[16..32]));
+
+    let mut flag = if compressed { FLAG_COMPRESSED } else { 0 };
+    if lot_sequence {
+        flag |= FLAG_LOT_SEQUENCE;
+    }
+    let mut encrypted = PREFIX_EC.to_vec();
+    encrypted.push(flag);
+    encrypted.extend_from_slice(&address_hash);
+    encrypted.extend_from_slice(owner_entropy);
+    encrypted.extend_from_slice(&part1[..8]);
+    encrypted.extend_from_slice(&part2);
+    Ok((to_base58check(&encrypted), address))
+}
+
+/// The passphrase-derived secret of EC-multiplied keys.
+fn pass_factor(passphrase: &str, owner_entropy: &[u8], lot_sequence: bool) -> Secret {
+    if lot_sequence {
+        let pre_factor = scrypt(passphrase.as_bytes(), &owner_entropy[..4], 14, 8, 8);
+        let mut data = pre_factor[..32].to_vec();
+        data.extend_from_slice(owner_entropy);
+        crypto::dhash256(&data)
+    } else {
+        let mut pass_factor = Secret::default();
+        pass_factor.copy_from_slice(&scrypt(passphrase.as_bytes(), owner_entropy, 14, 8, 8)[..32]);
+        pass_factor
+    }
+}
+
+fn pass_point(pass_factor: &Secret) -> Result<[u8; 33], Error> {
+    let secret = key::SecretKey::from_slice(pass_factor)?;
+    Ok(key::PublicKey::from_secret_key(&SECP256K1, &secret).serialize())
+}
+
+/// The first 4 bytes of the double SHA-256 of the mainnet P2PKH address.
+fn address_hash(public: &PublicKey) -> [u8; 4] {
+    let address = Address::p2pkh(public, Network::Mainnet).to_string();
+    crypto::checksum(address.as_bytes())
+}
+
+/// 64 bytes of scrypt output with `N = 2^log_n`.
+fn scrypt(password: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32) -> [u8; 64] {
+    let mut output = [0u8; 64];
+    // The parameters are constants well within scrypt's limits.
+    let params = scrypt::Params::new(log_n, r, p, output.len()).expect("valid scrypt parameters");
+    scrypt::scrypt(password, salt, &params, &mut output).expect("valid scrypt output length");
+    output
+}
+
+fn encrypt_block(cipher: &Aes256, data: &[u8]) -> Vec<u8> {
+    let mut block = GenericArray::clone_from_slice(data);
+    cipher.encrypt_block(&mut block);
+    block.to_vec()
+}
+
+fn decrypt_block(cipher: &Aes256, data: &[u8]) -> Vec<u8> {
+    let mut block = GenericArray::clone_from_slice(data);
+    cipher.decrypt_block(&mut block);
+    block.to_vec()
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+fn normalize(passphrase: &str) -> String {
+    passphrase.nfc().collect()
+}
+
+fn to_base58check(data: &[u8]) -> String {
+    let mut data = data.to_vec();
+    data.extend_from_slice(&crypto::checksum(&data));
+    data.to_base58()
+}
+
+fn from_base58check(s: &str) -> Result<Vec<u8>, Bip38Error> {
+    let data = s.from_base58().map_err(|_| Bip38Error::InvalidFormat)?;
+    if data.len() < 4 {
+        return Err(Bip38Error::InvalidFormat);
+    }
+    let (data, checksum) = data.split_at(data.len() - 4);
+    if !crypto::verify_checksum(data, checksum) {
+        return Err(Bip38Error::Key(Error::InvalidChecksum));
+    }
+    Ok(data.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn non_ec_multiply_vectors() -> Result<(), Bip38Error> {
+        let cases = [
+            (
+                "TestingOneTwoThree",
+                "6PRVWUbkzzsbcVac2qwfssoUJAN1Xhrg6bNk8J7Nzm5H7kxEbn2Nh2ZoGg",
+                "5KN7MzqK5wt2TP1fQCYyHBtDrXdJuXbUzm4A9rKAteGu3Qi5CVR",
+            ),
+            (
+                "TestingOneTwoThree",
+                "6PYNKZ1EAgYgmQfmNVamxyXVWHzK5s6DGhwP4J5o44cvXdoY7sRzhtpUeo",
+                "L44B5gGEpqEDRS9vVPz7QT35jcBG2r3CZwSwQ4fCewXAhAhqGVpP",
+            ),
+        ];
+        for (passphrase, encrypted, wif) in cases.iter() {
+            let private: PrivateKey = wif.parse()?;
+            assert_eq!(private.to_bip38(passphrase)?, *encrypted);
+            assert_eq!(PrivateKey::from_bip38(encrypted, passphrase)?, private);
+        }
+        assert!(matches!(
+            PrivateKey::from_bip38(cases[0].1, "TestingOneTwoFour"),
+            Err(Bip38Error::InvalidPassphrase)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn non_ec_rejects_unknown_flags() -> Result<(), Bip38Error> {
+        let data = from_base58check("6PYNKZ1EAgYgmQfmNVamxyXVWHzK5s6DGhwP4J5o44cvXdoY7sRzhtpUeo")?;
+        for flag in [0x00, 0x40, 0xc4, 0xe1, 0xff] {
+            let mut tampered = data.clone();
+            tampered[2] = flag;
+            assert!(matches!(
+                PrivateKey::from_bip38(&to_base58check(&tampered), "TestingOneTwoThree"),
+                Err(Bip38Error::InvalidFormat)
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ec_multiply_vectors() -> Result<(), Bip38Error> {
+        let cases = [
+            (
+                "TestingOneTwoThree",
+                "6PfQu77ygVyJLZjfvMLyhLMQbYnu5uguoJJ4kMCLqWwPEdfpwANVS76gTX",
+                "5K4caxezwjGCGfnoPTZ8tMcJBLB7Jvyjv4xxeacadhq8nLisLR2",
+            ),
+            (
+                "MOLON LABE",
+                "6PgNBNNzDkKdhkT6uJntUXwwzQV8Rr2tZcbkDcuC9DZRsS6AtHts4Ypo1j",
+                "5JLdxTtcTHcfYcmJsNVy1v2PMDx432JPoYcBTVVRHpPaxUrdtf8",
+            ),
+        ];
+        for (passphrase, encrypted, wif) in cases.iter() {
+            let private: PrivateKey = wif.parse()?;
+            assert_eq!(PrivateKey::from_bip38(encrypted, passphrase)?, private);
+        }
+
+        let intermediate = intermediate_code("hunter2", Some((263183, 1)))?;
+        assert!(intermediate.starts_with("passphrase"));
+        let (encrypted, address) = encrypt_from_intermediate(&intermediate, true)?;
+        assert!(encrypted.starts_with("6P"));
+        let private = PrivateKey::from_bip38(&encrypted, "hunter2")?;
+        assert_eq!(
+            Address::p2pkh(&private.public_key()?, Network::Mainnet),
+            address
+        );
+
+        assert!(matches!(
+            intermediate_code("hunter2", Some((1 << 20, 0))),
+            Err(Bip38Error::InvalidLotSequence(..))
+        ));
+        Ok(())
+    }
+}
@@ -2,17 +2,23 @@
 //!
 //! [BIP39]: https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
 
-use anyhow::{Context, Result};
-use hex;
+use anyhow::Result;
+use lazy_static::lazy_static;
 use rand_core::{OsRng, RngCore};
 use ring::{digest, pbkdf2};
-use std::fs::File;
-use std::io::{prelude::*, BufReader};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
 use std::num::NonZeroU32;
 use std::path::Path;
 use std::str;
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 use to_binary::BinaryString;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::entropy::EntropyReport;
+pub use crate::mnemonic::{Mnemonic, Words};
 
 static PBKDF2_ALG: pbkdf2::Algorithm = pbkdf2::PBKDF2_HMAC_SHA512;
 const CREDENTIAL_LEN: usize = digest::SHA512_OUTPUT_LEN;
@@ -27,6 +33,7 @@ const BITS_PER_CHECKSUM_DIGIT: usize = 32;
 const DEFAULT_PASSPHRASE: &str = "";
 const DEFAULT_SALT_BASE: &str = "mnemonic";
 const WORDLIST_PATH: &str = "./wordlist.txt";
+const VALID_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
 
 /// Error originating from [bip39](bip39) module.
 #[derive(Error, Debug)]
@@ -44,6 +51,36 @@ pub enum Bip39Error {
         "Error creating interations for PDKF2 encoding with iteration = {0}. Please report a bug."
     )]
     Pdkf2IterError(u32),
+
+    #[error("Rejected weak entropy: {0}")]
+    WeakEntropy(String),
+
+    #[error("Wordlist {0} does not match the official BIP39 wordlist")]
+    CorruptedWordlist(String),
+
+    #[error("Unknown mnemonic word {0}")]
+    UnknownWord(String),
+
+    #[error("Invalid mnemonic word count {0}")]
+    InvalidWordCount(usize),
+
+    #[error("Invalid entropy length of {0} bytes")]
+    InvalidEntropyLength(usize),
+
+    #[error("Mnemonic checksum mismatch")]
+    ChecksumMismatch,
+
+    #[error("Invalid SeedQR payload")]
+    InvalidSeedQr,
+
+    #[error("Could not render QR code: {0}")]
+    QrEncoding(String),
+
+    #[error("Seed XOR needs at least 2 shares, got {0}")]
+    InvalidShareCount(usize),
+
+    #[error("Seed XOR shares must all have the same word count")]
+    ShareLengthMismatch,
 }
 
 /// Define convenient aliases for the bit size of the seed entropy.
@@ -82,6 +119,7 @@ pub struct SeedBuilder<'a> {
     passphrase: &'a str,
     salt: Option<Vec<u8>>,
     bits: usize,
+    entropy: Option<Vec<u8>>,
 }
 
 impl<'a> Default for SeedBuilder<'a> {
@@ -91,6 +129,7 @@ impl<'a> Default for SeedBuilder<'a> {
             passphrase: DEFAULT_PASSPHRASE,
             salt: Some(salt.as_bytes().to_vec()),
             bits: SIZE_128_BITS,
+            entropy: None,
         }
     }
 }
@@ -209,59 +248,56 @@ impl<'a> SeedBuilder<'a> {
         self
     }
 
+    /// Use caller-supplied entropy instead of drawing it from the OS.
+    /// The size of the entropy overrides any size set on the builder.
+    ///
+    /// The entropy is checked with [EntropyReport](crate::entropy::EntropyReport)
+    /// and [build](SeedBuilder::build) fails if any weakness is found.
+    ///
+    /// # Arguments
+    ///
+    /// * `entropy` - 16 to 32 bytes of entropy, in steps of 4 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::bip39::SeedBuilder;
+    /// use rand_core::{RngCore, OsRng};
+    ///
+    /// let mut entropy = [0u8; 32];
+    /// OsRng.fill_bytes(&mut entropy);
+    ///
+    /// let seed = SeedBuilder::new().entropy(entropy.to_vec()).build().unwrap();
+    /// assert_eq!(seed.mnemonic.len(), 24);
+    ///
+    /// assert!(SeedBuilder::new().entropy(vec![0u8; 16]).build().is_err());
+    /// ```
+    pub fn entropy(mut self, entropy: Vec<u8>) -> Self {
+        self.bits = entropy.len() * BYTE_LEN;
+        self.entropy.replace(entropy);
+        self
+    }
+
     pub fn build(self) -> Result<Seed, Bip39Error> {
-        let mut key: Vec<u8>;
-        match self.bits {
-            256 => {
-                key = vec![0u8; FOUR_BYTES_LEN];
+        let key = match self.entropy {
+            Some(entropy) => {
+                let report = EntropyReport::analyze(&entropy);
+                if !report.is_acceptable() {
+                    return Err(Bip39Error::WeakEntropy(report.to_string()));
+                }
+                entropy
             }
-            128 | _ => {
-                key = vec![0u8; TWO_BYTES_LEN];
+            None => {
+                let mut key = match self.bits {
+                    256 => vec![0u8; FOUR_BYTES_LEN],
+                    _ => vec![0u8; TWO_BYTES_LEN],
+                };
+                OsRng.fill_bytes(&mut key);
+                key
             }
-        }
-
-        OsRng.fill_bytes(&mut key);
-
-        let result = digest::digest(&digest::SHA256, &key);
-        let BinaryString(b) = BinaryString::from(result.as_ref());
-
-        let BinaryString(bin) = BinaryString::from(&key[..]);
-        let checksum_digits = bin.len() / BITS_PER_CHECKSUM_DIGIT;
-        let checksum = &b[..checksum_digits];
-        let ent = bin + checksum;
-
-        let subs = ent
-            .as_bytes()
-            .chunks(BLOCK_SIZE)
-            .map(str::from_utf8)
-            .collect::<Result<Vec<&str>, _>>()
-            .unwrap();
-
-        let indices: Vec<usize> = subs
-            .iter()
-            .map(|b| {
-                let intval = isize::from_str_radix(b, 2)
-                    .with_context(|| Bip39Error::ParseBinError(b.to_string()))
-                    .unwrap();
-                intval as usize
-            })
-            .collect();
-
-        let path = Path::new(WORDLIST_PATH);
-        if !path.exists() {
-            return Err(Bip39Error::MissingFileOrDirectory(
-                WORDLIST_PATH.to_string(),
-            ));
-        }
-
-        let file = File::open(path)
-            .with_context(|| Bip39Error::FileError(WORDLIST_PATH.to_string()))
-            .unwrap();
-
-        let reader = BufReader::new(file);
-        let words: Vec<String> = reader.lines().into_iter().map(|o| o.unwrap()).collect();
+        };
 
-        let mnemonic_words: Vec<String> = indices.iter().map(|i| words[*i].to_owned()).collect();
+        let mnemonic = Mnemonic::from_entropy(&key)?;
 
         let mut salt = (DEFAULT_SALT_BASE.to_string() + self.passphrase)
             .as_bytes()
@@ -270,7 +306,7 @@ impl<'a> SeedBuilder<'a> {
             salt = s;
         }
 
-        let password = mnemonic_words.join(" ");
+        let password = mnemonic.to_string();
         let mut seed_store: Credential = [0u8; CREDENTIAL_LEN];
         if let Some(iterations) = NonZeroU32::new(DEFAULT_PDKF2_ITERATIONS) {
             pbkdf2::derive(
@@ -287,13 +323,260 @@ impl<'a> SeedBuilder<'a> {
         let hex_str = hex::encode(&seed_store[..]);
 
         Ok(Seed {
-            mnemonic: mnemonic_words,
+            mnemonic,
             hex: hex_str,
             entropy: seed_store,
         })
     }
 }
 
+/// Languages with an official BIP39 wordlist.
+///
+/// Mnemonics generated by [SeedBuilder](SeedBuilder) are always English; the
+/// other wordlists are used to [reencode](Mnemonic::reencode) them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    Spanish,
+    French,
+    Italian,
+    Portuguese,
+    Czech,
+    Japanese,
+    Korean,
+    ChineseSimplified,
+    ChineseTraditional,
+}
+
+impl Language {
+    /// Every supported language.
+    pub fn all() -> &'static [Language] {
+        use Language::*;
+        &[
+            English,
+            Spanish,
+            French,
+            Italian,
+            Portuguese,
+            Czech,
+            Japanese,
+            Korean,
+            ChineseSimplified,
+            ChineseTraditional,
+        ]
+    }
+
+    /// SHA256 of the official wordlist file, as published in BIP39.
+    fn wordlist_digest(self) -> &'static str {
+        match self {
+            Language::English => "2f5eed53a4727b4bf8880d8f3f199efc90e58503646d9ff8eff3a2ed3b24dbda",
+            Language::Spanish => "46846a5a0139d1e3cb77293e521c2865f7bcdb82c44e8d0a06a2cd0ecba48c0b",
+            Language::French => "ebc3959ab7801a1df6bac4fa7d970652f1df76b683cd2f4003c941c63d517e59",
+            Language::Italian => "d392c49fdb700a24cd1fceb237c1f65dcc128f6b34a8aacb58b59384b5c648c2",
+            Language::Portuguese => {
+                "2685e9c194c82ae67e10ba59d9ea5345a23dc093e92276fc5361f6667d79cd3f"
+            }
+            Language::Czech => "7e80e161c3e93d9554c2efb78d4e3cebf8fc727e9c52e03b83b94406bdcc95fc",
+            Language::Japanese => {
+                "2eed0aef492291e061633d7ad8117f1a2b03eb80a29d0e4e3117ac2528d05ffd"
+            }
+            Language::Korean => "9e95f86c167de88f450f0aaf89e87f6624a57f973c67b516e338e8e8b8897f60",
+            Language::ChineseSimplified => {
+                "5c5942792bd8340cb8b27cd592f1015edf56a8c5b26276ee18a482428e7c5726"
+            }
+            Language::ChineseTraditional => {
+                "417b26b3d8500a4ae3d59717d7011952db6fc2fb84b807f3f94ac734e89c1b5f"
+            }
+        }
+    }
+
+    fn wordlist_path(self) -> &'static str {
+        match self {
+            Language::English => WORDLIST_PATH,
+            Language::Spanish => "./wordlists/spanish.txt",
+            Language::French => "./wordlists/french.txt",
+            Language::Italian => "./wordlists/italian.txt",
+            Language::Portuguese => "./wordlists/portuguese.txt",
+            Language::Czech => "./wordlists/czech.txt",
+            Language::Japanese => "./wordlists/japanese.txt",
+            Language::Korean => "./wordlists/korean.txt",
+            Language::ChineseSimplified => "./wordlists/chinese_simplified.txt",
+            Language::ChineseTraditional => "./wordlists/chinese_traditional.txt",
+        }
+    }
+}
+
+/// A parsed wordlist with a reverse index for O(1) word lookups.
+struct Wordlist {
+    words: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+lazy_static! {
+    static ref WORDLISTS: Mutex<HashMap<Language, Arc<Wordlist>>> = Mutex::new(HashMap::new());
+}
+
+/// Load the wordlist of `language`, reading and verifying the file only on
+/// first use.
+fn load_wordlist(language: Language) -> Result<Arc<Wordlist>, Bip39Error> {
+    let mut cache = WORDLISTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(wordlist) = cache.get(&language) {
+        return Ok(Arc::clone(wordlist));
+    }
+
+    let wordlist = Arc::new(read_wordlist(language)?);
+    cache.insert(language, Arc::clone(&wordlist));
+    Ok(wordlist)
+}
+
+/// Read the wordlist of `language` from disk and check it against the
+/// official digest, so a corrupted or tampered file never produces words.
+fn read_wordlist(language: Language) -> Result<Wordlist, Bip39Error> {
+    let wordlist_path = language.wordlist_path();
+    let path = Path::new(wordlist_path);
+    if !path.exists() {
+        return Err(Bip39Error::MissingFileOrDirectory(
+            wordlist_path.to_string(),
+        ));
+    }
+
+    let contents = fs::read(path).map_err(|_| Bip39Error::FileError(wordlist_path.to_string()))?;
+    let hash = digest::digest(&digest::SHA256, &contents);
+    if hex::encode(hash.as_ref()) != language.wordlist_digest() {
+        return Err(Bip39Error::CorruptedWordlist(wordlist_path.to_string()));
+    }
+
+    let words: Vec<String> = str::from_utf8(&contents)
+        .map_err(|_| Bip39Error::FileError(wordlist_path.to_string()))?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let index = words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| (word.to_owned(), i))
+        .collect();
+
+    Ok(Wordlist { words, index })
+}
+
+/// Encode raw entropy into mnemonic words, appending the SHA256 checksum bits.
+pub(crate) fn entropy_to_mnemonic(
+    entropy: &[u8],
+    language: Language,
+) -> Result<Vec<String>, Bip39Error> {
+    let result = digest::digest(&digest::SHA256, entropy);
+    let BinaryString(b) = BinaryString::from(result.as_ref());
+
+    let BinaryString(bin) = BinaryString::from(entropy);
+    let checksum_digits = bin.len() / BITS_PER_CHECKSUM_DIGIT;
+    let checksum = &b[..checksum_digits];
+    let ent = bin + checksum;
+
+    let indices = ent
+        .as_bytes()
+        .chunks(BLOCK_SIZE)
+        .map(|chunk| {
+            let b = str::from_utf8(chunk).map_err(|e| Bip39Error::ParseBinError(e.to_string()))?;
+            usize::from_str_radix(b, 2).map_err(|_| Bip39Error::ParseBinError(b.to_string()))
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    indices_to_mnemonic(&indices, language)
+}
+
+/// Look up the wordlist index of every mnemonic word.
+pub(crate) fn mnemonic_to_indices<S: AsRef<str>>(
+    mnemonic: &[S],
+    language: Language,
+) -> Result<Vec<usize>, Bip39Error> {
+    let wordlist = load_wordlist(language)?;
+    mnemonic
+        .iter()
+        .map(|keyword| {
+            // Wordlists are stored in NFKD, as BIP39 requires.
+            let keyword: String = keyword.as_ref().nfkd().collect();
+            wordlist
+                .index
+                .get(&keyword)
+                .copied()
+                .ok_or(Bip39Error::UnknownWord(keyword))
+        })
+        .collect()
+}
+
+/// Map wordlist indices back to mnemonic words.
+pub(crate) fn indices_to_mnemonic(
+    indices: &[usize],
+    language: Language,
+) -> Result<Vec<String>, Bip39Error> {
+    let wordlist = load_wordlist(language)?;
+    indices
+        .iter()
+        .map(|i| {
+            wordlist
+                .words
+                .get(*i)
+                .cloned()
+                .ok_or_else(|| Bip39Error::UnknownWord(i.to_string()))
+        })
+        .collect()
+}
+
+/// Decode mnemonic words back into the raw entropy they encode, verifying the
+/// word count, that every word is in the wordlist, and the checksum bits.
+pub(crate) fn mnemonic_to_entropy<S: AsRef<str>>(
+    mnemonic: &[S],
+    language: Language,
+) -> Result<Vec<u8>, Bip39Error> {
+    if !VALID_WORD_COUNTS.contains(&mnemonic.len()) {
+        return Err(Bip39Error::InvalidWordCount(mnemonic.len()));
+    }
+
+    let subs: Vec<String> = mnemonic_to_indices(mnemonic, language)?
+        .into_iter()
+        .map(|i| format!("{:011b}", i))
+        .collect();
+    let ent = subs.join("");
+
+    let checksum_digits = ent.len() / (BITS_PER_CHECKSUM_DIGIT + 1);
+    let (bin, checksum) = ent.split_at(ent.len() - checksum_digits);
+
+    let entropy = bin
+        .as_bytes()
+        .chunks(BYTE_LEN)
+        .map(|chunk| {
+            let b = str::from_utf8(chunk).map_err(|e| Bip39Error::ParseBinError(e.to_string()))?;
+            u8::from_str_radix(b, 2).map_err(|_| Bip39Error::ParseBinError(b.to_string()))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let hash = digest::digest(&digest::SHA256, &entropy);
+    let BinaryString(b) = BinaryString::from(hash.as_ref());
+    if &b[..checksum_digits] != checksum {
+        return Err(Bip39Error::ChecksumMismatch);
+    }
+
+    Ok(entropy)
+}
+
+/// Find the language whose wordlist contains every word of `mnemonic`.
+///
+/// A few words appear in more than one wordlist; if the whole mnemonic fits
+/// several languages the first one in [Language::all](Language::all) wins.
+pub fn detect_language<S: AsRef<str>>(mnemonic: &[S]) -> Result<Language, Bip39Error> {
+    for language in Language::all() {
+        if mnemonic_to_indices(mnemonic, *language).is_ok() {
+            return Ok(*language);
+        }
+    }
+    let unknown = mnemonic
+        .first()
+        .map(|word| word.as_ref().to_string())
+        .unwrap_or_default();
+    Err(Bip39Error::UnknownWord(unknown))
+}
+
 /// Container of the mnemonic code words, the entropy byte array, and hex string.
 /// Use [SeedBuilder](SeedBuilder) to create.
 ///
@@ -304,12 +587,12 @@ impl<'a> SeedBuilder<'a> {
 /// let seed = SeedBuilder::new().build().unwrap();
 /// ```
 pub struct Seed {
-    pub mnemonic: Vec<String>,
+    pub mnemonic: Mnemonic,
     pub entropy: Credential,
     pub hex: String,
 }
 
-impl ToString for Seed {
+impl fmt::Display for Seed {
     /// Encode the seed into a hex string.
     ///
     /// # Examples
@@ -320,55 +603,33 @@ impl ToString for Seed {
     /// let hex_seed = seed.to_string();
     /// assert_eq!(hex_seed.len(), 128);
     /// ```
-    fn to_string(&self) -> String {
-        if self.hex.len() <= 0 {
-            return hex::encode(&self.entropy[..]);
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.hex.is_empty() {
+            return hex::encode(&self.entropy[..]).fmt(f);
         }
-        self.hex.to_owned()
+        self.hex.fmt(f)
     }
 }
 
 impl Seed {
+    /// Check that the mnemonic is well-formed: a valid word count, every word
+    /// in the wordlist, and matching checksum bits.
     pub fn validate(&self) -> bool {
-        let file = File::open(Path::new(WORDLIST_PATH)).unwrap();
-        let reader = BufReader::new(file);
-        let words: Vec<String> = reader.lines().into_iter().map(|o| o.unwrap()).collect();
-
-        let mut indices: Vec<usize> = Vec::with_capacity(self.mnemonic.len());
-        for keyword in self.mnemonic.clone() {
-            for (i, word) in (&words).into_iter().enumerate() {
-                if keyword == *word {
-                    indices.push(i);
-                }
-            }
-        }
-
-        let subs: Vec<String> = indices.into_iter().map(|i| format!("{:011b}", i)).collect();
-        let ent = subs.join("");
-
-        let checksum_size = match self.mnemonic.len() {
-            12 => SIZE_128_BITS,
-            24 => SIZE_256_BITS,
-            _ => SIZE_128_BITS,
-        };
-
-        let checksum_digits = checksum_size / BITS_PER_CHECKSUM_DIGIT;
-        let bin = &ent[..ent.len() - 4];
-        let checksum = &ent[ent.len() - 4..];
+        self.validate_detailed().is_ok()
+    }
 
-        let key: Vec<u8> = bin
-            .as_bytes()
-            .chunks(BYTE_LEN)
-            .map(|i| {
-                let b = str::from_utf8(i).unwrap();
-                let intval = isize::from_str_radix(b, 2).unwrap();
-                intval as u8
-            })
-            .collect();
+    /// Like [validate](Seed::validate), but report why the mnemonic is invalid.
+    ///
+    /// A [Mnemonic](Mnemonic) is validated when it is built, so this only
+    /// fails if its wordlist can no longer be loaded.
+    pub fn validate_detailed(&self) -> Result<(), Bip39Error> {
+        mnemonic_to_entropy(self.mnemonic.as_ref(), self.mnemonic.language()).map(|_| ())
+    }
 
-        let hash = digest::digest(&digest::SHA256, &key);
-        let BinaryString(b) = BinaryString::from(hash.as_ref());
-        &b[..checksum_digits] == checksum
+    /// The mnemonic in another wordlist language. See
+    /// [Mnemonic::reencode](Mnemonic::reencode).
+    pub fn reencode(&self, language: Language) -> Result<Mnemonic, Bip39Error> {
+        self.mnemonic.reencode(language)
     }
 }
 
@@ -376,6 +637,8 @@ impl Seed {
 mod tests {
 
     use super::*;
+    use std::fs::File;
+    use std::io::{prelude::*, BufReader};
 
     const BYTE_LEN: usize = 8;
     enum Error {
@@ -425,7 +688,7 @@ mod tests {
 
         let Seed { mnemonic, hex, .. } = &seed;
 
-        let password = mnemonic.join(" ");
+        let password = mnemonic.to_string();
         let mut store = [0u8; CREDENTIAL_LEN];
         hex_decode_to_slice(hex, &mut store).unwrap();
 
@@ -442,19 +705,19 @@ mod tests {
         {
             assert_eq!(verified, ());
         } else {
-            assert!(false);
+            panic!("pbkdf2 verification failed");
         }
 
         let file = File::open(Path::new(WORDLIST_PATH)).unwrap();
         let reader = BufReader::new(file);
 
-        let words: Vec<String> = reader.lines().into_iter().map(|o| o.unwrap()).collect();
+        let words: Vec<String> = reader.lines().map(|o| o.unwrap()).collect();
 
         let mut indices: Vec<usize> = Vec::with_capacity(mnemonic.len());
 
-        for keyword in mnemonic.clone() {
-            for (i, word) in (&words).into_iter().enumerate() {
-                if keyword == *word {
+        for keyword in mnemonic {
+            for (i, word) in words.iter().enumerate() {
+                if keyword == word {
                     indices.push(i);
                 }
             }
@@ -483,4 +746,53 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn validate_detailed_errors() -> Result<(), Bip39Error> {
+        let seed = SeedBuilder::new().size(MnemonicSize::Size24Words).build()?;
+        assert!(seed.validate());
+        seed.validate_detailed()?;
+
+        let mut words: Vec<String> = seed.mnemonic.iter().map(str::to_string).collect();
+        words.pop();
+        assert!(matches!(
+            mnemonic_to_entropy(&words, Language::English),
+            Err(Bip39Error::InvalidWordCount(23))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn wordlist_is_cached_and_indexed() -> Result<(), Bip39Error> {
+        let wordlist = load_wordlist(Language::English)?;
+        assert!(Arc::ptr_eq(&wordlist, &load_wordlist(Language::English)?));
+        assert_eq!(wordlist.words.len(), 2048);
+        assert_eq!(wordlist.index["zoo"], 2047);
+        Ok(())
+    }
+
+    #[test]
+    fn reencode_between_languages() -> Result<(), Bip39Error> {
+        let seed = SeedBuilder::new().size(MnemonicSize::Size24Words).build()?;
+
+        for language in Language::all() {
+            let words = seed.reencode(*language)?;
+            assert_eq!(words.len(), 24);
+            assert_eq!(words.language(), *language);
+            assert_eq!(words.indices()?, seed.mnemonic.indices()?);
+            assert_eq!(words.entropy(), seed.mnemonic.entropy());
+            assert_eq!(words.reencode(Language::English)?, seed.mnemonic);
+        }
+
+        let mut words = vec!["ábaco"; 11];
+        words.push("abierto");
+        assert_eq!(detect_language(&words)?, Language::Spanish);
+        let spanish = Mnemonic::from_phrase(&words.join(" "))?;
+        assert_eq!(spanish.language(), Language::Spanish);
+        assert_eq!(
+            spanish.reencode(Language::English)?.indices()?,
+            mnemonic_to_indices(&words, Language::Spanish)?
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,253 @@
+//! Cardano Shelley wallets: Icarus master keys, [BIP32-Ed25519] derivation
+//! and bech32 base addresses.
+//!
+//! Unlike [SLIP-0010](crate::bip32::Ed25519ExtendedKey), BIP32-Ed25519 keeps
+//! the extended ed25519 scalar itself, so non-hardened children exist and
+//! share the account's public key arithmetic. Wallets derive keys at
+//! `m/1852'/1815'/account'/role/index`.
+//!
+//! Enabled with the `cardano` feature, which is on by default.
+//!
+//! [BIP32-Ed25519]: https://input-output-hk.github.io/adrestia/static/Ed25519_BIP.pdf
+
+use std::num::NonZeroU32;
+
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use ring::pbkdf2;
+
+use crate::bech32::{self, Variant};
+use crate::bip32::Bip32Error;
+use crate::bip39::Mnemonic;
+use crate::crypto;
+use crate::{ChainCode, ChildNumber, CoinType, DerivationPath, Error, Network, Purpose};
+
+const ICARUS_ITERATIONS: u32 = 4096;
+
+/// The chain of a key under an account.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Role {
+    /// Receive addresses.
+    External,
+    /// Change addresses.
+    Internal,
+    /// The staking key, of which an account normally uses index 0.
+    Stake,
+}
+
+impl Role {
+    fn to_child_number(self) -> ChildNumber {
+        ChildNumber::Normal(match self {
+            Role::External => 0,
+            Role::Internal => 1,
+            Role::Stake => 2,
+        })
+    }
+}
+
+/// The path of the key at `index` of `role` in `account`,
+/// `m/1852'/1815'/account'/role/index`.
+///
+/// Fails with `Bip32Error::InvalidChildNumber` if `account` or `index` is
+/// 2^31 or more.
+pub fn path(account: u32, role: Role, index: u32) -> Result<DerivationPath, Bip32Error> {
+    Ok(
+        DerivationPath::account(Purpose::Cip1852, CoinType::Cardano, account)?
+            .child(role.to_child_number())
+            .child(ChildNumber::normal(index)?),
+    )
+}
+
+/// A BIP32-Ed25519 extended private key: the 64-byte extended scalar and a
+/// chain code.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::cardano::ExtendedKey;
+/// use keymaker::{Mnemonic, Network};
+///
+/// let mnemonic: Mnemonic = "test walk nut penalty hip pave soap entry language right filter choice"
+///     .parse()
+///     .unwrap();
+/// let root = ExtendedKey::from_mnemonic(&mnemonic, "");
+///
+/// let address = root.base_address(0, 0, Network::Mainnet).unwrap();
+/// assert!(address.starts_with("addr1"));
+/// ```
+#[derive(Clone)]
+pub struct ExtendedKey {
+    secret: [u8; 64],
+    chain_code: ChainCode,
+}
+
+impl ExtendedKey {
+    /// Create the root key from the entropy of `mnemonic`, as the Icarus
+    /// scheme used by Daedalus, Yoroi and most Shelley wallets does.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Self {
+        let mut data = [0u8; 96];
+        if let Some(iterations) = NonZeroU32::new(ICARUS_ITERATIONS) {
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA512,
+                iterations,
+                mnemonic.entropy(),
+                passphrase.as_bytes(),
+                &mut data,
+            );
+        }
+        // Clamp the scalar as ed25519 does, and clear the third highest bit
+        // so that derived scalars cannot overflow.
+        data[0] &= 0b1111_1000;
+        data[31] &= 0b0001_1111;
+        data[31] |= 0b0100_0000;
+
+        let mut key = ExtendedKey {
+            secret: [0u8; 64],
+            chain_code: ChainCode::default(),
+        };
+        key.secret.copy_from_slice(&data[..64]);
+        key.chain_code.copy_from_slice(&data[64..]);
+        key
+    }
+
+    /// Derive the child key at `child`, which may be hardened or not.
+    pub fn derive_child(&self, child: ChildNumber) -> Self {
+        let index = child.to_index().to_le_bytes();
+        let hmac = |parts: &[&[u8]]| crypto::hmac_sha512(&self.chain_code, &parts.concat());
+        let (z, c) = if child.is_hardened() {
+            (
+                hmac(&[&[0x00], &self.secret[..], &index]),
+                hmac(&[&[0x01], &self.secret[..], &index]),
+            )
+        } else {
+            let public = self.public_key();
+            (
+                hmac(&[&[0x02], &public[..], &index]),
+                hmac(&[&[0x03], &public[..], &index]),
+            )
+        };
+
+        let mut secret = [0u8; 64];
+        // kL + 8 * zL, where zL is the first 28 bytes of z.
+        let mut carry = 0u16;
+        for i in 0..32 {
+            let low = if i < 28 { z[i] << 3 } else { 0 };
+            let high = if (1..=28).contains(&i) {
+                z[i - 1] >> 5
+            } else {
+                0
+            };
+            let sum = u16::from(self.secret[i]) + u16::from(low | high) + carry;
+            secret[i] = sum as u8;
+            carry = sum >> 8;
+        }
+        // kR + zR, modulo 2^256.
+        let mut carry = 0u16;
+        for i in 32..64 {
+            let sum = u16::from(self.secret[i]) + u16::from(z[i]) + carry;
+            secret[i] = sum as u8;
+            carry = sum >> 8;
+        }
+
+        let mut chain_code = ChainCode::default();
+        chain_code.copy_from_slice(&c[32..]);
+        ExtendedKey { secret, chain_code }
+    }
+
+    /// Derive the key at `path`, relative to this key.
+    pub fn derive_path(&self, path: &DerivationPath) -> Self {
+        path.iter()
+            .fold(self.clone(), |key, child| key.derive_child(*child))
+    }
+
+    /// The 64-byte extended scalar.
+    pub fn secret(&self) -> [u8; 64] {
+        self.secret
+    }
+
+    pub fn chain_code(&self) -> ChainCode {
+        self.chain_code
+    }
+
+    /// The 32-byte ed25519 public key of the scalar.
+    pub fn public_key(&self) -> [u8; 32] {
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&self.secret[..32]);
+        EdwardsPoint::mul_base(&Scalar::from_bytes_mod_order(scalar))
+            .compress()
+            .to_bytes()
+    }
+
+    /// The base address of key `index` in `account`, which pays to its
+    /// external key and delegates with the account's stake key.
+    ///
+    /// Fails with `Error::InvalidAddress` if `account` or `index` is 2^31 or
+    /// more.
+    pub fn base_address(
+        &self,
+        account: u32,
+        index: u32,
+        network: Network,
+    ) -> Result<String, Error> {
+        let payment_path =
+            path(account, Role::External, index).map_err(|_| Error::InvalidAddress)?;
+        let stake_path = path(account, Role::Stake, 0).map_err(|_| Error::InvalidAddress)?;
+        let payment = self.derive_path(&payment_path);
+        let stake = self.derive_path(&stake_path);
+        base_address(&payment.public_key(), &stake.public_key(), network)
+    }
+}
+
+/// Encode the Shelley base address of a `payment` and a `stake` public key,
+/// as `addr1...` on mainnet or `addr_test1...` on a test network.
+pub fn base_address(
+    payment: &[u8; 32],
+    stake: &[u8; 32],
+    network: Network,
+) -> Result<String, Error> {
+    // The header holds the address type, 0 for key/key, and the network id.
+    let (header, hrp) = match network {
+        Network::Mainnet => (0x01, "addr"),
+        Network::Testnet => (0x00, "addr_test"),
+        _ => return Err(Error::InvalidNetwork),
+    };
+    let mut bytes = vec![header];
+    bytes.extend_from_slice(&crypto::blake2b_224(payment));
+    bytes.extend_from_slice(&crypto::blake2b_224(stake));
+
+    let data = bech32::convert_bits(&bytes, 8, 5, true).map_err(|_| Error::InvalidAddress)?;
+    bech32::encode_unbounded(hrp, &data, Variant::Bech32).map_err(|_| Error::InvalidAddress)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn base_addresses() -> Result<(), Error> {
+        // The payment key is the one of the CIP-19 test vectors, and the
+        // stake key that of reward address
+        // stake1uyevw2xnsc0pvn9t9r9c7qryfqfeerchgrlm3ea2nefr9hqxdekzz.
+        let mnemonic: Mnemonic =
+            "test walk nut penalty hip pave soap entry language right filter choice"
+                .parse()
+                .unwrap();
+        let root = ExtendedKey::from_mnemonic(&mnemonic, "");
+        assert_eq!(
+            path(0, Role::Stake, 0).unwrap().to_string(),
+            "m/1852'/1815'/0'/2/0"
+        );
+
+        assert_eq!(
+            root.base_address(0, 0, Network::Mainnet)?,
+            "addr1qx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3jcu5d8ps7zex2k2xt3uqxgjqnnj83ws8lhrn648jjxtwqfjkjv7"
+        );
+        assert_eq!(
+            root.base_address(0, 0, Network::Testnet)?,
+            "addr_test1qz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3jcu5d8ps7zex2k2xt3uqxgjqnnj83ws8lhrn648jjxtwq2ytjqp"
+        );
+        assert!(root.base_address(0, 0, Network::Litecoin).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+//! Bitcoin Cash [cashaddr] encoding of P2PKH and P2SH addresses, such as
+//! `bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a`.
+//!
+//! Legacy Bitcoin Cash addresses share Bitcoin's Base58Check version bytes,
+//! so they are represented by an [Address](crate::Address) and converted to
+//! and from cashaddr here.
+//!
+//! Enabled with the `bch` feature.
+//!
+//! [cashaddr]: https://github.com/bitcoincashorg/bitcoincash.org/blob/master/spec/cashaddr.md
+
+use crate::bech32;
+use crate::{Address, AddressType, Error, Network};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_LEN: usize = 8;
+const GENERATOR: [u64; 5] = [
+    0x98_f2bc_8e61,
+    0x79_b76d_99e2,
+    0xf3_3e5f_b3c4,
+    0xae_2eab_e2a8,
+    0x1e_4f43_e470,
+];
+/// Version byte type bits; the size bits are 0 for 160-bit hashes.
+const P2PKH_VERSION: u8 = 0x00;
+const P2SH_VERSION: u8 = 0x08;
+
+/// Encode a legacy P2PKH or P2SH `address` in cashaddr form, with the prefix
+/// of its network.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::cashaddr;
+///
+/// let legacy = "1BpEi6DfDAUFd7GtittLSdBeYJvcoaVggu".parse().unwrap();
+/// let cash = cashaddr::to_cashaddr(&legacy).unwrap();
+/// assert_eq!(cash, "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a");
+/// assert_eq!(cashaddr::from_cashaddr(&cash).unwrap(), legacy);
+/// ```
+pub fn to_cashaddr(address: &Address) -> Result<String, Error> {
+    let version = match address.address_type() {
+        AddressType::P2pkh => P2PKH_VERSION,
+        AddressType::P2sh => P2SH_VERSION,
+        _ => return Err(Error::InvalidAddress),
+    };
+    let prefix = prefix(address.network()).ok_or(Error::InvalidNetwork)?;
+
+    let mut payload = vec![version];
+    payload.extend_from_slice(address.hash());
+    let mut data = bech32::convert_bits(&payload, 8, 5, true).map_err(|_| Error::InvalidAddress)?;
+    let checksum = polymod(&[expand_prefix(prefix), data.clone(), vec![0; CHECKSUM_LEN]].concat());
+    data.extend((0..CHECKSUM_LEN).map(|i| ((checksum >> (5 * (7 - i))) & 31) as u8));
+
+    let encoded: String = data.iter().map(|d| CHARSET[*d as usize] as char).collect();
+    Ok(format!("{}:{}", prefix, encoded))
+}
+
+/// Decode a cashaddr string into its legacy [Address](crate::Address).
+///
+/// The prefix may be omitted, in which case the checksum decides the
+/// network.
+pub fn from_cashaddr(s: &str) -> Result<Address, Error> {
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(Error::InvalidAddress);
+    }
+    let s = s.to_lowercase();
+    let (given, payload) = match s.split_once(':') {
+        Some((prefix, payload)) => (Some(prefix), payload),
+        None => (None, s.as_str()),
+    };
+
+    let data = payload
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|b| *b as char == c)
+                .map(|i| i as u8)
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(Error::InvalidAddress)?;
+    if data.len() <= CHECKSUM_LEN {
+        return Err(Error::InvalidAddress);
+    }
+
+    let network = [Network::Mainnet, Network::Testnet, Network::Regtest]
+        .iter()
+        .copied()
+        .filter_map(|network| prefix(network).map(|p| (network, p)))
+        .filter(|(_, p)| given.is_none_or(|given| given == *p))
+        .find(|(_, p)| polymod(&[expand_prefix(p), data.clone()].concat()) == 0)
+        .map(|(network, _)| network)
+        .ok_or(Error::InvalidChecksum)?;
+
+    let payload = bech32::convert_bits(&data[..data.len() - CHECKSUM_LEN], 5, 8, false)
+        .map_err(|_| Error::InvalidAddress)?;
+    if payload.len() != 21 {
+        return Err(Error::InvalidAddress);
+    }
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&payload[1..]);
+
+    match payload[0] {
+        P2PKH_VERSION => Ok(Address::from_parts(network, AddressType::P2pkh, &hash)),
+        P2SH_VERSION => Ok(Address::p2sh(hash, network)),
+        _ => Err(Error::InvalidAddress),
+    }
+}
+
+fn prefix(network: Network) -> Option<&'static str> {
+    match network {
+        Network::Mainnet => Some("bitcoincash"),
+        Network::Testnet => Some("bchtest"),
+        Network::Regtest => Some("bchreg"),
+        _ => None,
+    }
+}
+
+fn expand_prefix(prefix: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = prefix.bytes().map(|b| b & 31).collect();
+    expanded.push(0);
+    expanded
+}
+
+fn polymod(values: &[u8]) -> u64 {
+    let checksum = values.iter().fold(1u64, |c, d| {
+        let top = c >> 35;
+        let c = ((c & 0x07_ffff_ffff) << 5) ^ u64::from(*d);
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(c, |c, (_, g)| c ^ g)
+    });
+    checksum ^ 1
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn spec_vectors() -> Result<(), Error> {
+        let cases = [
+            (
+                "1BpEi6DfDAUFd7GtittLSdBeYJvcoaVggu",
+                "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a",
+            ),
+            (
+                "1KXrWXciRDZUpQwQmuM1DbwsKDLYAYsVLR",
+                "bitcoincash:qr95sy3j9xwd2ap32xkykttr4cvcu7as4y0qverfuy",
+            ),
+            (
+                "3CWFddi6m4ndiGyKqzYvsFYagqDLPVMTzC",
+                "bitcoincash:ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq",
+            ),
+            (
+                "3LDsS579y7sruadqu11beEJoTjdFiFCdX4",
+                "bitcoincash:pr95sy3j9xwd2ap32xkykttr4cvcu7as4yc93ky28e",
+            ),
+        ];
+        for (legacy, cash) in cases.iter() {
+            let address: Address = legacy.parse()?;
+            assert_eq!(to_cashaddr(&address)?, *cash);
+            assert_eq!(from_cashaddr(cash)?, address);
+            assert_eq!(from_cashaddr(&cash.to_uppercase())?, address);
+            // Without the prefix.
+            assert_eq!(from_cashaddr(&cash[12..])?, address);
+        }
+
+        assert!(matches!(
+            from_cashaddr("bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6q"),
+            Err(Error::InvalidChecksum)
+        ));
+        assert!(matches!(
+            from_cashaddr("bchtest:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a"),
+            Err(Error::InvalidChecksum)
+        ));
+        let bech32: Address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".parse()?;
+        assert!(to_cashaddr(&bech32).is_err());
+        Ok(())
+    }
+}
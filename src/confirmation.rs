@@ -0,0 +1,295 @@
+//! Confirmation payloads for dangerous operations, which the host application
+//! shows to the user and acknowledges before keymaker goes ahead.
+//!
+//! A [Confirmation](Confirmation) carries a stable message id and named
+//! arguments, so hosts can look up a translated message, such as a Fluent
+//! message with the same id, and fall back to the English `Display` text.
+//! Acknowledging it yields an [Acknowledgement](Acknowledgement) token bound
+//! to that exact payload, which the gated operation consumes.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::crypto;
+use crate::path::HARDENED_OFFSET;
+use crate::{ChildNumber, DerivationPath, ExtendedPrivKey, Purpose};
+
+/// Error originating from [confirmation](crate::confirmation) module.
+#[derive(Error, Debug)]
+pub enum ConfirmationError {
+    #[error("{0} requires an acknowledged confirmation")]
+    Unacknowledged(&'static str),
+    #[error("The acknowledgement was given for another {0} confirmation")]
+    Mismatch(&'static str),
+}
+
+/// A dangerous operation that needs the user's confirmation.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Revealing an extended private key, which spends from every key below it.
+    ExportXprv,
+    /// Signing a spend larger than the host's threshold.
+    SignAboveThreshold,
+    /// Deriving a path that no standard wallet scans, so funds sent to its
+    /// addresses are easily lost.
+    NonStandardPath,
+}
+
+impl Operation {
+    /// The stable message id of the operation.
+    pub fn id(self) -> &'static str {
+        match self {
+            Operation::ExportXprv => "export-xprv",
+            Operation::SignAboveThreshold => "sign-above-threshold",
+            Operation::NonStandardPath => "non-standard-path",
+        }
+    }
+}
+
+/// A confirmation the user must see before a dangerous operation.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::confirmation::Confirmation;
+///
+/// let confirmation = Confirmation::sign_above_threshold(250_000, 100_000).unwrap();
+/// assert_eq!(confirmation.id(), "sign-above-threshold");
+/// assert_eq!(confirmation.args()[0], ("amount", "250000".to_string()));
+/// assert_eq!(
+///     confirmation.to_string(),
+///     "Sign a spend of 250000 sat, above the 100000 sat threshold"
+/// );
+///
+/// // After the user agrees:
+/// let ack = confirmation.acknowledge();
+/// confirmation.require(Some(ack)).unwrap();
+///
+/// assert!(Confirmation::sign_above_threshold(50_000, 100_000).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Confirmation {
+    operation: Operation,
+    args: Vec<(&'static str, String)>,
+}
+
+impl Confirmation {
+    /// Confirm revealing `keys` as an `xprv`.
+    pub fn export_xprv(keys: &ExtendedPrivKey) -> Self {
+        let fingerprint = keys
+            .fingerprint()
+            .map(hex::encode)
+            .unwrap_or_else(|_| "unknown".to_string());
+        Confirmation {
+            operation: Operation::ExportXprv,
+            args: vec![
+                ("fingerprint", fingerprint),
+                ("depth", keys.depth().to_string()),
+                ("network", format!("{:?}", keys.privkey().network)),
+            ],
+        }
+    }
+
+    /// Confirm signing a spend of `amount` satoshis, or `None` if it does not
+    /// exceed `threshold`.
+    pub fn sign_above_threshold(amount: u64, threshold: u64) -> Option<Self> {
+        if amount <= threshold {
+            return None;
+        }
+        Some(Confirmation {
+            operation: Operation::SignAboveThreshold,
+            args: vec![
+                ("amount", amount.to_string()),
+                ("threshold", threshold.to_string()),
+            ],
+        })
+    }
+
+    /// Confirm deriving `path`, or `None` if it is a standard path.
+    ///
+    /// A path is standard if it is `m` or a prefix of
+    /// `m/purpose'/coin'/account'/change/index` for a [Purpose](Purpose),
+    /// with change 0 or 1, or also 2 for the Cardano staking role.
+    pub fn non_standard_path(path: &DerivationPath) -> Option<Self> {
+        if is_standard(path) {
+            return None;
+        }
+        Some(Confirmation {
+            operation: Operation::NonStandardPath,
+            args: vec![("path", path.to_string())],
+        })
+    }
+
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// The stable message id to localize the confirmation with.
+    pub fn id(&self) -> &'static str {
+        self.operation.id()
+    }
+
+    /// The named arguments of the message, in a fixed order.
+    pub fn args(&self) -> &[(&'static str, String)] {
+        &self.args
+    }
+
+    /// Record that the user accepted this confirmation.
+    pub fn acknowledge(&self) -> Acknowledgement {
+        Acknowledgement {
+            operation: self.operation,
+            digest: self.digest(),
+        }
+    }
+
+    /// Check that `ack` acknowledges this exact confirmation, consuming it.
+    pub fn require(&self, ack: Option<Acknowledgement>) -> Result<(), ConfirmationError> {
+        let ack = ack.ok_or(ConfirmationError::Unacknowledged(self.id()))?;
+        if ack.operation != self.operation || ack.digest != self.digest() {
+            return Err(ConfirmationError::Mismatch(self.id()));
+        }
+        Ok(())
+    }
+
+    fn digest(&self) -> [u8; 32] {
+        let mut data = self.id().as_bytes().to_vec();
+        for (name, value) in &self.args {
+            for part in [name.as_bytes(), value.as_bytes()] {
+                data.extend_from_slice(&(part.len() as u32).to_be_bytes());
+                data.extend_from_slice(part);
+            }
+        }
+        crypto::sha256(&data)
+    }
+}
+
+impl fmt::Display for Confirmation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arg = |name| {
+            self.args
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map_or("", |(_, value)| value.as_str())
+        };
+        match self.operation {
+            Operation::ExportXprv => write!(
+                f,
+                "Reveal the extended private key {} at depth {} on {}",
+                arg("fingerprint"),
+                arg("depth"),
+                arg("network")
+            ),
+            Operation::SignAboveThreshold => write!(
+                f,
+                "Sign a spend of {} sat, above the {} sat threshold",
+                arg("amount"),
+                arg("threshold")
+            ),
+            Operation::NonStandardPath => {
+                write!(f, "Derive keys at the non-standard path {}", arg("path"))
+            }
+        }
+    }
+}
+
+/// Proof that the user accepted a [Confirmation](Confirmation).
+///
+/// It can only be made by [Confirmation::acknowledge], is neither `Clone` nor
+/// `Copy`, and is used up by the operation it unlocks.
+#[derive(Debug)]
+pub struct Acknowledgement {
+    operation: Operation,
+    digest: [u8; 32],
+}
+
+impl Acknowledgement {
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+}
+
+fn is_standard(path: &DerivationPath) -> bool {
+    let purposes = [
+        Purpose::Bip44,
+        Purpose::Bip49,
+        Purpose::Bip84,
+        Purpose::Cip1852,
+    ];
+    let mut children = path.iter();
+    let purpose = match children.next() {
+        None => return true,
+        Some(child) => match purposes.iter().find(|p| p.to_child_number() == *child) {
+            Some(purpose) => *purpose,
+            None => return false,
+        },
+    };
+    let max_change = if purpose == Purpose::Cip1852 { 2 } else { 1 };
+    children
+        .enumerate()
+        .all(|(level, child)| match (level, child) {
+            (0, ChildNumber::Hardened(_)) | (1, ChildNumber::Hardened(_)) => true,
+            (2, ChildNumber::Normal(change)) => *change <= max_change,
+            (3, ChildNumber::Normal(index)) => *index < HARDENED_OFFSET,
+            _ => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Network;
+    use anyhow::Result;
+
+    #[test]
+    fn standard_paths() -> Result<()> {
+        for path in [
+            "m",
+            "m/44'",
+            "m/84'/0'/0'",
+            "m/49'/1'/2'/1/7",
+            "m/1852'/1815'/0'/2/0",
+        ] {
+            assert!(Confirmation::non_standard_path(&path.parse()?).is_none());
+        }
+        for path in [
+            "m/0",
+            "m/0'/0'",
+            "m/84'/0",
+            "m/84'/0'/0'/2/0",
+            "m/44'/0'/0'/0/0/0",
+        ] {
+            let confirmation = Confirmation::non_standard_path(&path.parse()?).unwrap();
+            assert_eq!(confirmation.id(), "non-standard-path");
+            assert_eq!(confirmation.args(), &[("path", path.to_string())]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn acknowledgement_is_bound_to_the_payload() -> Result<()> {
+        let a = ExtendedPrivKey::new([1; 64], None, Network::Mainnet, true)?;
+        let b = ExtendedPrivKey::new([2; 64], None, Network::Mainnet, true)?;
+
+        let confirmation = Confirmation::export_xprv(&a);
+        assert!(confirmation.to_string().starts_with("Reveal"));
+        assert!(matches!(
+            confirmation.require(None),
+            Err(ConfirmationError::Unacknowledged("export-xprv"))
+        ));
+        assert!(matches!(
+            confirmation.require(Some(Confirmation::export_xprv(&b).acknowledge())),
+            Err(ConfirmationError::Mismatch("export-xprv"))
+        ));
+        let spend = Confirmation::sign_above_threshold(2, 1).unwrap();
+        assert!(confirmation.require(Some(spend.acknowledge())).is_err());
+
+        assert_eq!(a.export(confirmation.acknowledge())?, a.to_string());
+        assert!(a
+            .export(Confirmation::export_xprv(&b).acknowledge())
+            .is_err());
+        Ok(())
+    }
+}
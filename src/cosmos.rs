@@ -0,0 +1,96 @@
+//! Cosmos SDK account addresses, such as `cosmos1...` or `osmo1...`.
+//!
+//! An address is the bech32 encoding of the `hash160` of the compressed
+//! secp256k1 public key. Every chain shares the format and the
+//! `m/44'/118'/0'/0/i` derivation path, and differs only in its
+//! human-readable part.
+
+use crate::bech32::{self, Variant};
+use crate::{AddressHash, Error, PublicKey};
+
+/// The human-readable part of Cosmos Hub addresses.
+pub const COSMOS_HRP: &str = "cosmos";
+
+/// Encode the account address of `public` under the human-readable part
+/// `hrp`, e.g. `"cosmos"`, `"osmo"` or `"juno"`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{cosmos, CoinType, DerivationPath, ExtendedPrivKey, Network, Purpose, SeedBuilder};
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let path = DerivationPath::address(Purpose::Bip44, CoinType::Cosmos, 0, false, 0).unwrap();
+/// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+///     .unwrap()
+///     .derive_path(&path)
+///     .unwrap();
+///
+/// let address = cosmos::to_address(&keys.pubkey(), "osmo").unwrap();
+/// assert!(address.starts_with("osmo1"));
+/// ```
+pub fn to_address(public: &PublicKey, hrp: &str) -> Result<String, Error> {
+    let data = bech32::convert_bits(&public.identifier()?, 8, 5, true)
+        .map_err(|_| Error::InvalidAddress)?;
+    bech32::encode(hrp, &data, Variant::Bech32).map_err(|_| Error::InvalidAddress)
+}
+
+/// Parse an account address, checking that its human-readable part is `hrp`.
+///
+/// Fails with `Error::InvalidNetwork` for an address of another chain.
+pub fn validate_address(s: &str, hrp: &str) -> Result<AddressHash, Error> {
+    let (given, data, variant) = bech32::decode(s).map_err(|_| Error::InvalidAddress)?;
+    if variant != Variant::Bech32 {
+        return Err(Error::InvalidChecksum);
+    }
+    if given != hrp.to_lowercase() {
+        return Err(Error::InvalidNetwork);
+    }
+
+    let bytes = bech32::convert_bits(&data, 5, 8, false).map_err(|_| Error::InvalidAddress)?;
+    if bytes.len() != 20 {
+        return Err(Error::InvalidAddress);
+    }
+    let mut hash = AddressHash::default();
+    hash.copy_from_slice(&bytes);
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{Network, PrivateKey};
+
+    #[test]
+    fn addresses_of_public_key() -> Result<(), Error> {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let private = PrivateKey {
+            network: Network::Mainnet,
+            secret,
+            compressed: false,
+        };
+        // The key is always hashed compressed.
+        let public = private.public_key()?;
+
+        for (hrp, expected) in &[
+            (COSMOS_HRP, "cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60c"),
+            ("osmo", "osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e2"),
+        ] {
+            let address = to_address(&public, hrp)?;
+            assert_eq!(address, *expected);
+            assert_eq!(validate_address(&address, hrp)?, public.identifier()?);
+        }
+
+        assert!(matches!(
+            validate_address("osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e2", COSMOS_HRP),
+            Err(Error::InvalidNetwork)
+        ));
+        assert!(matches!(
+            validate_address("cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60d", COSMOS_HRP),
+            Err(Error::InvalidAddress)
+        ));
+        Ok(())
+    }
+}
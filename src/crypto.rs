@@ -1,69 +1,664 @@
-use crate::{Hash256Bits, Hash32Bits};
-pub use crypto::digest::Digest;
-use crypto::ripemd160::Ripemd160;
-use crypto::sha2::Sha256;
-use std::hash::Hasher;
+//! The hash functions keys, addresses and signatures are built on.
+//!
+//! The Bitcoin hashes are always available. Keccak-256 needs the `eth`
+//! feature, BLAKE2b-256 and BLAKE2b-512 the `blake2b` feature, BLAKE2b-224
+//! either that or the `cardano` feature, and BLAKE3 the `blake3` feature.
+//!
+//! HKDF with SHA-256 or SHA-512 derives subkeys from a master secret, kept
+//! apart by their info strings.
+//!
+//! Secrets at rest are encrypted with XChaCha20-Poly1305 by [seal] and
+//! [open]. Formats that require AES, such as Ethereum keystores, can use the
+//! AES-256-GCM and AES-CTR functions of the `aes` feature instead.
+//!
+//! Comparisons of secrets and checksums go through the constant-time
+//! helpers of [ct].
+//!
+//! The streaming hashes implement [Digest], so code can be generic over the
+//! hash and feed large inputs piece by piece.
 
-pub struct DHash256 {
-    hasher: Sha256,
+use crate::{AddressHash, Hash256Bits, Hash32Bits};
+#[cfg(feature = "aes")]
+use aes_gcm::{Aes256Gcm, Nonce};
+#[cfg(feature = "blake2")]
+use blake2::digest::consts::U28;
+#[cfg(feature = "blake2")]
+use blake2::Blake2b;
+#[cfg(feature = "blake2b")]
+use blake2::{digest::consts::U32, Blake2b512};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use rand_core::{OsRng, RngCore};
+use ring::hmac;
+#[cfg(feature = "blake2")]
+use sha2::Digest as _;
+use thiserror::Error;
+
+pub mod ct;
+
+/// Error originating from [crypto](crate::crypto) module.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CryptoError {
+    #[error("Invalid HKDF output length {0}")]
+    InvalidOutputLength(usize),
+
+    #[error("Invalid HKDF pseudorandom key length {0}")]
+    InvalidPrkLength(usize),
+
+    #[error("Invalid hex")]
+    InvalidHex,
+
+    #[error("Wrong key or corrupted ciphertext")]
+    DecryptionFailed,
 }
 
-impl Default for DHash256 {
-    fn default() -> Self {
-        DHash256 {
-            hasher: Sha256::new(),
-        }
+/// A hash function that can be fed its input in pieces.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::crypto::{self, Digest, Sha256};
+///
+/// fn hash_chunks<D: Digest>(chunks: &[&[u8]]) -> D::Output {
+///     let mut hasher = D::default();
+///     for chunk in chunks {
+///         hasher.update(chunk);
+///     }
+///     hasher.finalize()
+/// }
+///
+/// assert_eq!(hash_chunks::<Sha256>(&[b"a", b"bc"]), crypto::sha256(b"abc"));
+/// ```
+pub trait Digest: Default {
+    type Output: AsRef<[u8]>;
+
+    /// The length of the output in bytes.
+    const OUTPUT_SIZE: usize;
+
+    /// Feed `data` to the hash.
+    fn update(&mut self, data: &[u8]);
+
+    /// The hash of everything fed so far.
+    fn finalize(self) -> Self::Output;
+
+    /// Start over as if nothing had been fed.
+    fn reset(&mut self);
+
+    /// The hash of `data` alone.
+    fn digest(data: &[u8]) -> Self::Output {
+        let mut hasher = Self::default();
+        hasher.update(data);
+        hasher.finalize()
     }
 }
 
-impl DHash256 {
-    pub fn new() -> Self {
-        DHash256::default()
-    }
+macro_rules! impl_digest {
+    ($(#[$attr:meta])* $name:ident, $inner:ty, $size:expr) => {
+        $(#[$attr])*
+        #[derive(Default, Clone)]
+        pub struct $name {
+            hasher: $inner,
+        }
 
-    pub fn finish(mut self) -> Hash256Bits {
-        let mut result = Hash256Bits::default();
-        self.result(&mut result);
-        result
-    }
+        $(#[$attr])*
+        impl Digest for $name {
+            type Output = [u8; $size];
+            const OUTPUT_SIZE: usize = $size;
+
+            fn update(&mut self, data: &[u8]) {
+                sha2::Digest::update(&mut self.hasher, data)
+            }
+
+            fn finalize(self) -> Self::Output {
+                sha2::Digest::finalize(self.hasher).into()
+            }
+
+            fn reset(&mut self) {
+                sha2::Digest::reset(&mut self.hasher)
+            }
+        }
+    };
+}
+
+impl_digest!(
+    /// SHA-256.
+    Sha256,
+    sha2::Sha256,
+    32
+);
+impl_digest!(
+    /// SHA-512.
+    Sha512,
+    sha2::Sha512,
+    64
+);
+impl_digest!(
+    /// RIPEMD-160.
+    Ripemd160,
+    ripemd::Ripemd160,
+    20
+);
+impl_digest!(
+    /// The Keccak-256 hash used by Ethereum, which predates the final SHA3
+    /// padding.
+    #[cfg(feature = "eth")]
+    Keccak256,
+    sha3::Keccak256,
+    32
+);
+
+/// SHA-256 applied twice.
+#[derive(Default, Clone)]
+pub struct DHash256 {
+    hasher: Sha256,
 }
 
 impl Digest for DHash256 {
-    fn input(&mut self, d: &[u8]) {
-        self.hasher.input(d)
+    type Output = Hash256Bits;
+    const OUTPUT_SIZE: usize = 32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data)
     }
 
-    fn result(&mut self, out: &mut [u8]) {
-        self.hasher.result(out);
-        self.hasher.reset();
-        self.hasher.input(out);
-        self.hasher.result(out);
+    fn finalize(self) -> Hash256Bits {
+        Sha256::digest(&self.hasher.finalize())
     }
 
     fn reset(&mut self) {
-        self.hasher.reset();
+        self.hasher.reset()
     }
+}
+
+/// `sha256(sha256(input))`, as Bitcoin hashes transactions and checksums.
+#[inline]
+pub fn dhash256(input: &[u8]) -> Hash256Bits {
+    DHash256::digest(input)
+}
+
+/// A single SHA-256.
+pub fn sha256(input: &[u8]) -> Hash256Bits {
+    Sha256::digest(input)
+}
+
+/// A single SHA-512.
+pub fn sha512(input: &[u8]) -> [u8; 64] {
+    Sha512::digest(input)
+}
+
+/// `ripemd160(sha256(input))`, the hash behind P2PKH and P2SH addresses
+/// and key identifiers.
+pub fn hash160(input: &[u8]) -> AddressHash {
+    Ripemd160::digest(&Sha256::digest(input))
+}
+
+/// The 28-byte BLAKE2b hash Cardano uses for key and script hashes.
+#[cfg(feature = "blake2")]
+pub fn blake2b_224(input: &[u8]) -> [u8; 28] {
+    Blake2b::<U28>::digest(input).into()
+}
+
+/// The 32-byte BLAKE2b hash, as Zcash and Nano use it.
+#[cfg(feature = "blake2b")]
+pub fn blake2b_256(input: &[u8]) -> Hash256Bits {
+    Blake2b::<U32>::digest(input).into()
+}
+
+/// The full 64-byte BLAKE2b hash.
+#[cfg(feature = "blake2b")]
+pub fn blake2b_512(input: &[u8]) -> [u8; 64] {
+    Blake2b512::digest(input).into()
+}
+
+/// The 32-byte BLAKE3 hash.
+#[cfg(feature = "blake3")]
+pub fn blake3(input: &[u8]) -> Hash256Bits {
+    blake3::hash(input).into()
+}
+
+/// The [Keccak256] hash of `input`.
+#[cfg(feature = "eth")]
+pub fn keccak256(input: &[u8]) -> Hash256Bits {
+    Keccak256::digest(input)
+}
+
+/// The [BIP340] tagged hash `sha256(sha256(tag) || sha256(tag) || data)`.
+///
+/// [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+pub fn tagged_hash(tag: &str, data: &[u8]) -> Hash256Bits {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::default();
+    hasher.update(&tag_hash);
+    hasher.update(&tag_hash);
+    hasher.update(data);
+    hasher.finalize()
+}
 
-    fn output_bits(&self) -> usize {
-        256
+/// HMAC-SHA512 of `data` under `key`, the function BIP32, SLIP-0010 and
+/// Electrum seeds derive keys with.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::crypto;
+///
+/// let tag = crypto::hmac_sha512(b"Bitcoin seed", &[0u8; 16]);
+/// assert_eq!(tag.len(), 64);
+/// ```
+pub fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let key = hmac::Key::new(hmac::HMAC_SHA512, key);
+    let mut tag = [0u8; 64];
+    tag.copy_from_slice(hmac::sign(&key, data).as_ref());
+    tag
+}
+
+/// HKDF-SHA256 of `ikm` into `okm`, salted with `salt` and bound to `info`.
+///
+/// Fails if `okm` is longer than 255 hashes.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::crypto;
+///
+/// let mut encryption_key = [0u8; 32];
+/// crypto::hkdf_sha256(b"salt", &[7u8; 32], b"app/encryption", &mut encryption_key).unwrap();
+/// let mut signing_key = [0u8; 32];
+/// crypto::hkdf_sha256(b"salt", &[7u8; 32], b"app/signing", &mut signing_key).unwrap();
+/// assert_ne!(encryption_key, signing_key);
+/// ```
+pub fn hkdf_sha256(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) -> Result<(), CryptoError> {
+    hkdf_expand_sha256(&hkdf_extract_sha256(salt, ikm), info, okm)
+}
+
+/// The HKDF-SHA256 extract step, the pseudorandom key of `ikm`.
+pub fn hkdf_extract_sha256(salt: &[u8], ikm: &[u8]) -> Hash256Bits {
+    Hkdf::<sha2::Sha256>::extract(Some(salt), ikm).0.into()
+}
+
+/// The HKDF-SHA256 expand step, filling `okm` from the pseudorandom key
+/// `prk`.
+pub fn hkdf_expand_sha256(prk: &[u8], info: &[u8], okm: &mut [u8]) -> Result<(), CryptoError> {
+    Hkdf::<sha2::Sha256>::from_prk(prk)
+        .map_err(|_| CryptoError::InvalidPrkLength(prk.len()))?
+        .expand(info, okm)
+        .map_err(|_| CryptoError::InvalidOutputLength(okm.len()))
+}
+
+/// HKDF-SHA512 of `ikm` into `okm`, as [hkdf_sha256].
+pub fn hkdf_sha512(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) -> Result<(), CryptoError> {
+    hkdf_expand_sha512(&hkdf_extract_sha512(salt, ikm), info, okm)
+}
+
+/// The HKDF-SHA512 extract step, the pseudorandom key of `ikm`.
+pub fn hkdf_extract_sha512(salt: &[u8], ikm: &[u8]) -> [u8; 64] {
+    Hkdf::<sha2::Sha512>::extract(Some(salt), ikm).0.into()
+}
+
+/// The HKDF-SHA512 expand step, filling `okm` from the pseudorandom key
+/// `prk`.
+pub fn hkdf_expand_sha512(prk: &[u8], info: &[u8], okm: &mut [u8]) -> Result<(), CryptoError> {
+    Hkdf::<sha2::Sha512>::from_prk(prk)
+        .map_err(|_| CryptoError::InvalidPrkLength(prk.len()))?
+        .expand(info, okm)
+        .map_err(|_| CryptoError::InvalidOutputLength(okm.len()))
+}
+
+/// The length of the random nonce [seal] puts in front of its output.
+pub const SEAL_NONCE_LEN: usize = 24;
+
+/// The length of the authentication tag [seal] puts after the ciphertext.
+pub const SEAL_TAG_LEN: usize = 16;
+
+/// Encrypt and authenticate `plaintext` under `key` with XChaCha20-Poly1305,
+/// also authenticating `aad`.
+///
+/// The output is a fresh random nonce, the ciphertext and the tag. Nonces
+/// of 24 bytes are long enough to be picked at random for every message
+/// under the same key.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::crypto;
+///
+/// let key = [7u8; 32];
+/// let sealed = crypto::seal(&key, b"secret", b"header");
+/// assert_eq!(crypto::open(&key, &sealed, b"header").unwrap(), b"secret");
+/// assert!(crypto::open(&key, &sealed, b"other header").is_err());
+/// ```
+pub fn seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; SEAL_NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = XChaCha20Poly1305::new(key.into())
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("plaintext within the XChaCha20 length limit");
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    sealed
+}
+
+/// Decrypt the output of [seal], failing with
+/// `CryptoError::DecryptionFailed` if `key` or `aad` are wrong or `sealed`
+/// was tampered with.
+pub fn open(key: &[u8; 32], sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if sealed.len() < SEAL_NONCE_LEN + SEAL_TAG_LEN {
+        return Err(CryptoError::DecryptionFailed);
     }
+    let (nonce, ciphertext) = sealed.split_at(SEAL_NONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| CryptoError::DecryptionFailed)
+}
+
+/// The length of the random nonce [seal_aes256_gcm] puts in front of its
+/// output.
+#[cfg(feature = "aes")]
+pub const AES_GCM_NONCE_LEN: usize = 12;
 
-    fn block_size(&self) -> usize {
-        64
+/// Encrypt and authenticate `plaintext` under `key` with AES-256-GCM, laid
+/// out as [seal] does but with a 12-byte nonce.
+///
+/// Random 12-byte nonces are only safe for up to about 2^32 messages under
+/// the same key, so prefer [seal] unless a format requires AES.
+#[cfg(feature = "aes")]
+pub fn seal_aes256_gcm(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; AES_GCM_NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(key.into())
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("plaintext within the AES-GCM length limit");
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    sealed
+}
+
+/// Decrypt the output of [seal_aes256_gcm], failing with
+/// `CryptoError::DecryptionFailed` as [open] does.
+#[cfg(feature = "aes")]
+pub fn open_aes256_gcm(key: &[u8; 32], sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if sealed.len() < AES_GCM_NONCE_LEN + SEAL_TAG_LEN {
+        return Err(CryptoError::DecryptionFailed);
     }
+    let (nonce, ciphertext) = sealed.split_at(AES_GCM_NONCE_LEN);
+    Aes256Gcm::new(key.into())
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| CryptoError::DecryptionFailed)
 }
 
-#[inline]
-pub fn dhash256(input: &[u8]) -> Hash256Bits {
-    let mut result = Hash256Bits::default();
-    let mut hasher = DHash256::new();
-    hasher.input(input);
-    hasher.result(&mut result);
-    result
+/// Encrypt or decrypt `data` in place with AES-128 in CTR mode, starting
+/// from the big-endian counter `iv`, as Ethereum keystores do.
+///
+/// CTR mode does not authenticate `data`, so the format using it must.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::crypto;
+///
+/// let mut data = *b"secret";
+/// crypto::aes128_ctr(&[1u8; 16], &[2u8; 16], &mut data);
+/// assert_ne!(&data, b"secret");
+/// crypto::aes128_ctr(&[1u8; 16], &[2u8; 16], &mut data);
+/// assert_eq!(&data, b"secret");
+/// ```
+#[cfg(feature = "aes")]
+pub fn aes128_ctr(key: &[u8; 16], iv: &[u8; 16], data: &mut [u8]) {
+    use ctr::cipher::{KeyIvInit, StreamCipher};
+    ctr::Ctr128BE::<aes::Aes128>::new(key.into(), iv.into()).apply_keystream(data);
+}
+
+/// Encrypt or decrypt `data` in place with AES-256 in CTR mode, as
+/// [aes128_ctr] does.
+#[cfg(feature = "aes")]
+pub fn aes256_ctr(key: &[u8; 32], iv: &[u8; 16], data: &mut [u8]) {
+    use ctr::cipher::{KeyIvInit, StreamCipher};
+    ctr::Ctr128BE::<aes::Aes256>::new(key.into(), iv.into()).apply_keystream(data);
 }
 
+/// The first 4 bytes of [dhash256], as Base58Check appends them.
 pub fn checksum(data: &[u8]) -> Hash32Bits {
     let mut result = Hash32Bits::default();
     result.copy_from_slice(&dhash256(data)[..4]);
     result
 }
+
+/// Whether `checksum` is the [checksum] of `data`, compared in constant
+/// time.
+pub fn verify_checksum(data: &[u8], checksum: &[u8]) -> bool {
+    ct::eq(&self::checksum(data), checksum)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn known_digests() {
+        assert_eq!(
+            hex::encode(dhash256(b"abc")),
+            "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"
+        );
+        let mut hasher = DHash256::default();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert_eq!(hasher.clone().finalize(), dhash256(b"abc"));
+        hasher.reset();
+        hasher.update(b"abc");
+        assert_eq!(hasher.finalize(), dhash256(b"abc"));
+        assert_eq!(DHash256::OUTPUT_SIZE, 32);
+        assert_eq!(Ripemd160::OUTPUT_SIZE, 20);
+        assert_eq!(checksum(b"abc"), [0x4f, 0x8b, 0x42, 0xc2]);
+        assert_eq!(
+            hex::encode(&sha512(b"abc")[..]),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+
+        assert_eq!(
+            hex::encode(hash160(b"abc")),
+            "bb1be98c142444d7a56aa3981c3942a978e4dc33"
+        );
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn blake2b_224_digest() {
+        assert_eq!(
+            hex::encode(blake2b_224(b"abc")),
+            "9bd237b02a29e43bdd6738afa5b53ff0eee178d6210b618e4511aec8"
+        );
+    }
+
+    #[test]
+    fn hmac_sha512_rfc4231() {
+        // Test case 2 of RFC 4231.
+        assert_eq!(
+            hex::encode(&hmac_sha512(b"Jefe", b"what do ya want for nothing?")[..]),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    fn hkdf_rfc5869() -> Result<(), CryptoError> {
+        // Test case 1 of RFC 5869, and the same inputs with SHA-512.
+        let ikm = [0x0b; 22];
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+
+        assert_eq!(
+            hex::encode(hkdf_extract_sha256(&salt, &ikm)),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+        );
+        let mut okm = [0u8; 42];
+        hkdf_sha256(&salt, &ikm, &info, &mut okm)?;
+        assert_eq!(
+            hex::encode(&okm[..]),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+
+        assert_eq!(
+            hex::encode(&hkdf_extract_sha512(&salt, &ikm)[..]),
+            "665799823737ded04a88e47e54a5890bb2c3d247c7a4254a8e61350723590a26c36238127d8661b88cf80ef802d57e2f7cebcf1e00e083848be19929c61b4237"
+        );
+        hkdf_sha512(&salt, &ikm, &info, &mut okm)?;
+        assert_eq!(
+            hex::encode(&okm[..]),
+            "832390086cda71fb47625bb5ceb168e4c8e26a1a16ed34d9fc7fe92c1481579338da362cb8d9f925d7cb"
+        );
+
+        let mut too_long = vec![0u8; 255 * 32 + 1];
+        assert_eq!(
+            hkdf_sha256(&salt, &ikm, &info, &mut too_long),
+            Err(CryptoError::InvalidOutputLength(255 * 32 + 1))
+        );
+        assert_eq!(
+            hkdf_expand_sha256(&[0u8; 16], &info, &mut okm),
+            Err(CryptoError::InvalidPrkLength(16))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn seal_and_open() {
+        let key = [7u8; 32];
+        let sealed = seal(&key, b"secret", b"aad");
+        assert_eq!(sealed.len(), SEAL_NONCE_LEN + 6 + SEAL_TAG_LEN);
+        assert_eq!(open(&key, &sealed, b"aad").unwrap(), b"secret");
+        // A fresh nonce every time.
+        assert_ne!(seal(&key, b"secret", b"aad"), sealed);
+
+        assert_eq!(
+            open(&[8u8; 32], &sealed, b"aad"),
+            Err(CryptoError::DecryptionFailed)
+        );
+        let mut tampered = sealed.clone();
+        tampered[SEAL_NONCE_LEN] ^= 1;
+        assert_eq!(
+            open(&key, &tampered, b"aad"),
+            Err(CryptoError::DecryptionFailed)
+        );
+        assert_eq!(
+            open(&key, &sealed[..SEAL_NONCE_LEN + SEAL_TAG_LEN - 1], b"aad"),
+            Err(CryptoError::DecryptionFailed)
+        );
+
+        let empty = seal(&key, b"", b"");
+        assert_eq!(open(&key, &empty, b"").unwrap(), b"");
+    }
+
+    #[cfg(feature = "aes")]
+    #[test]
+    fn aes_backends() {
+        use std::convert::TryInto;
+
+        let key = [7u8; 32];
+        let sealed = seal_aes256_gcm(&key, b"secret", b"aad");
+        assert_eq!(sealed.len(), AES_GCM_NONCE_LEN + 6 + SEAL_TAG_LEN);
+        assert_eq!(open_aes256_gcm(&key, &sealed, b"aad").unwrap(), b"secret");
+        assert_eq!(
+            open_aes256_gcm(&key, &sealed, b"other"),
+            Err(CryptoError::DecryptionFailed)
+        );
+        assert_eq!(
+            open_aes256_gcm(&key, &sealed[..27], b"aad"),
+            Err(CryptoError::DecryptionFailed)
+        );
+
+        // F.5.1 and F.5.5 of NIST SP 800-38A.
+        let iv: [u8; 16] = hex::decode("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let plaintext = hex::decode("6bc1bee22e409f96e93d7e117393172a").unwrap();
+
+        let key: [u8; 16] = hex::decode("2b7e151628aed2a6abf7158809cf4f3c")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let mut data = plaintext.clone();
+        aes128_ctr(&key, &iv, &mut data);
+        assert_eq!(hex::encode(&data), "874d6191b620e3261bef6864990db6ce");
+
+        let key: [u8; 32] =
+            hex::decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let mut data = plaintext;
+        aes256_ctr(&key, &iv, &mut data);
+        assert_eq!(hex::encode(&data), "601ec313775789a5b7a7f504bbf3d228");
+    }
+
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn blake2b_digests() {
+        assert_eq!(
+            hex::encode(blake2b_256(b"abc")),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+        assert_eq!(
+            hex::encode(&blake2b_512(b"abc")[..]),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn blake3_digest() {
+        assert_eq!(
+            hex::encode(blake3(b"abc")),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[cfg(feature = "eth")]
+    #[test]
+    fn keccak_digests() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        let mut hasher = Keccak256::default();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert_eq!(hasher.finalize(), keccak256(b"abc"));
+    }
+}
@@ -0,0 +1,111 @@
+//! Constant-time operations on secrets.
+//!
+//! These take the same time whatever the bytes they handle, so timing
+//! cannot leak a secret or how much of a guessed checksum was right. Only
+//! lengths, which are public, change the running time.
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use super::CryptoError;
+
+/// Whether `a` and `b` are equal, without stopping at the first
+/// difference.
+pub fn eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// `if_true` if `condition` holds, otherwise `if_false`, without
+/// branching on `condition`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::crypto::ct;
+///
+/// assert_eq!(ct::select(true, &[1u8; 4], &[2u8; 4]), [1u8; 4]);
+/// assert_eq!(ct::select(false, &[1u8; 4], &[2u8; 4]), [2u8; 4]);
+/// ```
+pub fn select<const N: usize>(condition: bool, if_true: &[u8; N], if_false: &[u8; N]) -> [u8; N] {
+    let choice = Choice::from(u8::from(condition));
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::conditional_select(&if_false[i], &if_true[i], choice);
+    }
+    out
+}
+
+/// Decode hex digits of either case, without table lookups or branches
+/// on their values.
+///
+/// Fails with `CryptoError::InvalidHex` for an odd number of digits or any
+/// other character, which is only found out once the whole input is read.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::crypto::ct;
+///
+/// assert_eq!(ct::decode_hex("00fF7a").unwrap(), vec![0x00, 0xff, 0x7a]);
+/// assert!(ct::decode_hex("0g").is_err());
+/// ```
+pub fn decode_hex(s: &str) -> Result<Vec<u8>, CryptoError> {
+    let digits = s.as_bytes();
+    if digits.len() % 2 != 0 {
+        return Err(CryptoError::InvalidHex);
+    }
+
+    let mut valid = 0xffu8;
+    let mut bytes = Vec::with_capacity(digits.len() / 2);
+    for pair in digits.chunks(2) {
+        let (high, high_valid) = nibble(pair[0]);
+        let (low, low_valid) = nibble(pair[1]);
+        valid &= high_valid & low_valid;
+        bytes.push(high << 4 | low);
+    }
+    if valid != 0xff {
+        return Err(CryptoError::InvalidHex);
+    }
+    Ok(bytes)
+}
+
+/// The value of a hex digit, and `0xff` if `c` is one or `0x00` otherwise.
+fn nibble(c: u8) -> (u8, u8) {
+    let c = i16::from(c);
+    // '0' to '9' xor 0x30 are exactly the values below 10.
+    let digit = c ^ 0x30;
+    let is_digit = ((digit - 10) >> 8) as u8;
+    // Clearing bit 5 maps 'a' to 'f' onto 'A' to 'F', then onto 10 to 15.
+    let letter = (c & !0x20) - 55;
+    let is_letter = (((letter - 10) ^ (letter - 16)) >> 8) as u8;
+    (
+        (is_digit & digit as u8) | (is_letter & letter as u8),
+        is_digit | is_letter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn hex_matches_every_byte() {
+        for c in 0..=255u8 {
+            let (value, valid) = nibble(c);
+            match (c as char).to_digit(16) {
+                Some(expected) => assert_eq!((value, valid), (expected as u8, 0xff)),
+                None => assert_eq!(valid, 0x00, "{}", c),
+            }
+        }
+        assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+        assert_eq!(decode_hex("abc"), Err(CryptoError::InvalidHex));
+        assert_eq!(decode_hex("é0"), Err(CryptoError::InvalidHex));
+    }
+
+    #[test]
+    fn equality() {
+        assert!(eq(b"secret", b"secret"));
+        assert!(!eq(b"secret", b"secreT"));
+        assert!(!eq(b"secret", b"secret!"));
+    }
+}
@@ -0,0 +1,127 @@
+//! Elliptic-curve Diffie-Hellman between a [PrivateKey](crate::PrivateKey)
+//! and a [PublicKey](crate::PublicKey).
+//!
+//! Both parties multiply their secret by the other's public key and arrive
+//! at the same point, which is then reduced to 32 bytes. Protocols differ on
+//! that last step, so it is chosen with [EcdhHash](EcdhHash).
+
+use secp256k1::key;
+
+use crate::crypto::{self, ct};
+use crate::{Error, PrivateKey, PublicKey, SECP256K1};
+
+/// How the shared point becomes the shared secret.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum EcdhHash {
+    /// SHA-256 of the compressed point, as libsecp256k1 does by default.
+    #[default]
+    Sha256,
+    /// The bare x coordinate of the point, as BIP47 and ECIES use before
+    /// their own key derivation.
+    XOnly,
+}
+
+/// A 32-byte secret shared by two key holders.
+#[derive(Debug, Clone)]
+pub struct SharedSecret([u8; 32]);
+
+/// Compared in constant time.
+impl PartialEq for SharedSecret {
+    fn eq(&self, other: &Self) -> bool {
+        ct::eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedSecret {}
+
+impl SharedSecret {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for SharedSecret {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl PrivateKey {
+    /// The secret shared with the holder of `public`, hashed with SHA-256.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::KeyPairBuilder;
+    ///
+    /// let alice = KeyPairBuilder::new().build().unwrap();
+    /// let bob = KeyPairBuilder::new().build().unwrap();
+    ///
+    /// assert_eq!(
+    ///     alice.privkey().diffie_hellman(bob.pubkey()).unwrap(),
+    ///     bob.privkey().diffie_hellman(alice.pubkey()).unwrap()
+    /// );
+    /// ```
+    pub fn diffie_hellman(&self, public: &PublicKey) -> Result<SharedSecret, Error> {
+        self.diffie_hellman_with(public, EcdhHash::default())
+    }
+
+    /// The secret shared with the holder of `public`, reduced with `hash`.
+    pub fn diffie_hellman_with(
+        &self,
+        public: &PublicKey,
+        hash: EcdhHash,
+    ) -> Result<SharedSecret, Error> {
+        let mut point = key::PublicKey::from_slice(&public.compressed()?)?;
+        point.mul_assign(&SECP256K1, &self.secret)?;
+        let point = point.serialize();
+
+        let mut secret = [0u8; 32];
+        match hash {
+            EcdhHash::Sha256 => secret = crypto::sha256(&point),
+            EcdhHash::XOnly => secret.copy_from_slice(&point[1..]),
+        }
+        Ok(SharedSecret(secret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Network;
+
+    #[test]
+    fn shared_secrets() -> Result<(), Error> {
+        // With a secret of 1, the shared point is the other public key.
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let one = PrivateKey {
+            network: Network::Mainnet,
+            secret,
+            compressed: false,
+        };
+        let generator = one.public_key()?;
+        assert_eq!(
+            hex::encode(one.diffie_hellman(&generator)?),
+            "0f715baf5d4c2ed329785cef29e562f73488c8a2bb9dbc5700b361d54b9b0554"
+        );
+        assert_eq!(
+            hex::encode(one.diffie_hellman_with(&generator, EcdhHash::XOnly)?),
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+
+        secret[31] = 7;
+        let seven = PrivateKey {
+            secret,
+            ..one.clone()
+        };
+        for hash in &[EcdhHash::Sha256, EcdhHash::XOnly] {
+            assert_eq!(
+                one.diffie_hellman_with(&seven.public_key()?, *hash)?,
+                seven.diffie_hellman_with(&generator, *hash)?
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! ECDSA signing with nonces the safe `secp256k1` API does not expose,
+//! through the raw libsecp256k1 functions.
+//!
+//! The `unsafe` calls of the crate live here, each with the reasons it is
+//! sound, so that callers only deal in borrowed arrays and closures.
+
+use secp256k1::ffi::types::{c_int, c_uchar, c_uint, c_void};
+use secp256k1::ffi::{self, CPtr};
+use secp256k1::key;
+use secp256k1::Message as SecpMessage;
+use secp256k1::Signature as SecpSignature;
+use std::ptr;
+
+use crate::{Error, SECP256K1};
+
+/// Sign `message`, taking the nonce of every attempt from `nonce`, which is
+/// called with the message, the secret and the attempt number.
+///
+/// libsecp256k1 tries again with the next attempt while the returned nonce
+/// is not a valid secret key. Returning `None` gives up on signing.
+pub(crate) fn sign_with_nonce<F>(
+    message: &SecpMessage,
+    secret: &key::SecretKey,
+    mut nonce: F,
+) -> Result<SecpSignature, Error>
+where
+    F: FnMut(&[u8; 32], &[u8; 32], u32) -> Option<[u8; 32]>,
+{
+    // SAFETY: `Signature::new` zeroes the 64-byte struct, which is only an
+    // out parameter that `secp256k1_ecdsa_sign` fills in.
+    let mut signature = unsafe { ffi::Signature::new() };
+    // SAFETY: `SECP256K1` is a `Secp256k1<All>`, whose context has the
+    // SECP256K1_CONTEXT_SIGN flag signing requires, and lives for the whole
+    // program. `message` and `secret` point to 32 bytes each, borrowed for
+    // the call. `nonce` is a local `F` that outlives the call and is only
+    // reached through `data`, which `call_nonce::<F>` casts back to `F`:
+    // libsecp256k1 hands `data` to the nonce function untouched and never
+    // keeps it after returning.
+    let signed = unsafe {
+        ffi::secp256k1_ecdsa_sign(
+            *SECP256K1.ctx(),
+            &mut signature,
+            message.as_c_ptr(),
+            secret.as_c_ptr(),
+            Some(call_nonce::<F>),
+            &mut nonce as *mut F as *const c_void,
+        )
+    };
+    if signed != 1 {
+        return Err(Error::InvalidSecret);
+    }
+    Ok(SecpSignature::from(signature))
+}
+
+/// Sign `message` with the RFC6979 nonce of libsecp256k1, given `entropy`
+/// as its additional data.
+pub(crate) fn sign_with_entropy(
+    message: &SecpMessage,
+    secret: &key::SecretKey,
+    entropy: &[u8; 32],
+) -> Result<SecpSignature, Error> {
+    // SAFETY: as in [sign_with_nonce].
+    let mut signature = unsafe { ffi::Signature::new() };
+    // SAFETY: `SECP256K1` has the SECP256K1_CONTEXT_SIGN flag and lives for
+    // the whole program, and `message` and `secret` point to 32 bytes each,
+    // as in [sign_with_nonce]. The RFC6979 nonce function reads exactly 32
+    // bytes of additional data, which `entropy` holds and outlives the call,
+    // and never writes to it.
+    let signed = unsafe {
+        ffi::secp256k1_ecdsa_sign(
+            *SECP256K1.ctx(),
+            &mut signature,
+            message.as_c_ptr(),
+            secret.as_c_ptr(),
+            ffi::secp256k1_nonce_function_rfc6979,
+            entropy.as_c_ptr() as *const c_void,
+        )
+    };
+    if signed != 1 {
+        return Err(Error::InvalidSecret);
+    }
+    Ok(SecpSignature::from(signature))
+}
+
+/// The RFC6979 nonce of libsecp256k1 for `attempt`, with `extra` as its
+/// additional data.
+pub(crate) fn rfc6979(
+    message: &[u8; 32],
+    secret: &[u8; 32],
+    extra: &[u8; 32],
+    attempt: u32,
+) -> Option<[u8; 32]> {
+    // SAFETY: the static is a function pointer libsecp256k1 initializes at
+    // compile time and never changes.
+    let rfc6979 = unsafe { ffi::secp256k1_nonce_function_rfc6979 }?;
+    let mut nonce = [0u8; 32];
+    // SAFETY: the RFC6979 nonce function needs no context. It writes exactly
+    // 32 bytes to `nonce`, reads 32 bytes from each of `message`, `secret`
+    // and `extra`, all borrowed for the call, and reads but never writes
+    // the additional data despite its `*mut` type. A null `algo16` selects
+    // plain ECDSA.
+    let generated = unsafe {
+        rfc6979(
+            nonce.as_mut_ptr(),
+            message.as_ptr(),
+            secret.as_ptr(),
+            ptr::null(),
+            extra.as_ptr() as *mut c_void,
+            attempt,
+        )
+    };
+    if generated == 1 {
+        Some(nonce)
+    } else {
+        None
+    }
+}
+
+/// The nonce function [sign_with_nonce] passes to libsecp256k1, which calls
+/// back the closure behind `data`.
+///
+/// A panic in the closure aborts rather than unwind into C.
+unsafe extern "C" fn call_nonce<F>(
+    nonce32: *mut c_uchar,
+    msg32: *const c_uchar,
+    key32: *const c_uchar,
+    _algo16: *const c_uchar,
+    data: *mut c_void,
+    attempt: c_uint,
+) -> c_int
+where
+    F: FnMut(&[u8; 32], &[u8; 32], u32) -> Option<[u8; 32]>,
+{
+    // SAFETY: `secp256k1_ecdsa_sign` only calls this with the `data` that
+    // [sign_with_nonce] gave it, a live `*mut F` nothing else borrows while
+    // the call runs. `msg32` and `key32` are the 32-byte message and secret
+    // of that call, and `nonce32` is a 32-byte buffer on libsecp256k1's
+    // stack, all valid until this returns.
+    let nonce = &mut *(data as *mut F);
+    let message = &*(msg32 as *const [u8; 32]);
+    let secret = &*(key32 as *const [u8; 32]);
+    match nonce(message, secret, attempt) {
+        Some(value) => {
+            *(nonce32 as *mut [u8; 32]) = value;
+            1
+        }
+        None => 0,
+    }
+}
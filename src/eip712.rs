@@ -0,0 +1,405 @@
+//! Hashing and signing of [EIP-712] typed data in its JSON form, as
+//! `eth_signTypedData_v4` takes it.
+//!
+//! A document has the struct definitions under `types`, the `primaryType`
+//! of the `message`, and a `domain` of type `EIP712Domain` that binds the
+//! signature to one application. Each struct is hashed as the Keccak-256 of
+//! its type hash followed by its fields, each encoded in 32 bytes.
+//!
+//! Enabled with the `json` feature.
+//!
+//! [EIP-712]: https://eips.ethereum.org/EIPS/eip-712
+
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::crypto;
+use crate::eth::{self, EthSignature};
+use crate::{Error, Message, PrivateKey};
+
+/// The hash to sign for a typed data document.
+///
+/// Fails with `Error::InvalidMessage` if the document is malformed or a
+/// value does not fit its type.
+pub fn hash_typed_data(typed_data: &Value) -> Result<Message, Error> {
+    let types = typed_data
+        .get("types")
+        .and_then(Value::as_object)
+        .ok_or(Error::InvalidMessage)?;
+    let primary_type = typed_data
+        .get("primaryType")
+        .and_then(Value::as_str)
+        .ok_or(Error::InvalidMessage)?;
+    let domain = typed_data.get("domain").ok_or(Error::InvalidMessage)?;
+    let message = typed_data.get("message").ok_or(Error::InvalidMessage)?;
+
+    let domain_separator = hash_struct(types, "EIP712Domain", domain)?;
+    let struct_hash = hash_struct(types, primary_type, message)?;
+    Ok(eth::typed_data_hash(&domain_separator, &struct_hash))
+}
+
+/// The Keccak-256 of the encoded `data` of struct type `name`.
+pub fn hash_struct(types: &Map<String, Value>, name: &str, data: &Value) -> Result<Message, Error> {
+    let fields = data.as_object().ok_or(Error::InvalidMessage)?;
+    let mut encoded = crypto::keccak256(encode_type(types, name)?.as_bytes()).to_vec();
+    for (field, field_type) in fields_of(types, name)? {
+        let value = fields.get(field).ok_or(Error::InvalidMessage)?;
+        encoded.extend_from_slice(&encode_value(types, field_type, value)?);
+    }
+    Ok(Message::from_prehashed(crypto::keccak256(&encoded)))
+}
+
+/// The signature of struct type `name`, followed by those of the struct
+/// types it refers to in alphabetical order.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::eip712;
+/// use serde_json::json;
+///
+/// let types = json!({
+///     "Mail": [
+///         { "name": "from", "type": "Person" },
+///         { "name": "contents", "type": "string" }
+///     ],
+///     "Person": [{ "name": "wallet", "type": "address" }]
+/// });
+/// assert_eq!(
+///     eip712::encode_type(types.as_object().unwrap(), "Mail").unwrap(),
+///     "Mail(Person from,string contents)Person(address wallet)"
+/// );
+/// ```
+pub fn encode_type(types: &Map<String, Value>, name: &str) -> Result<String, Error> {
+    let mut dependencies = BTreeSet::new();
+    collect_dependencies(types, name, &mut dependencies)?;
+    dependencies.remove(name);
+
+    let mut encoded = format_type(types, name)?;
+    for dependency in dependencies {
+        encoded.push_str(&format_type(types, dependency)?);
+    }
+    Ok(encoded)
+}
+
+impl PrivateKey {
+    /// Sign a typed data document as `eth_signTypedData_v4` does.
+    pub fn sign_typed_data(&self, typed_data: &Value) -> Result<EthSignature, Error> {
+        self.sign_eth_hash(&hash_typed_data(typed_data)?)
+    }
+}
+
+/// The `(name, type)` pairs of the fields of struct type `name`.
+fn fields_of<'a>(
+    types: &'a Map<String, Value>,
+    name: &str,
+) -> Result<Vec<(&'a str, &'a str)>, Error> {
+    let fields = types
+        .get(name)
+        .and_then(Value::as_array)
+        .ok_or(Error::InvalidMessage)?;
+    fields
+        .iter()
+        .map(|field| {
+            let name = field.get("name").and_then(Value::as_str);
+            let field_type = field.get("type").and_then(Value::as_str);
+            name.zip(field_type).ok_or(Error::InvalidMessage)
+        })
+        .collect()
+}
+
+fn format_type(types: &Map<String, Value>, name: &str) -> Result<String, Error> {
+    let fields: Vec<String> = fields_of(types, name)?
+        .iter()
+        .map(|(field, field_type)| format!("{} {}", field_type, field))
+        .collect();
+    Ok(format!("{}({})", name, fields.join(",")))
+}
+
+fn collect_dependencies<'a>(
+    types: &'a Map<String, Value>,
+    name: &str,
+    found: &mut BTreeSet<&'a str>,
+) -> Result<(), Error> {
+    let name = match types.get_key_value(name) {
+        Some((name, _)) => name.as_str(),
+        None => return Ok(()),
+    };
+    if !found.insert(name) {
+        return Ok(());
+    }
+    for (_, field_type) in fields_of(types, name)? {
+        let base_type = field_type.split('[').next().unwrap_or(field_type);
+        collect_dependencies(types, base_type, found)?;
+    }
+    Ok(())
+}
+
+/// Encode one field value in 32 bytes.
+fn encode_value(
+    types: &Map<String, Value>,
+    field_type: &str,
+    value: &Value,
+) -> Result<[u8; 32], Error> {
+    if let Some(item_type) = field_type.strip_suffix(']') {
+        let open = item_type.rfind('[').ok_or(Error::InvalidMessage)?;
+        let (item_type, len) = (&item_type[..open], &item_type[open + 1..]);
+        let items = value.as_array().ok_or(Error::InvalidMessage)?;
+        if !len.is_empty() && len.parse::<usize>().ok() != Some(items.len()) {
+            return Err(Error::InvalidMessage);
+        }
+        let mut encoded = vec![];
+        for item in items {
+            encoded.extend_from_slice(&encode_value(types, item_type, item)?);
+        }
+        return Ok(crypto::keccak256(&encoded));
+    }
+    if types.contains_key(field_type) {
+        return Ok(*hash_struct(types, field_type, value)?.as_bytes());
+    }
+
+    let mut word = [0u8; 32];
+    match field_type {
+        "string" => {
+            let s = value.as_str().ok_or(Error::InvalidMessage)?;
+            word = crypto::keccak256(s.as_bytes());
+        }
+        "bytes" => word = crypto::keccak256(&decode_hex(value)?),
+        "bool" => word[31] = value.as_bool().ok_or(Error::InvalidMessage)? as u8,
+        "address" => {
+            let bytes = decode_hex(value)?;
+            if bytes.len() != 20 {
+                return Err(Error::InvalidMessage);
+            }
+            word[12..].copy_from_slice(&bytes);
+        }
+        _ => {
+            if let Some(size) = field_type.strip_prefix("bytes") {
+                // `bytes1` to `bytes32`; dynamic `bytes` is matched above.
+                let size = size.parse::<usize>().map_err(|_| Error::InvalidMessage)?;
+                let bytes = decode_hex(value)?;
+                if !(1..=32).contains(&size) || bytes.len() != size {
+                    return Err(Error::InvalidMessage);
+                }
+                word[..bytes.len()].copy_from_slice(&bytes);
+            } else if let Some(bits) = field_type.strip_prefix("uint") {
+                word = encode_integer(value, false, bits)?;
+            } else if let Some(bits) = field_type.strip_prefix("int") {
+                word = encode_integer(value, true, bits)?;
+            } else {
+                return Err(Error::InvalidMessage);
+            }
+        }
+    }
+    Ok(word)
+}
+
+fn decode_hex(value: &Value) -> Result<Vec<u8>, Error> {
+    let s = value.as_str().ok_or(Error::InvalidMessage)?;
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    hex::decode(digits).map_err(|_| Error::InvalidMessage)
+}
+
+/// Encode an integer given as a JSON number or as a decimal or `0x` hex
+/// string, in two's complement if negative.
+fn encode_integer(value: &Value, signed: bool, bits: &str) -> Result<[u8; 32], Error> {
+    let bits = if bits.is_empty() {
+        256
+    } else {
+        bits.parse::<usize>().map_err(|_| Error::InvalidMessage)?
+    };
+    if bits == 0 || bits > 256 || bits % 8 != 0 {
+        return Err(Error::InvalidMessage);
+    }
+
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return Err(Error::InvalidMessage),
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) if signed => (true, digits),
+        Some(_) => return Err(Error::InvalidMessage),
+        None => (false, text.as_str()),
+    };
+
+    let (radix, digits) = match digits.strip_prefix("0x") {
+        Some(digits) => (16, digits),
+        None => (10, digits),
+    };
+    if digits.is_empty() {
+        return Err(Error::InvalidMessage);
+    }
+    let mut word = [0u8; 32];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix).ok_or(Error::InvalidMessage)?;
+        for byte in word.iter_mut().rev() {
+            let product = u32::from(*byte) * radix + carry;
+            *byte = product as u8;
+            carry = product >> 8;
+        }
+        if carry != 0 {
+            return Err(Error::InvalidMessage);
+        }
+    }
+
+    // A signed value must fit in `bits - 1` bits, except for the most
+    // negative one, `-2^(bits - 1)`.
+    let magnitude_bits = bit_length(&word);
+    let fits = if !signed {
+        magnitude_bits <= bits
+    } else if negative {
+        magnitude_bits < bits || (magnitude_bits == bits && word_is_power_of_two(&word))
+    } else {
+        magnitude_bits < bits
+    };
+    if !fits {
+        return Err(Error::InvalidMessage);
+    }
+
+    if negative {
+        for byte in word.iter_mut() {
+            *byte = !*byte;
+        }
+        for byte in word.iter_mut().rev() {
+            let (sum, overflow) = byte.overflowing_add(1);
+            *byte = sum;
+            if !overflow {
+                break;
+            }
+        }
+    }
+    Ok(word)
+}
+
+fn bit_length(word: &[u8; 32]) -> usize {
+    match word.iter().position(|&byte| byte != 0) {
+        Some(i) => (32 - i) * 8 - word[i].leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+fn word_is_power_of_two(word: &[u8; 32]) -> bool {
+    word.iter().map(|byte| byte.count_ones()).sum::<u32>() == 1
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Network;
+    use secp256k1::bitcoin_hashes::hex::ToHex;
+    use serde_json::json;
+
+    #[test]
+    fn mail_example() -> Result<(), Error> {
+        // The example of the EIP, signed with keccak256("cow").
+        let typed_data = json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallet", "type": "address" }
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person" },
+                    { "name": "contents", "type": "string" }
+                ]
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+                "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+                "contents": "Hello, Bob!"
+            }
+        });
+        let types = typed_data["types"].as_object().unwrap();
+        assert_eq!(
+            encode_type(types, "Mail")?,
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hash_struct(types, "EIP712Domain", &typed_data["domain"])?.to_hex(),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hash_struct(types, "Mail", &typed_data["message"])?.to_hex(),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        let hash = hash_typed_data(&typed_data)?;
+        assert_eq!(
+            hash.to_hex(),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        let private = PrivateKey::from_slice(&crypto::keccak256(b"cow"), Network::Mainnet)?;
+        let signature = private.sign_typed_data(&typed_data)?;
+        assert_eq!(signature.v, 28);
+        assert_eq!(
+            signature.r.to_hex(),
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d"
+        );
+        assert_eq!(
+            signature.s.to_hex(),
+            "07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"
+        );
+        assert_eq!(
+            signature.recover_address(&hash)?,
+            "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fixed_bytes() -> Result<(), Error> {
+        let types = Map::new();
+        let word = encode_value(&types, "bytes2", &json!("0xabcd"))?;
+        assert_eq!(word[..2], [0xab, 0xcd]);
+        assert_eq!(word[2..], [0u8; 30]);
+        assert_eq!(
+            encode_value(&types, "bytes32", &json!(format!("0x{}", "11".repeat(32))))?,
+            [0x11; 32]
+        );
+
+        for (field_type, value) in &[
+            ("bytes0", json!("0x")),
+            ("bytes33", json!(format!("0x{}", "11".repeat(33)))),
+            ("bytes2", json!("0xab")),
+            ("bytesx", json!("0xab")),
+        ] {
+            assert!(encode_value(&types, field_type, value).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn integers() -> Result<(), Error> {
+        assert_eq!(encode_integer(&json!(-1), true, "8")?, [0xff; 32]);
+        assert_eq!(encode_integer(&json!("0xff"), false, "8")?[31], 0xff);
+        assert_eq!(encode_integer(&json!("-128"), true, "8")?[31], 0x80);
+        assert_eq!(encode_integer(&json!("-128"), true, "8")?[0], 0xff);
+
+        for (value, signed, bits) in &[
+            (json!(256), false, "8"),
+            (json!(128), true, "8"),
+            (json!(-129), true, "8"),
+            (json!(-1), false, "256"),
+            (json!("12a"), false, "256"),
+        ] {
+            assert!(encode_integer(value, *signed, bits).is_err());
+        }
+        Ok(())
+    }
+}
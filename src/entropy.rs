@@ -0,0 +1,409 @@
+//! Sanity checks and manual collection of caller-supplied seed entropy.
+//!
+//! None of the checks in [EntropyReport](EntropyReport) can prove that entropy
+//! is random, they only catch the obvious mistakes (zeroed buffers, repeated
+//! patterns, published test vectors) before they end up protecting real funds.
+//!
+//! [from_dice](from_dice), [from_coin_flips](from_coin_flips) and
+//! [from_card_shuffle](from_card_shuffle) turn physical randomness into
+//! [ManualEntropy](ManualEntropy), which keeps an estimate of the bits
+//! collected and refuses to produce more entropy than that. For dice and
+//! cards the estimate assumes a fair die and a thorough shuffle, so it is an
+//! upper bound: collect more than the minimum when in doubt.
+
+use ring::digest;
+use std::collections::HashSet;
+use std::fmt;
+use thiserror::Error;
+
+pub(crate) const MIN_ENTROPY_LEN: usize = 16;
+pub(crate) const MAX_ENTROPY_LEN: usize = 32;
+pub(crate) const ENTROPY_LEN_STEP: usize = 4;
+const BYTE_LEN: usize = 8;
+const DECK_SIZE: u8 = 52;
+
+/// Entropy vectors published in the BIP39 reference test suite. Anything on
+/// this list is known to the world and must never back a real wallet.
+const KNOWN_WEAK_VECTORS: [&str; 8] = [
+    "9e885d952ad362caeb4efe34a8e91bd2",
+    "c0ba5a8e914111210f2bd131f3d5e08d",
+    "23db8160a31d3e0dca3688ed941adbf3",
+    "f30f8c1da665478f49b001d94c5fc452",
+    "6610b25967cdcca9d59875f5cb50b0ea75433311869e930b",
+    "68a79eaca2324873eacc50cb9c6eca8cc68ea5d936f98787c60c7ebc74e6ce7c",
+    "066dca1a2bb7e8a1db2832148ce9933eea0f3ac9548d793112d9a95c9407efad",
+    "f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f",
+];
+
+/// Error originating from [entropy](entropy) module.
+#[derive(Error, Debug, PartialEq)]
+pub enum EntropyError {
+    #[error("A die must have 2 to 255 sides, got {0}")]
+    InvalidSides(u8),
+
+    #[error("Roll {roll} is out of range for a {sides}-sided die")]
+    RollOutOfRange { roll: u8, sides: u8 },
+
+    #[error("Card {0} is not in a 52-card deck")]
+    InvalidCard(u8),
+
+    #[error("Card {0} appears more than once")]
+    DuplicateCard(u8),
+
+    #[error("Invalid entropy length of {0} bytes")]
+    InvalidLength(usize),
+
+    #[error("Collected {collected} bits of entropy, {required} are required")]
+    InsufficientEntropy { required: usize, collected: usize },
+}
+
+/// Entropy gathered by hand, along with an estimate of how many bits of
+/// min-entropy it holds.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::bip39::SeedBuilder;
+/// use keymaker::entropy;
+///
+/// // 50 rolls of a six-sided die hold just over 128 bits.
+/// let rolls: Vec<u8> = (0..50).map(|i| (i * 7 % 6 + 1) as u8).collect();
+/// let dice = entropy::from_dice(&rolls, 6).unwrap();
+/// assert_eq!(dice.min_entropy_bits(), 129);
+///
+/// let seed = SeedBuilder::new().entropy(dice.to_entropy(16).unwrap()).build().unwrap();
+/// assert_eq!(seed.mnemonic.len(), 12);
+///
+/// // ...but not enough for a 24-word mnemonic.
+/// assert!(dice.to_entropy(32).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualEntropy {
+    source: Vec<u8>,
+    min_entropy_bits: usize,
+}
+
+impl ManualEntropy {
+    /// Estimated bits of min-entropy collected.
+    pub fn min_entropy_bits(&self) -> usize {
+        self.min_entropy_bits
+    }
+
+    /// Condition the collected entropy through SHA256 into `len` bytes, ready
+    /// for [SeedBuilder::entropy](crate::bip39::SeedBuilder::entropy).
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - 16 to 32 bytes, in steps of 4 bytes.
+    pub fn to_entropy(&self, len: usize) -> Result<Vec<u8>, EntropyError> {
+        if !(MIN_ENTROPY_LEN..=MAX_ENTROPY_LEN).contains(&len) || len % ENTROPY_LEN_STEP != 0 {
+            return Err(EntropyError::InvalidLength(len));
+        }
+
+        let required = len * BYTE_LEN;
+        if self.min_entropy_bits < required {
+            return Err(EntropyError::InsufficientEntropy {
+                required,
+                collected: self.min_entropy_bits,
+            });
+        }
+
+        let hash = digest::digest(&digest::SHA256, &self.source);
+        Ok(hash.as_ref()[..len].to_vec())
+    }
+}
+
+/// Collect entropy from rolls of a fair die.
+///
+/// Each roll counts for `log2(sides)` bits, which only a fair die delivers,
+/// so the estimate is an upper bound. A biased die holds less and cannot be
+/// corrected after the fact, so roll a casino-grade die on a flat surface.
+///
+/// # Arguments
+///
+/// * `rolls` - Roll results, from 1 to `sides`.
+/// * `sides` - Number of sides of the die.
+pub fn from_dice(rolls: &[u8], sides: u8) -> Result<ManualEntropy, EntropyError> {
+    if sides < 2 {
+        return Err(EntropyError::InvalidSides(sides));
+    }
+
+    if let Some(roll) = rolls.iter().find(|r| **r == 0 || **r > sides) {
+        return Err(EntropyError::RollOutOfRange { roll: *roll, sides });
+    }
+
+    let mut source = b"dice".to_vec();
+    source.push(sides);
+    source.extend_from_slice(rolls);
+
+    let min_entropy_bits = (rolls.len() as f64 * f64::from(sides).log2()).floor() as usize;
+    Ok(ManualEntropy {
+        source,
+        min_entropy_bits,
+    })
+}
+
+/// Collect entropy from coin flips, `true` for heads.
+///
+/// The flips go through a von Neumann extractor: consecutive pairs `HT` and
+/// `TH` yield one bit and `HH`/`TT` pairs are discarded, which removes the bias
+/// of an unfair coin at the cost of needing roughly four flips per bit.
+pub fn from_coin_flips(flips: &[bool]) -> Result<ManualEntropy, EntropyError> {
+    let bits: Vec<bool> = flips
+        .chunks_exact(2)
+        .filter(|pair| pair[0] != pair[1])
+        .map(|pair| pair[0])
+        .collect();
+
+    // The bit count goes in first, as the last byte is padded and would
+    // otherwise not tell `0` from `00`.
+    let mut source = b"coin".to_vec();
+    source.extend_from_slice(&(bits.len() as u64).to_be_bytes());
+    source.extend(bits.chunks(BYTE_LEN).map(|chunk| {
+        chunk
+            .iter()
+            .fold(0u8, |byte, bit| (byte << 1) | u8::from(*bit))
+    }));
+
+    Ok(ManualEntropy {
+        source,
+        min_entropy_bits: bits.len(),
+    })
+}
+
+/// Collect entropy from the order of a shuffled 52-card deck.
+///
+/// # Arguments
+///
+/// * `order` - Cards as dealt from the top of the deck, numbered 0 to 51. A
+///   partial deal is accepted and counts for fewer bits; a full, well-shuffled
+///   deck holds about 225 bits.
+pub fn from_card_shuffle(order: &[u8]) -> Result<ManualEntropy, EntropyError> {
+    let mut seen = HashSet::new();
+    for card in order {
+        if *card >= DECK_SIZE {
+            return Err(EntropyError::InvalidCard(*card));
+        }
+        if !seen.insert(*card) {
+            return Err(EntropyError::DuplicateCard(*card));
+        }
+    }
+
+    let mut source = b"card".to_vec();
+    source.extend_from_slice(order);
+
+    let min_entropy_bits = (0..order.len())
+        .map(|dealt| f64::from(DECK_SIZE - dealt as u8).log2())
+        .sum::<f64>()
+        .floor() as usize;
+    Ok(ManualEntropy {
+        source,
+        min_entropy_bits,
+    })
+}
+
+/// A single weakness found in a piece of entropy.
+#[derive(Debug, PartialEq, Clone)]
+pub enum EntropyWarning {
+    /// Entropy must be 128 to 256 bits long, in steps of 32 bits.
+    InvalidLength(usize),
+    /// Every byte has the same value (e.g. all zeros).
+    RepeatedByte(u8),
+    /// The bytes repeat with the given period.
+    RepeatedPattern(usize),
+    /// Every byte differs from the previous one by the same step.
+    Sequential,
+    /// Too few distinct byte values for the length of the entropy.
+    LowByteDiversity { distinct: usize, len: usize },
+    /// The entropy is a published test vector.
+    KnownWeakVector,
+}
+
+impl fmt::Display for EntropyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntropyWarning::InvalidLength(len) => {
+                write!(f, "invalid entropy length of {} bytes", len)
+            }
+            EntropyWarning::RepeatedByte(b) => write!(f, "every byte is {:#04x}", b),
+            EntropyWarning::RepeatedPattern(period) => {
+                write!(f, "bytes repeat every {} bytes", period)
+            }
+            EntropyWarning::Sequential => "bytes form a sequence".fmt(f),
+            EntropyWarning::LowByteDiversity { distinct, len } => {
+                write!(f, "only {} distinct values in {} bytes", distinct, len)
+            }
+            EntropyWarning::KnownWeakVector => "entropy is a published test vector".fmt(f),
+        }
+    }
+}
+
+/// Result of checking a piece of entropy for obvious weaknesses.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::entropy::{EntropyReport, EntropyWarning};
+///
+/// let report = EntropyReport::analyze(&[0u8; 16]);
+/// assert!(!report.is_acceptable());
+/// assert!(report.warnings().contains(&EntropyWarning::RepeatedByte(0)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EntropyReport {
+    warnings: Vec<EntropyWarning>,
+}
+
+impl EntropyReport {
+    /// Run every check against `entropy`.
+    pub fn analyze(entropy: &[u8]) -> Self {
+        let mut warnings = vec![];
+        let len = entropy.len();
+
+        if !(MIN_ENTROPY_LEN..=MAX_ENTROPY_LEN).contains(&len) || len % ENTROPY_LEN_STEP != 0 {
+            warnings.push(EntropyWarning::InvalidLength(len));
+        }
+
+        if len == 0 {
+            return EntropyReport { warnings };
+        }
+
+        if entropy.iter().all(|b| *b == entropy[0]) {
+            warnings.push(EntropyWarning::RepeatedByte(entropy[0]));
+        } else if let Some(period) = (2..=len / 2).find(|p| is_periodic(entropy, *p)) {
+            warnings.push(EntropyWarning::RepeatedPattern(period));
+        }
+
+        if len > 2 {
+            let step = entropy[1].wrapping_sub(entropy[0]);
+            let sequential =
+                step != 0 && entropy.windows(2).all(|w| w[1].wrapping_sub(w[0]) == step);
+            if sequential {
+                warnings.push(EntropyWarning::Sequential);
+            }
+        }
+
+        let distinct = entropy.iter().collect::<HashSet<_>>().len();
+        if distinct * 2 < len {
+            warnings.push(EntropyWarning::LowByteDiversity { distinct, len });
+        }
+
+        let hex_entropy = hex::encode(entropy);
+        if KNOWN_WEAK_VECTORS.contains(&hex_entropy.as_str()) {
+            warnings.push(EntropyWarning::KnownWeakVector);
+        }
+
+        EntropyReport { warnings }
+    }
+
+    /// Every weakness found, in the order the checks ran.
+    pub fn warnings(&self) -> &[EntropyWarning] {
+        &self.warnings
+    }
+
+    /// `true` if no check flagged the entropy.
+    pub fn is_acceptable(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+impl fmt::Display for EntropyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.warnings.is_empty() {
+            return "no weaknesses found".fmt(f);
+        }
+        let msgs: Vec<String> = self.warnings.iter().map(|w| w.to_string()).collect();
+        msgs.join(", ").fmt(f)
+    }
+}
+
+fn is_periodic(data: &[u8], period: usize) -> bool {
+    data.iter()
+        .skip(period)
+        .zip(data.iter())
+        .all(|(a, b)| a == b)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand_core::{OsRng, RngCore};
+
+    #[test]
+    fn random_entropy_is_acceptable() {
+        let mut entropy = [0u8; 32];
+        OsRng.fill_bytes(&mut entropy);
+        let report = EntropyReport::analyze(&entropy);
+        assert!(report.is_acceptable(), "{}", report);
+    }
+
+    #[test]
+    fn weak_entropy_is_flagged() {
+        let report = EntropyReport::analyze(&[0xffu8; 16]);
+        assert!(report
+            .warnings()
+            .contains(&EntropyWarning::RepeatedByte(0xff)));
+
+        let pattern: Vec<u8> = [0xde, 0xad, 0xbe, 0xef].repeat(4);
+        let report = EntropyReport::analyze(&pattern);
+        assert!(report
+            .warnings()
+            .contains(&EntropyWarning::RepeatedPattern(4)));
+
+        let sequence: Vec<u8> = (0u8..16).collect();
+        let report = EntropyReport::analyze(&sequence);
+        assert_eq!(report.warnings(), &[EntropyWarning::Sequential]);
+
+        let vector = hex::decode(KNOWN_WEAK_VECTORS[0]).unwrap();
+        let report = EntropyReport::analyze(&vector);
+        assert_eq!(report.warnings(), &[EntropyWarning::KnownWeakVector]);
+
+        let report = EntropyReport::analyze(&[1u8; 15]);
+        assert!(report
+            .warnings()
+            .contains(&EntropyWarning::InvalidLength(15)));
+    }
+
+    #[test]
+    fn manual_entropy_accounting() -> Result<(), EntropyError> {
+        let rolls = [6u8; 99];
+        assert_eq!(from_dice(&rolls, 6)?.min_entropy_bits(), 255);
+        assert_eq!(from_dice(&rolls, 6)?.to_entropy(28)?.len(), 28);
+        assert_eq!(
+            from_dice(&[7], 6).unwrap_err(),
+            EntropyError::RollOutOfRange { roll: 7, sides: 6 }
+        );
+
+        // Only the HT and TH pairs count.
+        let flips = [true, true, true, false, false, true, false, false];
+        assert_eq!(from_coin_flips(&flips)?.min_entropy_bits(), 2);
+
+        // 128 bits then `1`, or `01`, pack into the same bytes.
+        let flips = [true, false].repeat(128);
+        let shorter = from_coin_flips(&[&flips[..], &[true, false]].concat())?;
+        let longer = from_coin_flips(&[&flips[..], &[false, true, true, false]].concat())?;
+        assert_eq!(
+            (shorter.min_entropy_bits(), longer.min_entropy_bits()),
+            (129, 130)
+        );
+        assert_ne!(shorter.to_entropy(16)?, longer.to_entropy(16)?);
+
+        let deck: Vec<u8> = (0..52).collect();
+        let shuffle = from_card_shuffle(&deck)?;
+        assert_eq!(shuffle.min_entropy_bits(), 225);
+        assert_eq!(shuffle.to_entropy(28)?.len(), 28);
+        assert_eq!(
+            shuffle.to_entropy(32).unwrap_err(),
+            EntropyError::InsufficientEntropy {
+                required: 256,
+                collected: 225
+            }
+        );
+        assert_eq!(
+            from_card_shuffle(&[3, 3]).unwrap_err(),
+            EntropyError::DuplicateCard(3)
+        );
+        Ok(())
+    }
+}
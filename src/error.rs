@@ -11,6 +11,8 @@ pub enum Error {
     InvalidChecksum,
     InvalidPrivate,
     InvalidAddress,
+    InvalidExtendedKey,
+    InvalidTweak,
     FailedKeyGeneration,
 }
 
@@ -25,6 +27,8 @@ impl fmt::Display for Error {
             Error::InvalidChecksum => "Invalid Checksum",
             Error::InvalidPrivate => "Invalid Private",
             Error::InvalidAddress => "Invalid Address",
+            Error::InvalidExtendedKey => "Invalid Extended Key",
+            Error::InvalidTweak => "Invalid Tweak",
             Error::FailedKeyGeneration => "Key generation failed",
         };
 
@@ -32,12 +36,15 @@ impl fmt::Display for Error {
     }
 }
 
+impl std::error::Error for Error {}
+
 impl From<SecpError> for Error {
     fn from(e: SecpError) -> Self {
         match e {
             SecpError::InvalidPublicKey => Error::InvalidPublic,
             SecpError::InvalidSecretKey => Error::InvalidSecret,
             SecpError::InvalidMessage => Error::InvalidMessage,
+            SecpError::InvalidTweak => Error::InvalidTweak,
             _ => Error::InvalidSignature,
         }
     }
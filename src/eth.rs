@@ -0,0 +1,246 @@
+//! Ethereum addresses with the mixed-case checksum of [EIP-55], and message
+//! signing as in [EIP-191] and [EIP-712].
+//!
+//! An address is written as `0x` and 40 hex digits. EIP-55 uppercases each
+//! letter whose nibble in the Keccak-256 hash of the lowercase address is 8
+//! or more, so a typo is caught without changing the format.
+//!
+//! Signatures come as the `(v, r, s)` triple that `ecrecover` takes. Hashing
+//! EIP-712 typed data from its JSON form is in the `eip712` module,
+//! behind the `json` feature.
+//!
+//! This module and the Keccak-256 hash it builds on are behind the `eth`
+//! feature, which is on by default.
+//!
+//! [EIP-55]: https://eips.ethereum.org/EIPS/eip-55
+//! [EIP-191]: https://eips.ethereum.org/EIPS/eip-191
+//! [EIP-712]: https://eips.ethereum.org/EIPS/eip-712
+
+use secp256k1::bitcoin_hashes::hex::ToHex;
+use std::fmt;
+
+use crate::crypto::{self, Digest};
+use crate::{AddressHash, Error, Message, PrivateKey, RecoverableSignature};
+
+const PERSONAL_MESSAGE_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+
+/// An ECDSA signature split the way Ethereum transactions and `ecrecover`
+/// carry it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthSignature {
+    /// The recovery id plus 27.
+    pub v: u8,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+impl EthSignature {
+    /// The 65 bytes `r || s || v` that wallets return from `eth_sign`.
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..64].copy_from_slice(&self.s);
+        bytes[64] = self.v;
+        bytes
+    }
+
+    /// Recover the address that signed `hash`.
+    pub fn recover_address(&self, hash: &Message) -> Result<String, Error> {
+        if self.v != 27 && self.v != 28 {
+            return Err(Error::InvalidSignature);
+        }
+        let signature = RecoverableSignature::new(self.v - 27, self.r, self.s)?;
+        signature.recover(hash)?.to_eth_address()
+    }
+}
+
+impl From<RecoverableSignature> for EthSignature {
+    fn from(signature: RecoverableSignature) -> Self {
+        EthSignature {
+            v: signature.v(None) as u8,
+            r: signature.r(),
+            s: signature.s(),
+        }
+    }
+}
+
+impl fmt::Display for EthSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{}", self.to_bytes().to_hex())
+    }
+}
+
+/// The EIP-191 hash of a `personal_sign` message: the Keccak-256 of
+/// `"\x19Ethereum Signed Message:\n"`, the message length in decimal and the
+/// message.
+pub fn personal_message_hash(message: &[u8]) -> Message {
+    let mut hasher = crypto::Keccak256::default();
+    hasher.update(PERSONAL_MESSAGE_PREFIX);
+    hasher.update(message.len().to_string().as_bytes());
+    hasher.update(message);
+    Message::from_prehashed(hasher.finalize())
+}
+
+/// The EIP-712 hash to sign, `keccak256(0x19 0x01 || domain_separator ||
+/// struct_hash)`, from the hashes of the domain and the message.
+pub fn typed_data_hash(domain_separator: &Message, struct_hash: &Message) -> Message {
+    let mut hasher = crypto::Keccak256::default();
+    hasher.update(&[0x19, 0x01]);
+    hasher.update(domain_separator.as_bytes());
+    hasher.update(struct_hash.as_bytes());
+    Message::from_prehashed(hasher.finalize())
+}
+
+impl PrivateKey {
+    /// Sign a 32-byte `hash` for `ecrecover`.
+    pub fn sign_eth_hash(&self, hash: &Message) -> Result<EthSignature, Error> {
+        Ok(self.sign_recoverable(hash)?.into())
+    }
+
+    /// Sign `message` as `personal_sign` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{eth, KeyPairBuilder};
+    ///
+    /// let keys = KeyPairBuilder::new().build().unwrap();
+    /// let signature = keys.privkey().sign_eth_message(b"hello").unwrap();
+    /// assert_eq!(
+    ///     signature.recover_address(&eth::personal_message_hash(b"hello")).unwrap(),
+    ///     keys.pubkey().to_eth_address().unwrap()
+    /// );
+    /// ```
+    pub fn sign_eth_message(&self, message: &[u8]) -> Result<EthSignature, Error> {
+        self.sign_eth_hash(&personal_message_hash(message))
+    }
+}
+
+/// Format a 20-byte address with its EIP-55 checksum.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::eth;
+///
+/// let address = hex::decode("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+/// let mut bytes = [0u8; 20];
+/// bytes.copy_from_slice(&address);
+/// assert_eq!(
+///     eth::to_checksum_address(&bytes),
+///     "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+/// );
+/// ```
+pub fn to_checksum_address(address: &AddressHash) -> String {
+    let lower = hex::encode(address);
+    let hash = crypto::keccak256(lower.as_bytes());
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        if nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+    checksummed
+}
+
+/// Parse an address, checking its EIP-55 checksum.
+///
+/// All-lowercase and all-uppercase addresses carry no checksum and are
+/// accepted as they are. A mixed-case address with a wrong letter case fails
+/// with `Error::InvalidChecksum`.
+pub fn validate_address(s: &str) -> Result<AddressHash, Error> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    if digits.len() != 40 {
+        return Err(Error::InvalidAddress);
+    }
+
+    let bytes = hex::decode(digits).map_err(|_| Error::InvalidAddress)?;
+    let mut address = AddressHash::default();
+    address.copy_from_slice(&bytes);
+
+    let has_lower = digits.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = digits.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper && to_checksum_address(&address)[2..] != *digits {
+        return Err(Error::InvalidChecksum);
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{Network, PrivateKey};
+
+    #[test]
+    fn eip55_vectors() -> Result<(), Error> {
+        for s in &[
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let address = validate_address(s)?;
+            assert_eq!(to_checksum_address(&address), *s);
+            assert!(validate_address(&s.to_lowercase()).is_ok());
+        }
+
+        assert!(matches!(
+            validate_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(Error::InvalidChecksum)
+        ));
+        assert!(matches!(
+            validate_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"),
+            Err(Error::InvalidAddress)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn address_of_public_key() -> Result<(), Error> {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let private = PrivateKey {
+            network: Network::Mainnet,
+            secret,
+            compressed: true,
+        };
+        assert_eq!(
+            private.public_key()?.to_eth_address()?,
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn personal_sign_vector() -> Result<(), Error> {
+        let private = PrivateKey::from_hex(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            Network::Mainnet,
+        )?;
+        let hash = personal_message_hash(b"Some data");
+        assert_eq!(
+            hash.to_hex(),
+            "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+        );
+
+        let signature = private.sign_eth_message(b"Some data")?;
+        assert_eq!(
+            signature.to_string(),
+            "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c"
+        );
+        assert_eq!(
+            signature.recover_address(&hash)?,
+            private.public_key()?.to_eth_address()?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+//! Conversions between keymaker types and their [rust-bitcoin] counterparts.
+//!
+//! Enabled with the `bitcoin` feature. rust-bitcoin links its own version of
+//! secp256k1, so keys and signatures cross over in their serialized form.
+//!
+//! rust-bitcoin only knows Bitcoin, so keys and addresses of any other
+//! [Network] fail to convert with `Error::InvalidNetwork` rather than turn
+//! into Bitcoin mainnet ones.
+//!
+//! [rust-bitcoin]: https://docs.rs/bitcoin
+
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::bip32::{Xpriv, Xpub};
+use bitcoin::secp256k1 as bitcoin_secp;
+use std::convert::{TryFrom, TryInto};
+
+use crate::{
+    Address, Error, ExtendedPrivKey, ExtendedPubKey, Network, PrivateKey, PublicKey, Signature,
+};
+
+impl TryFrom<Network> for bitcoin::NetworkKind {
+    type Error = Error;
+
+    fn try_from(network: Network) -> Result<Self, Error> {
+        match network {
+            Network::Mainnet => Ok(bitcoin::NetworkKind::Main),
+            Network::Testnet | Network::Regtest => Ok(bitcoin::NetworkKind::Test),
+            _ => Err(Error::InvalidNetwork),
+        }
+    }
+}
+
+impl TryFrom<Network> for bitcoin::Network {
+    type Error = Error;
+
+    fn try_from(network: Network) -> Result<Self, Error> {
+        match network {
+            Network::Mainnet => Ok(bitcoin::Network::Bitcoin),
+            Network::Testnet => Ok(bitcoin::Network::Testnet),
+            Network::Regtest => Ok(bitcoin::Network::Regtest),
+            _ => Err(Error::InvalidNetwork),
+        }
+    }
+}
+
+/// The test networks other than regtest share the testnet version bytes and
+/// bech32 prefix, so they become [Testnet](Network::Testnet).
+impl From<bitcoin::Network> for Network {
+    fn from(network: bitcoin::Network) -> Self {
+        match network {
+            bitcoin::Network::Bitcoin => Network::Mainnet,
+            bitcoin::Network::Regtest => Network::Regtest,
+            _ => Network::Testnet,
+        }
+    }
+}
+
+impl From<bitcoin::NetworkKind> for Network {
+    fn from(network: bitcoin::NetworkKind) -> Self {
+        match network {
+            bitcoin::NetworkKind::Main => Network::Mainnet,
+            bitcoin::NetworkKind::Test => Network::Testnet,
+        }
+    }
+}
+
+impl TryFrom<&PrivateKey> for bitcoin::PrivateKey {
+    type Error = Error;
+
+    fn try_from(private: &PrivateKey) -> Result<Self, Error> {
+        let inner = bitcoin_secp::SecretKey::from_slice(&private.secret)
+            .map_err(|_| Error::InvalidSecret)?;
+        Ok(bitcoin::PrivateKey {
+            compressed: private.compressed,
+            network: private.network.try_into()?,
+            inner,
+        })
+    }
+}
+
+impl From<bitcoin::PrivateKey> for PrivateKey {
+    fn from(private: bitcoin::PrivateKey) -> Self {
+        PrivateKey {
+            network: private.network.into(),
+            secret: private.inner.secret_bytes(),
+            compressed: private.compressed,
+        }
+    }
+}
+
+impl TryFrom<&PublicKey> for bitcoin::PublicKey {
+    type Error = Error;
+
+    fn try_from(public: &PublicKey) -> Result<Self, Error> {
+        let (bytes, compressed): (&[u8], bool) = match public {
+            PublicKey::Standard(inner) => (inner, false),
+            PublicKey::Compressed(inner) => (inner, true),
+        };
+        let inner = bitcoin_secp::PublicKey::from_slice(bytes).map_err(|_| Error::InvalidPublic)?;
+        Ok(bitcoin::PublicKey { compressed, inner })
+    }
+}
+
+impl From<bitcoin::PublicKey> for PublicKey {
+    fn from(public: bitcoin::PublicKey) -> Self {
+        if public.compressed {
+            PublicKey::Compressed(public.inner.serialize())
+        } else {
+            PublicKey::Standard(public.inner.serialize_uncompressed())
+        }
+    }
+}
+
+impl TryFrom<&Signature> for bitcoin_secp::ecdsa::Signature {
+    type Error = Error;
+
+    fn try_from(signature: &Signature) -> Result<Self, Error> {
+        bitcoin_secp::ecdsa::Signature::from_der(signature).map_err(|_| Error::InvalidSignature)
+    }
+}
+
+impl From<bitcoin_secp::ecdsa::Signature> for Signature {
+    fn from(signature: bitcoin_secp::ecdsa::Signature) -> Self {
+        Signature(signature.serialize_der().to_vec())
+    }
+}
+
+impl TryFrom<&ExtendedPrivKey> for Xpriv {
+    type Error = Error;
+
+    fn try_from(keys: &ExtendedPrivKey) -> Result<Self, Error> {
+        bitcoin::NetworkKind::try_from(keys.privkey().network)?;
+        keys.to_string()
+            .parse()
+            .map_err(|_| Error::InvalidExtendedKey)
+    }
+}
+
+impl TryFrom<&Xpriv> for ExtendedPrivKey {
+    type Error = Error;
+
+    fn try_from(keys: &Xpriv) -> Result<Self, Error> {
+        keys.to_string()
+            .parse()
+            .map_err(|_| Error::InvalidExtendedKey)
+    }
+}
+
+impl TryFrom<&ExtendedPubKey> for Xpub {
+    type Error = Error;
+
+    fn try_from(keys: &ExtendedPubKey) -> Result<Self, Error> {
+        bitcoin::NetworkKind::try_from(keys.network())?;
+        keys.to_string()
+            .parse()
+            .map_err(|_| Error::InvalidExtendedKey)
+    }
+}
+
+impl TryFrom<&Xpub> for ExtendedPubKey {
+    type Error = Error;
+
+    fn try_from(keys: &Xpub) -> Result<Self, Error> {
+        keys.to_string()
+            .parse()
+            .map_err(|_| Error::InvalidExtendedKey)
+    }
+}
+
+impl TryFrom<&Address> for bitcoin::Address {
+    type Error = Error;
+
+    fn try_from(address: &Address) -> Result<Self, Error> {
+        let network = bitcoin::Network::try_from(address.network())?;
+        address
+            .to_string()
+            .parse::<bitcoin::Address<NetworkUnchecked>>()
+            .map_err(|_| Error::InvalidAddress)?
+            .require_network(network)
+            .map_err(|_| Error::InvalidNetwork)
+    }
+}
+
+impl TryFrom<&bitcoin::Address> for Address {
+    type Error = Error;
+
+    fn try_from(address: &bitcoin::Address) -> Result<Self, Error> {
+        address.to_string().parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::bip39::{Seed, SeedBuilder};
+    use crate::{KeyPair, MasterExtendedKeys, Message};
+
+    #[test]
+    fn round_trip_through_rust_bitcoin() -> anyhow::Result<()> {
+        let Seed { entropy, .. } = SeedBuilder::new().build()?;
+        let keys = MasterExtendedKeys::new(entropy, None, Network::Mainnet, true)?;
+        let kp = KeyPair::from_private(keys.privkey(), false)?;
+
+        let private = bitcoin::PrivateKey::try_from(kp.private())?;
+        assert_eq!(private.to_wif(), kp.private().to_string());
+        assert_eq!(PrivateKey::from(private), *kp.private());
+
+        let public = bitcoin::PublicKey::try_from(kp.public())?;
+        assert_eq!(public.to_string(), kp.public().to_string());
+        assert_eq!(PublicKey::from(public).to_string(), kp.public().to_string());
+
+        let signature = kp
+            .private()
+            .sign_hash(&Message::from_prehashed([7u8; 32]))?;
+        let converted = bitcoin_secp::ecdsa::Signature::try_from(&signature)?;
+        assert_eq!(Signature::from(converted), signature);
+        Ok(())
+    }
+
+    #[test]
+    fn extended_keys_round_trip() -> anyhow::Result<()> {
+        for network in [Network::Mainnet, Network::Testnet].iter() {
+            let keys = ExtendedPrivKey::new([7u8; 64], None, *network, true)?
+                .derive_path(&"m/84'/0'/0'/0/1".parse()?)?;
+            let xpriv = Xpriv::try_from(&keys)?;
+            assert_eq!(xpriv.to_string(), keys.to_string());
+            assert_eq!(
+                ExtendedPrivKey::try_from(&xpriv)?.to_string(),
+                keys.to_string()
+            );
+
+            let xpub = Xpub::try_from(&keys.neuter())?;
+            assert_eq!(
+                xpub,
+                Xpub::from_priv(&bitcoin_secp::Secp256k1::new(), &xpriv)
+            );
+            assert_eq!(
+                ExtendedPubKey::try_from(&xpub)?.to_string(),
+                keys.neuter().to_string()
+            );
+        }
+
+        // Litecoin keys share the Bitcoin mainnet versions, but are not
+        // Bitcoin keys.
+        let litecoin = ExtendedPrivKey::new([7u8; 64], None, Network::Litecoin, true)?;
+        assert_eq!(
+            Xpriv::try_from(&litecoin).err(),
+            Some(Error::InvalidNetwork)
+        );
+        assert_eq!(
+            Xpub::try_from(&litecoin.neuter()).err(),
+            Some(Error::InvalidNetwork)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn addresses_round_trip() -> anyhow::Result<()> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        let public = private.public_key()?;
+        for network in [Network::Mainnet, Network::Testnet, Network::Regtest].iter() {
+            let addresses = [
+                Address::p2pkh(&public, *network),
+                Address::p2sh_wpkh(&public, *network)?,
+                Address::p2wpkh(&public, *network)?,
+                Address::p2tr(&public, None, *network)?,
+            ];
+            for address in addresses.iter() {
+                let converted = bitcoin::Address::try_from(address)?;
+                assert_eq!(converted.to_string(), address.to_string());
+                assert_eq!(
+                    converted.script_pubkey().as_bytes(),
+                    &address.script_pubkey()[..]
+                );
+                // Base58Check regtest addresses come back as testnet ones.
+                let back = Address::try_from(&converted)?.require_network(*network)?;
+                assert_eq!(back.to_string(), address.to_string());
+                assert_eq!(back.address_type(), address.address_type());
+            }
+        }
+
+        let litecoin = Address::p2wpkh(&public, Network::Litecoin)?;
+        assert_eq!(
+            bitcoin::Address::try_from(&litecoin).err(),
+            Some(Error::InvalidNetwork)
+        );
+
+        let private = PrivateKey {
+            network: Network::Dogecoin,
+            ..private
+        };
+        assert_eq!(
+            bitcoin::PrivateKey::try_from(&private).err(),
+            Some(Error::InvalidNetwork)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Signing of JSON documents canonicalized with the JSON Canonicalization
+//! Scheme ([RFC 8785]).
+//!
+//! Two documents that differ only in key order, whitespace or number spelling
+//! canonicalize to the same bytes, so a signature survives re-serialization by
+//! any JSON library along the way. The SHA256 of the canonical form is signed
+//! with ECDSA.
+//!
+//! Enabled with the `json` feature.
+//!
+//! [RFC 8785]: https://www.rfc-editor.org/rfc/rfc8785
+
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::{DataHash, Error, Message, PrivateKey, PublicKey, Signature};
+
+/// Serialize `value` in its RFC 8785 canonical form.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::jcs;
+/// use serde_json::json;
+///
+/// let value = json!({ "b": [1.50, 1e30], "a": "\u{20ac}" });
+/// assert_eq!(jcs::canonicalize(&value), r#"{"a":"€","b":[1.5,1e+30]}"#);
+/// ```
+pub fn canonicalize(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// Sign the canonical form of `value` with `private`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{jcs, MasterExtendedKeys, Network, SeedBuilder};
+/// use serde_json::json;
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let keys = MasterExtendedKeys::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+///
+/// let payload = json!({ "event": "paid", "amount": 10 });
+/// let signature = jcs::sign_json(&keys.privkey(), &payload).unwrap();
+///
+/// let reordered = json!({ "amount": 10.0, "event": "paid" });
+/// assert!(jcs::verify_json(&keys.pubkey(), &reordered, &signature).unwrap());
+/// ```
+pub fn sign_json(private: &PrivateKey, value: &Value) -> Result<Signature, Error> {
+    private.sign_hash(&json_hash(value))
+}
+
+/// Verify a signature created by [sign_json](sign_json).
+pub fn verify_json(
+    public: &PublicKey,
+    value: &Value,
+    signature: &Signature,
+) -> Result<bool, Error> {
+    public.verify(&json_hash(value), signature)
+}
+
+fn json_hash(value: &Value) -> Message {
+    DataHash::Sha256.hash(canonicalize(value).as_bytes())
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            // JSON numbers are IEEE 754 doubles in RFC 8785, integers included.
+            let n = n.as_f64().unwrap_or_default();
+            out.push_str(&format_number(n));
+        }
+        Value::String(s) => write_string(out, s),
+        Value::Array(values) => {
+            out.push('[');
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, v);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            // Members are sorted by the UTF-16 code units of their names.
+            let mut members: Vec<(&String, &Value)> = map.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (i, (k, v)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, k);
+                out.push(':');
+                write_value(out, v);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < '\u{20}' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Format a double the way ECMAScript's `Number.prototype.toString` does.
+fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if n < 0.0 {
+        return format!("-{}", format_number(-n));
+    }
+
+    // Rust's `{:e}` yields the shortest round-tripping digits, e.g. "1.25e-7".
+    let sci = format!("{:e}", n);
+    let (mantissa, exponent) = sci.split_at(sci.find('e').unwrap_or(sci.len()));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent[1..].parse().unwrap_or_default();
+
+    let k = digits.len() as i32;
+    let point = exponent + 1;
+
+    if k <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - k) as usize))
+    } else if 0 < point && point <= 21 {
+        let (int, frac) = digits.split_at(point as usize);
+        format!("{}.{}", int, frac)
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        let (first, rest) = digits.split_at(1);
+        if rest.is_empty() {
+            format!("{}e{}{}", first, sign, exponent.abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, sign, exponent.abs())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn rfc8785_example() {
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let value: Value = serde_json::from_str(input).unwrap();
+        assert_eq!(
+            canonicalize(&value),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn number_formatting() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e+21"),
+            (1e-6, "0.000001"),
+            (1e-7, "1e-7"),
+            (123456789012345680000.0, "123456789012345680000"),
+            (9007199254740993.0, "9007199254740992"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+        ];
+        for (n, expected) in cases.iter() {
+            assert_eq!(format_number(*n), *expected);
+        }
+    }
+
+    #[test]
+    fn object_keys_sort_by_utf16() {
+        // U+1F600 sorts after U+FB33 by code point but before it in UTF-16.
+        let value: Value = serde_json::from_str("{\"\u{fb33}\":2,\"\u{1f600}\":1}").unwrap();
+        assert_eq!(canonicalize(&value), "{\"\u{1f600}\":1,\"\u{fb33}\":2}");
+    }
+}
@@ -1,14 +1,58 @@
+mod address;
+pub mod backup;
+pub mod bech32;
+pub mod bip21;
 pub mod bip32;
+pub mod bip38;
 pub mod bip39;
-mod crypto;
+#[cfg(feature = "cardano")]
+pub mod cardano;
+#[cfg(feature = "bch")]
+pub mod cashaddr;
+pub mod confirmation;
+pub mod cosmos;
+pub mod crypto;
 mod display;
+pub mod ecdh;
+mod ecdsa;
+#[cfg(all(feature = "eth", feature = "json"))]
+pub mod eip712;
+pub mod entropy;
 mod error;
+#[cfg(feature = "eth")]
+pub mod eth;
+#[cfg(feature = "bitcoin")]
+mod interop;
+#[cfg(feature = "json")]
+pub mod jcs;
+pub mod message;
+mod mnemonic;
+pub mod multisig;
+pub mod musig;
 mod network;
+mod path;
+mod point;
 mod private;
 mod public;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod s2c;
+pub mod scalar;
+pub mod scanner;
+pub mod seedqr;
+pub mod seedxor;
 mod signature;
+pub mod solana;
+#[cfg(feature = "eth")]
+pub mod tron;
+pub mod vanity;
+mod xonly;
+pub mod xrp;
 
-pub use bip32::{KeyPair, MasterExtendedKeys};
+pub use address::{Address, AddressType};
+pub use bip32::{
+    DerivedKey, ExtendedPrivKey, ExtendedPubKey, KeyPair, KeyPairBuilder, MasterExtendedKeys,
+};
 /// Re-exported for convenience.
 ///
 /// ```
@@ -18,10 +62,17 @@ pub use bip32::{KeyPair, MasterExtendedKeys};
 pub use bip39::{MnemonicSize, Seed, SeedBuilder};
 pub use display::DisplayLayout;
 pub use error::Error;
-pub use network::Network;
+pub use mnemonic::Mnemonic;
+pub use network::{Network, NetworkParams};
+pub use path::{ChildNumber, CoinType, DerivationPath, Purpose};
+pub use point::Point;
 pub use private::PrivateKey;
-pub use public::PublicKey;
-pub use signature::{CompactSignature, Signature};
+pub use public::{PublicKey, PublicKeyError};
+pub use signature::{
+    CompactSignature, DataHash, Message, RecoverableSignature, SchnorrSignature, SighashType,
+    SignOptions, Signature,
+};
+pub use xonly::{Parity, XOnlyPublicKey};
 
 use lazy_static::lazy_static;
 
@@ -35,10 +86,10 @@ type Hash520Bits = [u8; 65];
 pub type AddressHash = Hash160Bits;
 /// 32-byte long secret key
 pub type Secret = Hash256Bits;
-/// 32-byte long signable message
-pub type Message = Hash256Bits;
 /// 32-byte long chain code
 pub type ChainCode = Hash256Bits;
+/// First 4 bytes of the `ripemd160(sha256(public))` of an extended key
+pub type Fingerprint = Hash32Bits;
 
 lazy_static! {
     pub static ref SECP256K1: secp256k1::Secp256k1<secp256k1::All> = secp256k1::Secp256k1::new();
@@ -0,0 +1,167 @@
+//! The Bitcoin signed message protocol of Bitcoin Core's `signmessage` and
+//! `verifymessage`, as extended by [BIP137] to SegWit addresses.
+//!
+//! The message is framed behind a magic prefix and hashed with double
+//! SHA-256. The signature is a 65-byte compact recoverable signature in
+//! base64, whose header byte tells how to rebuild the address from the
+//! recovered key.
+//!
+//! [BIP137]: https://github.com/bitcoin/bips/blob/master/bip-0137.mediawiki
+
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+
+use crate::{
+    Address, AddressType, CompactSignature, DataHash, Error, Message, PrivateKey, PublicKey,
+    SECP256K1,
+};
+
+const MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+/// The hash that is signed for `message`: the double SHA-256 of the magic
+/// prefix, the message length as a varint and the message.
+pub fn signed_message_hash(message: &str) -> Message {
+    let mut data = MAGIC.to_vec();
+    let len = message.len();
+    if len < 0xfd {
+        data.push(len as u8);
+    } else if len <= 0xffff {
+        data.push(0xfd);
+        data.extend_from_slice(&(len as u16).to_le_bytes());
+    } else if len <= 0xffff_ffff {
+        data.push(0xfe);
+        data.extend_from_slice(&(len as u32).to_le_bytes());
+    } else {
+        data.push(0xff);
+        data.extend_from_slice(&(len as u64).to_le_bytes());
+    }
+    data.extend_from_slice(message.as_bytes());
+    DataHash::DoubleSha256.hash(&data)
+}
+
+impl PrivateKey {
+    /// Sign `message` as `signmessage` does, returning the base64 signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Address, KeyPairBuilder};
+    ///
+    /// let keys = KeyPairBuilder::new().build().unwrap();
+    /// let address = Address::p2pkh(keys.pubkey(), keys.privkey().network);
+    ///
+    /// let signature = keys.privkey().sign_message("hello").unwrap();
+    /// assert!(address.verify_message("hello", &signature).unwrap());
+    /// ```
+    pub fn sign_message(&self, message: &str) -> Result<String, Error> {
+        let signature = self.sign_compact(&signed_message_hash(message))?;
+        Ok(signature.to_base64())
+    }
+}
+
+impl Address {
+    /// Check a base64 `signature` of `message` against this address.
+    ///
+    /// P2PKH, P2SH-P2WPKH and P2WPKH addresses can be verified. A malformed
+    /// signature is an error, while a well-formed one by another key is
+    /// `Ok(false)`.
+    pub fn verify_message(&self, message: &str, signature: &str) -> Result<bool, Error> {
+        if !matches!(
+            self.address_type(),
+            AddressType::P2pkh | AddressType::P2sh | AddressType::P2wpkh
+        ) {
+            return Err(Error::InvalidAddress);
+        }
+
+        let signature = CompactSignature::from_base64(signature)?;
+        if !(27..=42).contains(&signature[0]) {
+            return Err(Error::InvalidSignature);
+        }
+        let header = signature[0] - 27;
+        let recovery_id = RecoveryId::from_i32(i32::from(header % 4))?;
+        let signature = RecoverableSignature::from_compact(&signature[1..], recovery_id)?;
+
+        let hash = signed_message_hash(message).to_secp();
+        let public = match SECP256K1.recover(&hash, &signature) {
+            Ok(public) => public,
+            Err(_) => return Ok(false),
+        };
+        // Headers from 31 on are for compressed keys.
+        let public = if header >= 4 {
+            PublicKey::Compressed(public.serialize())
+        } else {
+            PublicKey::Standard(public.serialize_uncompressed())
+        };
+
+        let signer = Address::from_public(&public, self.address_type(), self.network())?;
+        Ok(signer == *self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Network;
+
+    #[test]
+    fn message_hash() {
+        assert_eq!(
+            signed_message_hash("Hello, world!").to_hex(),
+            "02d6c0643e40b0db549cbbd7eb47dcab71a59d7017199ebde6b272f28fbbf95f"
+        );
+        // A length over 252 takes a 3-byte varint.
+        assert_eq!(
+            signed_message_hash(&"a".repeat(300)).to_hex(),
+            "3ec158a43b80359df647352dac1d37dbf26a94e5f06e5790760290c75cd11dc0"
+        );
+    }
+
+    #[test]
+    fn sign_and_verify() -> Result<(), Error> {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        for compressed in &[true, false] {
+            let private = PrivateKey {
+                network: Network::Mainnet,
+                secret,
+                compressed: *compressed,
+            };
+            let public = private.public_key()?;
+            let signature = private.sign_message("Hello, world!")?;
+
+            let address = Address::p2pkh(&public, Network::Mainnet);
+            assert!(address.verify_message("Hello, world!", &signature)?);
+            assert!(!address.verify_message("Hello, world?", &signature)?);
+            // The header commits to the key's form.
+            let other = PrivateKey {
+                compressed: !compressed,
+                ..private.clone()
+            };
+            assert!(
+                !address.verify_message("Hello, world!", &other.sign_message("Hello, world!")?)?
+            );
+        }
+
+        let private = PrivateKey {
+            network: Network::Mainnet,
+            secret,
+            compressed: true,
+        };
+        let public = private.public_key()?;
+        let signature = private.sign_message("segwit")?;
+        assert!(Address::p2wpkh(&public, Network::Mainnet)?.verify_message("segwit", &signature)?);
+        assert!(
+            Address::p2sh_wpkh(&public, Network::Mainnet)?.verify_message("segwit", &signature)?
+        );
+
+        let address = Address::p2pkh(&public, Network::Mainnet);
+        assert!(matches!(
+            address.verify_message("segwit", "not base64!"),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(Address::p2tr(&public, None, Network::Mainnet)?
+            .verify_message("segwit", &signature)
+            .is_err());
+        Ok(())
+    }
+}
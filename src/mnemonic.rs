@@ -0,0 +1,293 @@
+use std::fmt;
+use std::iter::Map;
+use std::ops::Index;
+use std::slice;
+use std::str;
+
+use crate::bip39::{self, Bip39Error, Language};
+use crate::entropy::{ENTROPY_LEN_STEP, MAX_ENTROPY_LEN, MIN_ENTROPY_LEN};
+
+/// A validated BIP39 mnemonic in one wordlist language.
+///
+/// A `Mnemonic` can only be built from entropy or from a phrase that passes
+/// every BIP39 check (word count, wordlist membership and checksum), so code
+/// holding one never has to validate it again.
+///
+/// Its `Debug` output never includes the words.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::Mnemonic;
+///
+/// let mnemonic: Mnemonic = "abandon abandon abandon abandon abandon abandon \
+///     abandon abandon abandon abandon abandon about"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(mnemonic.len(), 12);
+/// assert_eq!(mnemonic.word(0), Some("abandon"));
+/// assert_eq!(&mnemonic[11], "about");
+/// assert_eq!(mnemonic.checksum_word(), "about");
+/// assert_eq!(mnemonic.entropy(), &[0u8; 16][..]);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    words: Vec<String>,
+    language: Language,
+    entropy: Vec<u8>,
+}
+
+impl Mnemonic {
+    /// Encode 16 to 32 bytes of entropy, in steps of 4 bytes, as an English
+    /// mnemonic.
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, Bip39Error> {
+        Self::from_entropy_in(Language::English, entropy)
+    }
+
+    /// Encode entropy as a mnemonic in `language`.
+    ///
+    /// Fails with `Bip39Error::InvalidEntropyLength` unless `entropy` is 16,
+    /// 20, 24, 28 or 32 bytes long.
+    pub fn from_entropy_in(language: Language, entropy: &[u8]) -> Result<Self, Bip39Error> {
+        let len = entropy.len();
+        if !(MIN_ENTROPY_LEN..=MAX_ENTROPY_LEN).contains(&len) || len % ENTROPY_LEN_STEP != 0 {
+            return Err(Bip39Error::InvalidEntropyLength(len));
+        }
+        Ok(Mnemonic {
+            words: bip39::entropy_to_mnemonic(entropy, language)?,
+            language,
+            entropy: entropy.to_vec(),
+        })
+    }
+
+    /// Parse a whitespace-separated phrase, detecting its language.
+    pub fn from_phrase(phrase: &str) -> Result<Self, Bip39Error> {
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        Self::from_words_in(bip39::detect_language(&words)?, &words)
+    }
+
+    /// Parse a whitespace-separated phrase in `language`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::bip39::{Bip39Error, Language};
+    /// use keymaker::Mnemonic;
+    ///
+    /// let phrase = "abandon ".repeat(11);
+    /// assert!(matches!(
+    ///     Mnemonic::from_phrase_in(Language::English, &phrase),
+    ///     Err(Bip39Error::InvalidWordCount(11))
+    /// ));
+    /// assert!(matches!(
+    ///     Mnemonic::from_phrase_in(Language::English, &(phrase + "zoo")),
+    ///     Err(Bip39Error::ChecksumMismatch)
+    /// ));
+    /// ```
+    pub fn from_phrase_in(language: Language, phrase: &str) -> Result<Self, Bip39Error> {
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        Self::from_words_in(language, &words)
+    }
+
+    fn from_words_in<S: AsRef<str>>(language: Language, words: &[S]) -> Result<Self, Bip39Error> {
+        let entropy = bip39::mnemonic_to_entropy(words, language)?;
+        Self::from_entropy_in(language, &entropy)
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// The raw entropy the mnemonic encodes.
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    /// The word at position `i`, counting from zero.
+    pub fn word(&self, i: usize) -> Option<&str> {
+        self.words.get(i).map(String::as_str)
+    }
+
+    /// The last word, which carries the checksum bits.
+    pub fn checksum_word(&self) -> &str {
+        &self.words[self.words.len() - 1]
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Always `false`, since a valid mnemonic has at least 12 words.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn iter(&self) -> Words<'_> {
+        self.words.iter().map(String::as_str)
+    }
+
+    /// The wordlist index of every word, which is the same in all languages.
+    pub fn indices(&self) -> Result<Vec<usize>, Bip39Error> {
+        bip39::mnemonic_to_indices(&self.words, self.language)
+    }
+
+    /// The same entropy, and therefore the same indices and checksum, in
+    /// another language.
+    ///
+    /// BIP39 stretches the mnemonic *sentence* into the seed, so a translated
+    /// mnemonic produces a different seed in wallets that accept it as-is. Use
+    /// it to read or transcribe a backup, and reencode it back before
+    /// restoring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::bip39::{Language, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let spanish = seed.mnemonic.reencode(Language::Spanish).unwrap();
+    /// assert_eq!(spanish.reencode(Language::English).unwrap(), seed.mnemonic);
+    /// ```
+    pub fn reencode(&self, language: Language) -> Result<Self, Bip39Error> {
+        Self::from_entropy_in(language, &self.entropy)
+    }
+
+    /// Show only the word numbers, one per line, e.g. to print a blank backup
+    /// card or ask for specific words back without revealing any.
+    ///
+    /// ```
+    /// use keymaker::Mnemonic;
+    ///
+    /// let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+    /// assert!(mnemonic.masked().starts_with(" 1. ****\n 2. ****\n"));
+    /// ```
+    pub fn masked(&self) -> String {
+        (1..=self.words.len())
+            .map(|n| format!("{:>2}. ****\n", n))
+            .collect()
+    }
+}
+
+/// Iterator over the words of a [Mnemonic](Mnemonic).
+pub type Words<'a> = Map<slice::Iter<'a, String>, fn(&String) -> &str>;
+
+impl<'a> IntoIterator for &'a Mnemonic {
+    type Item = &'a str;
+    type IntoIter = Words<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Index<usize> for Mnemonic {
+    type Output = str;
+
+    fn index(&self, i: usize) -> &str {
+        &self.words[i]
+    }
+}
+
+impl AsRef<[String]> for Mnemonic {
+    fn as_ref(&self) -> &[String] {
+        &self.words
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    /// The phrase, with words separated as BIP39 specifies for the language.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = match self.language {
+            Language::Japanese => "\u{3000}",
+            _ => " ",
+        };
+        self.words.join(separator).fmt(f)
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Mnemonic")
+            .field("language", &self.language)
+            .field("words", &self.words.len())
+            .finish()
+    }
+}
+
+impl str::FromStr for Mnemonic {
+    type Err = Bip39Error;
+
+    fn from_str(s: &str) -> Result<Self, Bip39Error> {
+        Self::from_phrase(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn mnemonic_accessors() -> Result<(), Bip39Error> {
+        let mnemonic = Mnemonic::from_entropy(&[0xffu8; 32])?;
+        assert_eq!(mnemonic.len(), 24);
+        assert_eq!(mnemonic.iter().filter(|w| *w == "zoo").count(), 23);
+        assert_eq!(mnemonic.checksum_word(), "vote");
+        assert_eq!(mnemonic.word(24), None);
+        assert_eq!(mnemonic.to_string().parse::<Mnemonic>()?, mnemonic);
+        assert_eq!(
+            format!("{:?}", mnemonic),
+            "Mnemonic { language: English, words: 24 }"
+        );
+        assert_eq!(mnemonic.masked().lines().nth(23), Some("24. ****"));
+        Ok(())
+    }
+
+    #[test]
+    fn mnemonic_rejects_invalid_entropy_lengths() {
+        for len in &[0, 4, 12, 17, 30, 36] {
+            assert!(matches!(
+                Mnemonic::from_entropy(&vec![0x5au8; *len]),
+                Err(Bip39Error::InvalidEntropyLength(l)) if l == *len
+            ));
+        }
+        for len in &[16, 20, 24, 28, 32] {
+            assert_eq!(
+                Mnemonic::from_entropy(&vec![0x5au8; *len]).unwrap().len(),
+                len * 3 / 4
+            );
+        }
+    }
+
+    #[test]
+    fn mnemonic_rejects_invalid_phrases() -> Result<(), Bip39Error> {
+        let mnemonic = Mnemonic::from_entropy(&[0x42u8; 16])?;
+        let mut words: Vec<String> = mnemonic.iter().map(str::to_string).collect();
+
+        words[3] = "notaword".to_string();
+        assert!(matches!(
+            Mnemonic::from_phrase_in(Language::English, &words.join(" ")),
+            Err(Bip39Error::UnknownWord(ref w)) if w == "notaword"
+        ));
+
+        // Flip a checksum bit of the last word, leaving the entropy intact.
+        let mut indices = mnemonic.indices()?;
+        indices[11] ^= 1;
+        let tampered = bip39::indices_to_mnemonic(&indices, Language::English)?;
+        assert!(matches!(
+            Mnemonic::from_phrase_in(Language::English, &tampered.join(" ")),
+            Err(Bip39Error::ChecksumMismatch)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn japanese_phrase_round_trip() -> Result<(), Bip39Error> {
+        let mnemonic = Mnemonic::from_entropy_in(Language::Japanese, &[7u8; 20])?;
+        let phrase = mnemonic.to_string();
+        assert!(phrase.contains('\u{3000}'));
+        assert_eq!(Mnemonic::from_phrase(&phrase)?, mnemonic);
+        Ok(())
+    }
+}
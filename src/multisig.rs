@@ -0,0 +1,188 @@
+//! Bare m-of-n multisig scripts with keys sorted as in [BIP67], so every
+//! cosigner derives the same script and address whatever order they list
+//! the keys in.
+//!
+//! [BIP67]: https://github.com/bitcoin/bips/blob/master/bip-0067.mediawiki
+
+use thiserror::Error;
+
+use crate::address::OP_1;
+use crate::crypto;
+use crate::{Address, Error, Network, PublicKey};
+
+const OP_CHECKMULTISIG: u8 = 0xae;
+/// Keys are counted with `OP_1` to `OP_16`.
+const MAX_KEYS: usize = 16;
+/// The largest script a P2SH input may push.
+const MAX_P2SH_SCRIPT_LEN: usize = 520;
+
+/// Error originating from [multisig](crate::multisig) module.
+#[derive(Error, Debug, PartialEq)]
+pub enum MultisigError {
+    #[error("Invalid threshold {0} of {1} keys")]
+    InvalidThreshold(usize, usize),
+    #[error("BIP67 requires compressed keys")]
+    UncompressedKey,
+    #[error("Script of {0} bytes is too large for P2SH")]
+    ScriptTooLarge(usize),
+    #[error(transparent)]
+    Address(#[from] Error),
+}
+
+/// An m-of-n multisig policy over keys in BIP67 order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Multisig {
+    threshold: usize,
+    keys: Vec<PublicKey>,
+}
+
+impl Multisig {
+    /// Require `threshold` signatures out of `keys`, which are sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::multisig::Multisig;
+    /// use keymaker::{Network, PublicKey};
+    ///
+    /// let keys: Vec<PublicKey> = [
+    ///     "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8",
+    ///     "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f",
+    /// ]
+    /// .iter()
+    /// .map(|key| key.parse().unwrap())
+    /// .collect();
+    ///
+    /// let multisig = Multisig::new(2, &keys).unwrap();
+    /// assert_eq!(multisig.keys()[0], keys[1]);
+    /// assert_eq!(
+    ///     multisig.p2sh_address(Network::Mainnet).unwrap().to_string(),
+    ///     "39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z"
+    /// );
+    /// ```
+    pub fn new(threshold: usize, keys: &[PublicKey]) -> Result<Self, MultisigError> {
+        if threshold == 0 || threshold > keys.len() || keys.len() > MAX_KEYS {
+            return Err(MultisigError::InvalidThreshold(threshold, keys.len()));
+        }
+        if keys.iter().any(|key| matches!(key, PublicKey::Standard(_))) {
+            return Err(MultisigError::UncompressedKey);
+        }
+        let mut keys = keys.to_vec();
+        keys.sort();
+        Ok(Multisig { threshold, keys })
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// The keys in BIP67 order.
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    /// `OP_m <key>... OP_n OP_CHECKMULTISIG`, the redeem script of a P2SH
+    /// output or the witness script of a P2WSH output.
+    pub fn script(&self) -> Vec<u8> {
+        let mut script = vec![OP_1 - 1 + self.threshold as u8];
+        for key in &self.keys {
+            script.push(key.len() as u8);
+            script.extend_from_slice(key);
+        }
+        script.push(OP_1 - 1 + self.keys.len() as u8);
+        script.push(OP_CHECKMULTISIG);
+        script
+    }
+
+    /// The P2SH address of the [script](Multisig::script).
+    ///
+    /// Fails if the script is over the 520 bytes P2SH can spend, which 16
+    /// keys are.
+    pub fn p2sh_address(&self, network: Network) -> Result<Address, MultisigError> {
+        let script = self.script();
+        if script.len() > MAX_P2SH_SCRIPT_LEN {
+            return Err(MultisigError::ScriptTooLarge(script.len()));
+        }
+        Ok(Address::p2sh(crypto::hash160(&script), network))
+    }
+
+    /// The P2WSH address of the [script](Multisig::script).
+    pub fn p2wsh_address(&self, network: Network) -> Result<Address, MultisigError> {
+        Ok(Address::p2wsh(crypto::sha256(&self.script()), network)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn keys(hex: &[&str]) -> Vec<PublicKey> {
+        hex.iter().map(|key| key.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn bip67_vectors() -> Result<(), MultisigError> {
+        let cases = [
+            (
+                keys(&[
+                    "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8",
+                    "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f",
+                ]),
+                "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae",
+                "39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z",
+            ),
+            (
+                keys(&[
+                    "02632b12f4ac5b1d1b72b2a3b508c19172de44f6f46bcee50ba33f3f9291e47ed0",
+                    "027735a29bae7780a9755fae7a1c4374c656ac6a69ea9f3697fda61bb99a4f3e77",
+                    "02e2cc6bd5f45edd43bebe7cb9b675f0ce9ed3efe613b177588290ad188d11b404",
+                ]),
+                "522102632b12f4ac5b1d1b72b2a3b508c19172de44f6f46bcee50ba33f3f9291e47ed021027735a29bae7780a9755fae7a1c4374c656ac6a69ea9f3697fda61bb99a4f3e772102e2cc6bd5f45edd43bebe7cb9b675f0ce9ed3efe613b177588290ad188d11b40453ae",
+                "3CKHTjBKxCARLzwABMu9yD85kvtm7WnMfH",
+            ),
+        ];
+        for (keys, script, address) in cases.iter() {
+            let mut reversed = keys.clone();
+            reversed.reverse();
+            for list in &[keys, &reversed] {
+                let multisig = Multisig::new(2, list)?;
+                assert_eq!(hex::encode(multisig.script()), *script);
+                assert_eq!(
+                    multisig.p2sh_address(Network::Mainnet)?.to_string(),
+                    *address
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn policy_limits() -> Result<(), MultisigError> {
+        let key = keys(&["02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8"]);
+        assert_eq!(
+            Multisig::new(0, &key),
+            Err(MultisigError::InvalidThreshold(0, 1))
+        );
+        assert_eq!(
+            Multisig::new(2, &key),
+            Err(MultisigError::InvalidThreshold(2, 1))
+        );
+        assert_eq!(
+            Multisig::new(1, &[key[0].decompress()?]),
+            Err(MultisigError::UncompressedKey)
+        );
+
+        let sixteen = vec![key[0].clone(); 16];
+        let multisig = Multisig::new(1, &sixteen)?;
+        assert_eq!(
+            multisig.p2sh_address(Network::Mainnet),
+            Err(MultisigError::ScriptTooLarge(547))
+        );
+        assert!(multisig
+            .p2wsh_address(Network::Mainnet)?
+            .to_string()
+            .starts_with("bc1q"));
+        Ok(())
+    }
+}
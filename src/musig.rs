@@ -0,0 +1,140 @@
+//! [MuSig2] key aggregation, the first step of a collaborative taproot
+//! key-path spend.
+//!
+//! Each key is weighted by a coefficient hashed from the whole key list, so
+//! no signer can pick a key that cancels out the others. The second distinct
+//! key gets a coefficient of 1, which saves a multiplication. The aggregate
+//! depends on the order of the keys, so sort them first where that matters.
+//!
+//! [MuSig2]: https://github.com/bitcoin/bips/blob/master/bip-0327.mediawiki
+
+use secp256k1::key;
+
+use crate::crypto;
+use crate::scalar::Scalar256;
+use crate::{Error, PublicKey, XOnlyPublicKey, SECP256K1};
+
+/// The aggregate of a list of public keys.
+#[derive(Debug, Clone)]
+pub struct AggregatedKey {
+    public: PublicKey,
+    coefficients: Vec<[u8; 32]>,
+}
+
+impl AggregatedKey {
+    /// The aggregate key, compressed.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// The aggregate key in the x-only form a taproot output commits to.
+    pub fn x_only(&self) -> Result<XOnlyPublicKey, Error> {
+        Ok(self.public.x_only()?.0)
+    }
+
+    /// The coefficient of the key at `index` in the aggregated list.
+    pub fn coefficient(&self, index: usize) -> Option<&[u8; 32]> {
+        self.coefficients.get(index)
+    }
+}
+
+impl PublicKey {
+    /// Aggregate `keys` with the MuSig2 coefficient scheme.
+    ///
+    /// Fails with `Error::InvalidPublic` if `keys` is empty or the keys sum
+    /// to the point at infinity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Network, PrivateKey, PublicKey};
+    ///
+    /// let alice = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let bob = PrivateKey::from_slice(&[2u8; 32], Network::Mainnet).unwrap();
+    /// let keys = [alice.public_key().unwrap(), bob.public_key().unwrap()];
+    ///
+    /// let aggregate = PublicKey::aggregate(&keys).unwrap();
+    /// assert_eq!(aggregate.coefficient(1).unwrap()[31], 1);
+    /// assert_eq!(aggregate.x_only().unwrap().len(), 32);
+    /// ```
+    pub fn aggregate(keys: &[PublicKey]) -> Result<AggregatedKey, Error> {
+        let keys = keys
+            .iter()
+            .map(PublicKey::compressed)
+            .collect::<Result<Vec<_>, _>>()?;
+        let first = keys.first().ok_or(Error::InvalidPublic)?;
+        let second = keys.iter().find(|key| *key != first);
+
+        let list_hash = crypto::tagged_hash("KeyAgg list", &keys.concat());
+        let one = Scalar256::ONE.to_bytes();
+
+        let mut coefficients = vec![];
+        let mut points = vec![];
+        for key in keys.iter() {
+            let coefficient = if Some(key) == second {
+                one
+            } else {
+                let mut data = list_hash.to_vec();
+                data.extend_from_slice(key);
+                let hash = crypto::tagged_hash("KeyAgg coefficient", &data);
+                Scalar256::from_bytes_mod_order(&hash).to_bytes()
+            };
+            let mut point = key::PublicKey::from_slice(key)?;
+            point.mul_assign(&SECP256K1, &coefficient)?;
+            coefficients.push(coefficient);
+            points.push(point);
+        }
+
+        let points: Vec<_> = points.iter().collect();
+        let sum = key::PublicKey::combine_keys(&points).map_err(|_| Error::InvalidPublic)?;
+        Ok(AggregatedKey {
+            public: PublicKey::Compressed(sum.serialize()),
+            coefficients,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn bip327_key_agg_vectors() -> Result<(), Error> {
+        let keys: Vec<PublicKey> = [
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "03dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "023590a94e768f8e1815c2f24b4d80a8e3149316c3518ce7b7ad338368d038ca66",
+        ]
+        .iter()
+        .map(|key| key.parse())
+        .collect::<Result<_, _>>()?;
+
+        let cases = [
+            (
+                vec![0, 1, 2],
+                "90539eede565f5d054f32cc0c220126889ed1e5d193baf15aef344fe59d4610c",
+            ),
+            (
+                vec![2, 1, 0],
+                "6204de8b083426dc6eaf9502d27024d53fc826bf7d2012148a0575435df54b2b",
+            ),
+            (
+                vec![0, 0, 0],
+                "b436e3bad62b8cd409969a224731c193d051162d8c5ae8b109306127da3aa935",
+            ),
+            (
+                vec![0, 0, 1, 1],
+                "69bc22bfa5d106306e48a20679de1d7389386124d07571d0d872686028c26a3e",
+            ),
+        ];
+        for (indices, expected) in cases.iter() {
+            let list: Vec<PublicKey> = indices.iter().map(|&i| keys[i].clone()).collect();
+            let aggregate = PublicKey::aggregate(&list)?;
+            assert_eq!(aggregate.x_only()?.to_string(), *expected);
+        }
+
+        assert!(PublicKey::aggregate(&[]).is_err());
+        Ok(())
+    }
+}
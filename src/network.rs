@@ -1,5 +1,347 @@
-#[derive(Debug, PartialEq, Clone, Copy)]
+use lazy_static::lazy_static;
+use std::sync::RwLock;
+use std::{fmt, str};
+
+use crate::Error;
+
+/// A chain whose keys and addresses this crate encodes.
+///
+/// Some variants only exist with their feature, and more may be added, so
+/// a `match` outside the crate needs a wildcard arm whatever features are
+/// enabled.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[non_exhaustive]
 pub enum Network {
     Mainnet,
     Testnet,
+    /// A local regression test network. It shares the testnet version bytes,
+    /// so only bech32 addresses tell it apart, and Base58Check strings parse
+    /// as [Testnet](Network::Testnet).
+    Regtest,
+    /// Litecoin mainnet. Its extended keys use the Bitcoin mainnet version
+    /// bytes, as Litecoin Core does, so they parse as
+    /// [Mainnet](Network::Mainnet).
+    Litecoin,
+    /// Dogecoin mainnet, which has no SegWit addresses.
+    Dogecoin,
+    /// Dash mainnet. Its extended keys use the Bitcoin mainnet version
+    /// bytes, so they parse as [Mainnet](Network::Mainnet).
+    #[cfg(feature = "dash")]
+    Dash,
+    /// Zcash mainnet, for transparent `t1` and `t3` addresses only. Its
+    /// private keys and extended keys use the Bitcoin mainnet version bytes,
+    /// so they parse as [Mainnet](Network::Mainnet).
+    #[cfg(feature = "zcash")]
+    Zcash,
+    /// Any other chain with Bitcoin-style keys and addresses. Strings only
+    /// parse as one once it is [registered](Network::register).
+    Custom(&'static NetworkParams),
+}
+
+/// The version bytes and prefixes that set the keys and addresses of one
+/// chain apart.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{Address, Network, NetworkParams, PrivateKey};
+///
+/// static EXAMPLE: NetworkParams = NetworkParams {
+///     name: "examplecoin",
+///     wif: 0x99,
+///     p2pkh: &[0x21],
+///     p2sh: &[0x22],
+///     bech32_hrp: Some("ex"),
+///     xprv: [0x04, 0x88, 0xad, 0xe4],
+///     xpub: [0x04, 0x88, 0xb2, 0x1e],
+///     coin_type: 9999,
+/// };
+///
+/// let network = Network::register(&EXAMPLE).unwrap();
+/// let private = PrivateKey::from_slice(&[1u8; 32], network).unwrap();
+/// let address = Address::p2pkh(&private.public_key().unwrap(), network);
+/// assert_eq!(address.to_string().parse::<Address>().unwrap().network(), network);
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct NetworkParams {
+    /// The lowercase name of the chain, unique among registered chains.
+    pub name: &'static str,
+    /// The version byte of private keys in Wallet Import Format.
+    pub wif: u8,
+    /// The Base58Check version of P2PKH addresses, a single byte on most
+    /// chains.
+    pub p2pkh: &'static [u8],
+    /// The Base58Check version of P2SH addresses.
+    pub p2sh: &'static [u8],
+    /// The human-readable part of SegWit addresses, or `None` for a chain
+    /// without SegWit.
+    pub bech32_hrp: Option<&'static str>,
+    /// The version of serialized extended private keys.
+    pub xprv: [u8; 4],
+    /// The version of serialized extended public keys.
+    pub xpub: [u8; 4],
+    /// The [SLIP-0044] coin type, 1 for every test network.
+    ///
+    /// [SLIP-0044]: https://github.com/satoshilabs/slips/blob/master/slip-0044.md
+    pub coin_type: u32,
+}
+
+const XPRV_MAINNET: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+const XPUB_MAINNET: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const XPRV_TESTNET: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+const XPUB_TESTNET: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+
+static MAINNET: NetworkParams = NetworkParams {
+    name: "mainnet",
+    wif: 0x80,
+    p2pkh: &[0x00],
+    p2sh: &[0x05],
+    bech32_hrp: Some("bc"),
+    xprv: XPRV_MAINNET,
+    xpub: XPUB_MAINNET,
+    coin_type: 0,
+};
+
+static TESTNET: NetworkParams = NetworkParams {
+    name: "testnet",
+    wif: 0xef,
+    p2pkh: &[0x6f],
+    p2sh: &[0xc4],
+    bech32_hrp: Some("tb"),
+    xprv: XPRV_TESTNET,
+    xpub: XPUB_TESTNET,
+    coin_type: 1,
+};
+
+static REGTEST: NetworkParams = NetworkParams {
+    name: "regtest",
+    bech32_hrp: Some("bcrt"),
+    ..TESTNET
+};
+
+static LITECOIN: NetworkParams = NetworkParams {
+    name: "litecoin",
+    wif: 0xb0,
+    p2pkh: &[0x30],
+    p2sh: &[0x32],
+    bech32_hrp: Some("ltc"),
+    xprv: XPRV_MAINNET,
+    xpub: XPUB_MAINNET,
+    coin_type: 2,
+};
+
+static DOGECOIN: NetworkParams = NetworkParams {
+    name: "dogecoin",
+    wif: 0x9e,
+    p2pkh: &[0x1e],
+    p2sh: &[0x16],
+    bech32_hrp: None,
+    xprv: [0x02, 0xfa, 0xc3, 0x98],
+    xpub: [0x02, 0xfa, 0xca, 0xfd],
+    coin_type: 3,
+};
+
+#[cfg(feature = "dash")]
+static DASH: NetworkParams = NetworkParams {
+    name: "dash",
+    wif: 0xcc,
+    p2pkh: &[0x4c],
+    p2sh: &[0x10],
+    bech32_hrp: None,
+    xprv: XPRV_MAINNET,
+    xpub: XPUB_MAINNET,
+    coin_type: 5,
+};
+
+#[cfg(feature = "zcash")]
+static ZCASH: NetworkParams = NetworkParams {
+    name: "zcash",
+    wif: 0x80,
+    p2pkh: &[0x1c, 0xb8],
+    p2sh: &[0x1c, 0xbd],
+    bech32_hrp: None,
+    xprv: XPRV_MAINNET,
+    xpub: XPUB_MAINNET,
+    coin_type: 133,
+};
+
+/// The built-in networks, in the order parsing tries them.
+const BUILT_IN: &[Network] = &[
+    Network::Mainnet,
+    Network::Testnet,
+    Network::Regtest,
+    Network::Litecoin,
+    Network::Dogecoin,
+    #[cfg(feature = "dash")]
+    Network::Dash,
+    #[cfg(feature = "zcash")]
+    Network::Zcash,
+];
+
+lazy_static! {
+    static ref REGISTRY: RwLock<Vec<&'static NetworkParams>> = RwLock::new(vec![]);
+}
+
+impl Network {
+    /// The version bytes and prefixes of the network.
+    pub fn params(&self) -> &'static NetworkParams {
+        match self {
+            Network::Mainnet => &MAINNET,
+            Network::Testnet => &TESTNET,
+            Network::Regtest => &REGTEST,
+            Network::Litecoin => &LITECOIN,
+            Network::Dogecoin => &DOGECOIN,
+            #[cfg(feature = "dash")]
+            Network::Dash => &DASH,
+            #[cfg(feature = "zcash")]
+            Network::Zcash => &ZCASH,
+            Network::Custom(params) => params,
+        }
+    }
+
+    /// Register a custom chain, so that its keys and addresses parse, and
+    /// return its network.
+    ///
+    /// Registering the same parameters again is a no-op. Fails with
+    /// `Error::InvalidNetwork` if another network already has the name.
+    /// Version bytes may be shared, in which case parsing picks the
+    /// built-in networks first, then custom ones in the order they were
+    /// registered.
+    pub fn register(params: &'static NetworkParams) -> Result<Network, Error> {
+        let mut registry = REGISTRY.write().map_err(|_| Error::InvalidNetwork)?;
+        let taken = BUILT_IN
+            .iter()
+            .map(Network::params)
+            .chain(registry.iter().copied())
+            .find(|registered| registered.name == params.name);
+        match taken {
+            Some(registered) if registered == params => {}
+            Some(_) => return Err(Error::InvalidNetwork),
+            None => registry.push(params),
+        }
+        Ok(Network::Custom(params))
+    }
+
+    /// Every built-in network, then every registered one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::Network;
+    ///
+    /// for network in Network::all() {
+    ///     assert_eq!(network.to_string().parse::<Network>().unwrap(), network);
+    /// }
+    /// assert_eq!("Testnet".parse::<Network>().unwrap(), Network::Testnet);
+    /// ```
+    pub fn all() -> Vec<Network> {
+        let mut networks = BUILT_IN.to_vec();
+        if let Ok(registry) = REGISTRY.read() {
+            networks.extend(registry.iter().map(|params| Network::Custom(params)));
+        }
+        networks
+    }
+
+    /// The first built-in or registered network whose parameters satisfy
+    /// `predicate`.
+    pub(crate) fn find<P>(predicate: P) -> Option<Network>
+    where
+        P: Fn(&NetworkParams) -> bool,
+    {
+        if let Some(network) = BUILT_IN.iter().find(|network| predicate(network.params())) {
+            return Some(*network);
+        }
+        let registry = REGISTRY.read().ok()?;
+        registry
+            .iter()
+            .find(|params| predicate(params))
+            .map(|params| Network::Custom(params))
+    }
+}
+
+/// The name in [NetworkParams].
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.params().name.fmt(f)
+    }
+}
+
+impl str::FromStr for Network {
+    type Err = Error;
+
+    /// Find a built-in or registered network by name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Network::find(|params| params.name.eq_ignore_ascii_case(s)).ok_or(Error::InvalidNetwork)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    static CUSTOM: NetworkParams = NetworkParams {
+        name: "custom",
+        wif: 0x99,
+        p2pkh: &[0x21],
+        p2sh: &[0x22],
+        bech32_hrp: Some("cu"),
+        xprv: [1, 2, 3, 4],
+        xpub: [5, 6, 7, 8],
+        coin_type: 9999,
+    };
+
+    #[test]
+    fn register_and_find() -> Result<(), Error> {
+        let network = Network::register(&CUSTOM)?;
+        assert_eq!(network.params(), &CUSTOM);
+        assert_eq!(Network::register(&CUSTOM)?, network);
+        assert_eq!(Network::find(|params| params.wif == 0x99), Some(network));
+        assert!(Network::all().contains(&network));
+        assert_eq!("CUSTOM".parse::<Network>()?, network);
+        assert_eq!(network.to_string(), "custom");
+
+        // The name is taken, by a built-in network or the first registration.
+        static MAINNET_AGAIN: NetworkParams = NetworkParams {
+            coin_type: 10000,
+            ..MAINNET
+        };
+        static CUSTOM_AGAIN: NetworkParams = NetworkParams {
+            wif: 0x98,
+            ..CUSTOM
+        };
+        assert_eq!(
+            Network::register(&MAINNET_AGAIN),
+            Err(Error::InvalidNetwork)
+        );
+        assert_eq!(Network::register(&CUSTOM_AGAIN), Err(Error::InvalidNetwork));
+
+        // Built-in networks come first, so testnet wins over regtest.
+        assert_eq!(
+            Network::find(|params| params.wif == 0xef),
+            Some(Network::Testnet)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn names() -> Result<(), Error> {
+        let cases = [
+            (Network::Mainnet, "mainnet"),
+            (Network::Testnet, "testnet"),
+            (Network::Regtest, "regtest"),
+            (Network::Litecoin, "litecoin"),
+            (Network::Dogecoin, "dogecoin"),
+        ];
+        for (network, name) in cases.iter() {
+            assert_eq!(network.to_string(), *name);
+            assert_eq!(name.parse::<Network>()?, *network);
+        }
+        assert_eq!(
+            &Network::all()[..cases.len()],
+            &cases.map(|(network, _)| network)[..]
+        );
+        assert_eq!("signet".parse::<Network>(), Err(Error::InvalidNetwork));
+        Ok(())
+    }
 }
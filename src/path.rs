@@ -0,0 +1,572 @@
+use std::fmt;
+use std::slice;
+use std::str;
+
+use crate::bip32::Bip32Error;
+use crate::{AddressType, Network};
+
+pub(crate) const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// A single step of a BIP32 derivation path.
+///
+/// The wrapped index must be below 2^31 for both variants; hardening is
+/// carried by the variant rather than the high bit. The
+/// [normal](ChildNumber::normal) and [hardened](ChildNumber::hardened)
+/// constructors check this, and derivation refuses a child number built
+/// directly with a larger index.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ChildNumber {
+    Normal(u32),
+    Hardened(u32),
+}
+
+impl ChildNumber {
+    /// A normal child, failing with `Bip32Error::InvalidChildNumber` unless
+    /// `index` is below 2^31.
+    pub fn normal(index: u32) -> Result<Self, Bip32Error> {
+        ChildNumber::Normal(index).validate()
+    }
+
+    /// A hardened child, failing with `Bip32Error::InvalidChildNumber`
+    /// unless `index` is below 2^31.
+    pub fn hardened(index: u32) -> Result<Self, Bip32Error> {
+        ChildNumber::Hardened(index).validate()
+    }
+
+    /// The child number itself, if its index is below 2^31.
+    pub(crate) fn validate(self) -> Result<Self, Bip32Error> {
+        match self {
+            ChildNumber::Normal(i) | ChildNumber::Hardened(i) if i >= HARDENED_OFFSET => {
+                Err(Bip32Error::InvalidChildNumber(i))
+            }
+            _ => Ok(self),
+        }
+    }
+
+    pub fn is_hardened(&self) -> bool {
+        matches!(self, ChildNumber::Hardened(_))
+    }
+
+    /// The raw 32-bit index used in derivation, with the hardened bit set.
+    pub fn to_index(&self) -> u32 {
+        match *self {
+            ChildNumber::Normal(i) => i,
+            ChildNumber::Hardened(i) => i | HARDENED_OFFSET,
+        }
+    }
+}
+
+impl From<u32> for ChildNumber {
+    fn from(index: u32) -> Self {
+        if index >= HARDENED_OFFSET {
+            ChildNumber::Hardened(index - HARDENED_OFFSET)
+        } else {
+            ChildNumber::Normal(index)
+        }
+    }
+}
+
+impl fmt::Display for ChildNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChildNumber::Normal(i) => write!(f, "{}", i),
+            ChildNumber::Hardened(i) => write!(f, "{}'", i),
+        }
+    }
+}
+
+impl str::FromStr for ChildNumber {
+    type Err = Bip32Error;
+
+    fn from_str(s: &str) -> Result<Self, Bip32Error> {
+        let invalid = || Bip32Error::InvalidPath(s.to_string());
+
+        let (digits, hardened) = match s.strip_suffix(|c| c == '\'' || c == 'h') {
+            Some(digits) => (digits, true),
+            None => (s, false),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let index: u32 = digits.parse().map_err(|_| invalid())?;
+        if hardened {
+            ChildNumber::hardened(index)
+        } else {
+            ChildNumber::normal(index)
+        }
+    }
+}
+
+/// The purpose level of a multi-account hierarchy, which fixes the script
+/// type of the addresses derived under it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Purpose {
+    /// [BIP44]: legacy pay-to-pubkey-hash (P2PKH).
+    ///
+    /// [BIP44]: https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki
+    Bip44,
+    /// [BIP49]: SegWit nested in pay-to-script-hash (P2SH-P2WPKH).
+    ///
+    /// [BIP49]: https://github.com/bitcoin/bips/blob/master/bip-0049.mediawiki
+    Bip49,
+    /// [BIP84]: native SegWit (P2WPKH).
+    ///
+    /// [BIP84]: https://github.com/bitcoin/bips/blob/master/bip-0084.mediawiki
+    Bip84,
+    /// [CIP-1852]: Cardano Shelley wallets.
+    ///
+    /// [CIP-1852]: https://cips.cardano.org/cips/cip1852/
+    Cip1852,
+}
+
+impl Purpose {
+    /// The hardened index of the purpose level.
+    pub fn to_child_number(self) -> ChildNumber {
+        match self {
+            Purpose::Bip44 => ChildNumber::Hardened(44),
+            Purpose::Bip49 => ChildNumber::Hardened(49),
+            Purpose::Bip84 => ChildNumber::Hardened(84),
+            Purpose::Cip1852 => ChildNumber::Hardened(1852),
+        }
+    }
+
+    /// The Bitcoin script type of the addresses derived under the purpose,
+    /// with P2SH standing for P2SH-P2WPKH as in
+    /// [Address](crate::Address), or `None` for Cardano.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{AddressType, Purpose};
+    ///
+    /// assert_eq!(Purpose::Bip49.address_type(), Some(AddressType::P2sh));
+    /// assert_eq!(Purpose::Cip1852.address_type(), None);
+    /// ```
+    pub fn address_type(self) -> Option<AddressType> {
+        match self {
+            Purpose::Bip44 => Some(AddressType::P2pkh),
+            Purpose::Bip49 => Some(AddressType::P2sh),
+            Purpose::Bip84 => Some(AddressType::P2wpkh),
+            Purpose::Cip1852 => None,
+        }
+    }
+}
+
+/// A coin type registered in [SLIP-0044], the hardened second level of a
+/// BIP44 path.
+///
+/// Coin types parse from their lowercase name or ticker, and a
+/// [Network](Network) converts to the coin type of its coin.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{CoinType, DerivationPath, Purpose};
+///
+/// let coin: CoinType = "eth".parse().unwrap();
+/// let account = DerivationPath::account(Purpose::Bip44, coin, 0).unwrap();
+/// assert_eq!(account.to_string(), "m/44'/60'/0'");
+/// ```
+///
+/// [SLIP-0044]: https://github.com/satoshilabs/slips/blob/master/slip-0044.md
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CoinType {
+    Bitcoin,
+    /// Shared by the test networks of every coin.
+    Testnet,
+    Litecoin,
+    Dogecoin,
+    Dash,
+    Ethereum,
+    EthereumClassic,
+    Cosmos,
+    Zcash,
+    Ripple,
+    BitcoinCash,
+    Stellar,
+    Tron,
+    Solana,
+    Cardano,
+    /// A coin type not listed above, by its index, as used by custom
+    /// [networks](crate::NetworkParams).
+    Other(u32),
+}
+
+/// Every coin type with its SLIP-0044 index, name and ticker.
+const COIN_TYPES: [(CoinType, u32, &str, &str); 15] = [
+    (CoinType::Bitcoin, 0, "bitcoin", "btc"),
+    (CoinType::Testnet, 1, "testnet", "test"),
+    (CoinType::Litecoin, 2, "litecoin", "ltc"),
+    (CoinType::Dogecoin, 3, "dogecoin", "doge"),
+    (CoinType::Dash, 5, "dash", "dash"),
+    (CoinType::Ethereum, 60, "ethereum", "eth"),
+    (CoinType::EthereumClassic, 61, "ethereum-classic", "etc"),
+    (CoinType::Cosmos, 118, "cosmos", "atom"),
+    (CoinType::Zcash, 133, "zcash", "zec"),
+    (CoinType::Ripple, 144, "ripple", "xrp"),
+    (CoinType::BitcoinCash, 145, "bitcoin-cash", "bch"),
+    (CoinType::Stellar, 148, "stellar", "xlm"),
+    (CoinType::Tron, 195, "tron", "trx"),
+    (CoinType::Solana, 501, "solana", "sol"),
+    (CoinType::Cardano, 1815, "cardano", "ada"),
+];
+
+impl CoinType {
+    /// The SLIP-0044 index, before hardening.
+    pub fn index(self) -> u32 {
+        match self {
+            CoinType::Other(index) => index,
+            _ => self.entry().1,
+        }
+    }
+
+    /// The coin type registered at `index`, if it is one of the above.
+    pub fn from_index(index: u32) -> Option<Self> {
+        COIN_TYPES
+            .iter()
+            .find(|(_, i, _, _)| *i == index)
+            .map(|(coin, _, _, _)| *coin)
+    }
+
+    /// The hardened index of the coin type level, or
+    /// `Bip32Error::InvalidChildNumber` for an [Other](CoinType::Other)
+    /// index of 2^31 or more.
+    pub fn to_child_number(self) -> Result<ChildNumber, Bip32Error> {
+        ChildNumber::hardened(self.index())
+    }
+
+    fn entry(self) -> &'static (CoinType, u32, &'static str, &'static str) {
+        COIN_TYPES
+            .iter()
+            .find(|(coin, _, _, _)| *coin == self)
+            .expect("every coin type is registered")
+    }
+}
+
+impl From<Network> for CoinType {
+    fn from(network: Network) -> Self {
+        let index = network.params().coin_type;
+        CoinType::from_index(index).unwrap_or(CoinType::Other(index))
+    }
+}
+
+impl fmt::Display for CoinType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoinType::Other(index) => index.fmt(f),
+            _ => self.entry().2.fmt(f),
+        }
+    }
+}
+
+impl str::FromStr for CoinType {
+    type Err = Bip32Error;
+
+    fn from_str(s: &str) -> Result<Self, Bip32Error> {
+        let name = s.to_lowercase();
+        COIN_TYPES
+            .iter()
+            .find(|(_, _, n, ticker)| *n == name || *ticker == name)
+            .map(|(coin, _, _, _)| *coin)
+            .ok_or_else(|| Bip32Error::UnknownCoinType(s.to_string()))
+    }
+}
+
+/// A BIP32 derivation path such as `m/44'/0'/0'/0/1`.
+///
+/// Hardened steps may be written with either `'` or `h`, and are always
+/// displayed with `'`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{ChildNumber, DerivationPath};
+///
+/// let account: DerivationPath = "m/44h/0h/0h".parse().unwrap();
+/// let receive = account.extend(&"0/1".parse().unwrap());
+///
+/// assert_eq!(receive.to_string(), "m/44'/0'/0'/0/1");
+/// assert_eq!(receive.iter().last(), Some(&ChildNumber::Normal(1)));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    /// The path of the master key itself, `m`.
+    pub fn master() -> Self {
+        DerivationPath(vec![])
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, ChildNumber> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The account path `m/purpose'/coin_type'/account'`. A
+    /// [Network](Network) may be passed for the coin type of its coin.
+    ///
+    /// Fails with `Bip32Error::InvalidChildNumber` if the coin type or
+    /// `account` is 2^31 or more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{DerivationPath, Network, Purpose};
+    ///
+    /// let account = DerivationPath::account(Purpose::Bip84, Network::Mainnet, 0).unwrap();
+    /// assert_eq!(account.to_string(), "m/84'/0'/0'");
+    ///
+    /// let change = DerivationPath::address(Purpose::Bip49, Network::Testnet, 1, true, 5).unwrap();
+    /// assert_eq!(change.to_string(), "m/49'/1'/1'/1/5");
+    /// assert!(DerivationPath::account(Purpose::Bip84, Network::Mainnet, 1 << 31).is_err());
+    /// ```
+    pub fn account<C: Into<CoinType>>(
+        purpose: Purpose,
+        coin: C,
+        account: u32,
+    ) -> Result<Self, Bip32Error> {
+        Ok(DerivationPath(vec![
+            purpose.to_child_number(),
+            coin.into().to_child_number()?,
+            ChildNumber::hardened(account)?,
+        ]))
+    }
+
+    /// The path of a single address under [account](DerivationPath::account),
+    /// on the internal chain for `change` addresses and the external chain
+    /// for receive addresses.
+    ///
+    /// Fails as [account](DerivationPath::account) does, or if `index` is
+    /// 2^31 or more.
+    pub fn address<C: Into<CoinType>>(
+        purpose: Purpose,
+        coin: C,
+        account: u32,
+        change: bool,
+        index: u32,
+    ) -> Result<Self, Bip32Error> {
+        Ok(Self::account(purpose, coin, account)?
+            .child(ChildNumber::Normal(change as u32))
+            .child(ChildNumber::normal(index)?))
+    }
+
+    /// Append a single step to the path.
+    pub fn child(&self, child: ChildNumber) -> Self {
+        let mut path = self.0.clone();
+        path.push(child);
+        DerivationPath(path)
+    }
+
+    /// Append every step of `other` to the path.
+    pub fn extend(&self, other: &DerivationPath) -> Self {
+        let mut path = self.0.clone();
+        path.extend_from_slice(&other.0);
+        DerivationPath(path)
+    }
+}
+
+impl From<Vec<ChildNumber>> for DerivationPath {
+    fn from(path: Vec<ChildNumber>) -> Self {
+        DerivationPath(path)
+    }
+}
+
+impl AsRef<[ChildNumber]> for DerivationPath {
+    fn as_ref(&self) -> &[ChildNumber] {
+        &self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a DerivationPath {
+    type Item = &'a ChildNumber;
+    type IntoIter = slice::Iter<'a, ChildNumber>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for child in &self.0 {
+            write!(f, "/{}", child)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for DerivationPath {
+    type Err = Bip32Error;
+
+    /// Parse an absolute path starting with `m`, or a relative one without it.
+    fn from_str(s: &str) -> Result<Self, Bip32Error> {
+        let mut parts = s.split('/').peekable();
+        if parts.peek() == Some(&"m") {
+            parts.next();
+        }
+
+        parts
+            .map(|part| {
+                part.parse()
+                    .map_err(|_| Bip32Error::InvalidPath(s.to_string()))
+            })
+            .collect::<Result<Vec<ChildNumber>, _>>()
+            .map(DerivationPath)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ExtendedPrivKey;
+    use anyhow::Result;
+
+    #[test]
+    fn purpose_address_types() -> Result<()> {
+        // The all-abandon mnemonic, with the first receive address of each
+        // purpose from BIP44, BIP49 and BIP84.
+        let root: ExtendedPrivKey = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu"
+            .parse()?;
+        for (purpose, expected) in [
+            (Purpose::Bip44, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"),
+            (Purpose::Bip49, "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf"),
+            (Purpose::Bip84, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"),
+        ] {
+            let path = DerivationPath::address(purpose, Network::Mainnet, 0, false, 0)?;
+            let address_type = purpose.address_type().unwrap();
+            let key = root.derive_key(&path)?;
+            assert_eq!(key.address(address_type)?.to_string(), expected);
+        }
+
+        // BIP49 testnet vector.
+        let root: ExtendedPrivKey = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd"
+            .parse()?;
+        let path = DerivationPath::address(Purpose::Bip49, Network::Testnet, 0, false, 0)?;
+        let key = root.derive_key(&path)?;
+        assert_eq!(
+            key.address(Purpose::Bip49.address_type().unwrap())?
+                .to_string(),
+            "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2"
+        );
+
+        assert_eq!(Purpose::Cip1852.address_type(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let path: DerivationPath = "m/44'/0h/0'/0/1".parse().unwrap();
+        assert_eq!(
+            path.as_ref(),
+            &[
+                ChildNumber::Hardened(44),
+                ChildNumber::Hardened(0),
+                ChildNumber::Hardened(0),
+                ChildNumber::Normal(0),
+                ChildNumber::Normal(1),
+            ]
+        );
+        assert_eq!(path.to_string(), "m/44'/0'/0'/0/1");
+        assert_eq!(
+            "m".parse::<DerivationPath>().unwrap(),
+            DerivationPath::master()
+        );
+
+        for bad in &[
+            "",
+            "m/",
+            "m/x",
+            "m/1''",
+            "m/-1",
+            "m//1",
+            "m/2147483648",
+            "n/1",
+        ] {
+            assert!(bad.parse::<DerivationPath>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn purpose_paths() {
+        let cases = [
+            (Purpose::Bip44, "m/44'/0'/3'/0/7"),
+            (Purpose::Bip49, "m/49'/0'/3'/0/7"),
+            (Purpose::Bip84, "m/84'/0'/3'/0/7"),
+        ];
+        for (purpose, expected) in cases.iter() {
+            let path = DerivationPath::address(*purpose, Network::Mainnet, 3, false, 7).unwrap();
+            assert_eq!(path.to_string(), *expected);
+        }
+        assert_eq!(
+            DerivationPath::account(Purpose::Bip84, Network::Testnet, 0)
+                .unwrap()
+                .to_string(),
+            "m/84'/1'/0'"
+        );
+    }
+
+    #[test]
+    fn coin_types() {
+        for (coin, index, name, ticker) in COIN_TYPES.iter() {
+            assert_eq!(coin.index(), *index);
+            assert_eq!(CoinType::from_index(*index), Some(*coin));
+            assert_eq!(name.parse::<CoinType>().unwrap(), *coin);
+            assert_eq!(ticker.to_uppercase().parse::<CoinType>().unwrap(), *coin);
+            assert_eq!(coin.to_string(), *name);
+        }
+        assert_eq!(CoinType::from_index(4), None);
+        assert_eq!(CoinType::Other(4).index(), 4);
+        assert_eq!(CoinType::Other(4).to_string(), "4");
+        assert!("monopoly".parse::<CoinType>().is_err());
+        assert_eq!(
+            DerivationPath::address(Purpose::Bip44, CoinType::Solana, 0, false, 2)
+                .unwrap()
+                .to_string(),
+            "m/44'/501'/0'/0/2"
+        );
+    }
+
+    #[test]
+    fn child_number_index() {
+        assert_eq!(ChildNumber::Hardened(1).to_index(), HARDENED_OFFSET + 1);
+        assert_eq!(
+            ChildNumber::from(HARDENED_OFFSET + 1),
+            ChildNumber::Hardened(1)
+        );
+        assert_eq!(ChildNumber::from(5), ChildNumber::Normal(5));
+    }
+
+    #[test]
+    fn out_of_range_child_numbers() {
+        assert_eq!(ChildNumber::normal(5).unwrap(), ChildNumber::Normal(5));
+        assert_eq!(
+            ChildNumber::hardened(HARDENED_OFFSET - 1).unwrap(),
+            ChildNumber::Hardened(HARDENED_OFFSET - 1)
+        );
+        assert!(matches!(
+            ChildNumber::normal(HARDENED_OFFSET + 5),
+            Err(Bip32Error::InvalidChildNumber(_))
+        ));
+        assert!(ChildNumber::hardened(HARDENED_OFFSET).is_err());
+
+        assert!(
+            DerivationPath::account(Purpose::Bip44, Network::Mainnet, HARDENED_OFFSET).is_err()
+        );
+        assert!(
+            DerivationPath::account(Purpose::Bip44, CoinType::Other(HARDENED_OFFSET), 0).is_err()
+        );
+        assert!(
+            DerivationPath::address(Purpose::Bip44, Network::Mainnet, 0, false, u32::MAX).is_err()
+        );
+    }
+}
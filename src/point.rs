@@ -0,0 +1,153 @@
+//! Points of the secp256k1 curve and their group operations, which split
+//! key generation, pay-to-contract and stealth addresses are built from.
+//!
+//! Secp256k1 has no encoding for the point at infinity, so every operation
+//! that could reach it returns a `Result`.
+
+use secp256k1::bitcoin_hashes::hex::ToHex;
+use secp256k1::key;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::scalar::GENERATOR;
+use crate::{Error, PublicKey, SECP256K1};
+
+/// A point of the secp256k1 curve other than the point at infinity.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Point(key::PublicKey);
+
+impl Point {
+    /// The generator point `G`.
+    pub fn generator() -> Self {
+        Point(key::PublicKey::from_slice(&GENERATOR).unwrap())
+    }
+
+    /// Parse a 33-byte compressed or 65-byte uncompressed point.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        Ok(Point(key::PublicKey::from_slice(data)?))
+    }
+
+    /// `scalar * G`, failing with `Error::InvalidTweak` for a scalar of zero
+    /// or not below the curve order.
+    pub fn mul_generator(scalar: &[u8; 32]) -> Result<Self, Error> {
+        Self::generator().mul(scalar)
+    }
+
+    /// `self + other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::Point;
+    ///
+    /// let mut two = [0u8; 32];
+    /// two[31] = 2;
+    /// let g = Point::generator();
+    /// assert_eq!(g.add(&g).unwrap(), Point::mul_generator(&two).unwrap());
+    /// assert!(g.add(&g.negate()).is_err());
+    /// ```
+    pub fn add(&self, other: &Point) -> Result<Self, Error> {
+        let sum = self.0.combine(&other.0).map_err(|_| Error::InvalidPublic)?;
+        Ok(Point(sum))
+    }
+
+    /// `scalar * self`, failing with `Error::InvalidTweak` for a scalar of
+    /// zero or not below the curve order.
+    pub fn mul(&self, scalar: &[u8; 32]) -> Result<Self, Error> {
+        let mut product = self.0;
+        product
+            .mul_assign(&SECP256K1, scalar)
+            .map_err(|_| Error::InvalidTweak)?;
+        Ok(Point(product))
+    }
+
+    /// `-self`, the point with the same x and the other y.
+    pub fn negate(&self) -> Self {
+        let mut negated = self.0;
+        negated.negate_assign(&SECP256K1);
+        Point(negated)
+    }
+
+    /// The 33-byte compressed encoding.
+    pub fn serialize(&self) -> [u8; 33] {
+        self.0.serialize()
+    }
+
+    /// The 65-byte uncompressed encoding.
+    pub fn serialize_uncompressed(&self) -> [u8; 65] {
+        self.0.serialize_uncompressed()
+    }
+}
+
+impl From<Point> for PublicKey {
+    /// The compressed public key of the point.
+    fn from(point: Point) -> Self {
+        PublicKey::Compressed(point.serialize())
+    }
+}
+
+impl TryFrom<&PublicKey> for Point {
+    type Error = Error;
+
+    fn try_from(public: &PublicKey) -> Result<Self, Error> {
+        Point::from_slice(public)
+    }
+}
+
+impl fmt::Debug for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.serialize().to_hex().fmt(f)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.serialize().to_hex().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::scalar::{Scalar256, CURVE_ORDER, GENERATOR_UNCOMPRESSED};
+    use crate::{Network, PrivateKey};
+
+    #[test]
+    fn group_operations() -> Result<(), Error> {
+        let a = [3u8; 32];
+        let b = [5u8; 32];
+        let sum = Scalar256::from_bytes_mod_order(&a)
+            .add(&Scalar256::from_bytes_mod_order(&b))
+            .to_bytes();
+
+        // aG + bG = (a + b)G, and it matches the public key of a + b.
+        let a_g = Point::mul_generator(&a)?;
+        let b_g = Point::mul_generator(&b)?;
+        assert_eq!(a_g.add(&b_g)?, Point::mul_generator(&sum)?);
+        let private = PrivateKey::from_slice(&sum, Network::Mainnet)?;
+        assert_eq!(PublicKey::from(a_g.add(&b_g)?), private.public_key()?);
+        assert_eq!(Point::try_from(&private.public_key()?)?, a_g.add(&b_g)?);
+
+        // b(aG) = a(bG).
+        assert_eq!(a_g.mul(&b)?, b_g.mul(&a)?);
+
+        let negated = a_g.negate();
+        assert_eq!(negated.serialize()[1..], a_g.serialize()[1..]);
+        assert_ne!(negated.serialize()[0], a_g.serialize()[0]);
+        assert_eq!(negated.negate(), a_g);
+        assert_eq!(a_g.add(&negated).err(), Some(Error::InvalidPublic));
+
+        assert_eq!(
+            Point::generator().serialize_uncompressed(),
+            GENERATOR_UNCOMPRESSED
+        );
+        assert_eq!(
+            Point::from_slice(&GENERATOR_UNCOMPRESSED)?,
+            Point::generator()
+        );
+        assert_eq!(a_g.mul(&[0u8; 32]).err(), Some(Error::InvalidTweak));
+        assert_eq!(a_g.mul(&CURVE_ORDER).err(), Some(Error::InvalidTweak));
+        Ok(())
+    }
+}
@@ -1,17 +1,19 @@
-use crate::crypto;
-use crate::Hash520Bits;
+use crate::crypto::{self, ct};
+use crate::ecdsa;
+use crate::scalar::Scalar256;
 use crate::{
-    CompactSignature, DisplayLayout, Error, Message, Network, Secret, Signature, SECP256K1,
+    CompactSignature, DataHash, DisplayLayout, Error, Message, Network, PublicKey,
+    RecoverableSignature, SchnorrSignature, Secret, SignOptions, Signature, SECP256K1,
 };
 use base58::{FromBase58, ToBase58};
+use rand_core::{OsRng, RngCore};
 use secp256k1::bitcoin_hashes::hex::ToHex;
-use secp256k1::bitcoin_hashes::sha256t::Hash;
 use secp256k1::key;
-use secp256k1::Message as SecpMessage;
-use std::convert::TryInto;
+use secp256k1::schnorrsig;
 use std::fmt;
+use std::str;
 
-#[derive(PartialEq, Clone)]
+#[derive(Clone)]
 pub struct PrivateKey {
     /// The network on which this key is used.
     pub network: Network,
@@ -22,30 +24,247 @@ pub struct PrivateKey {
 }
 
 impl PrivateKey {
-    pub fn sign(&self, message: &Message) -> Result<Signature, Error> {
+    /// Parse a key in Wallet Import Format, detecting its network and
+    /// whether its public key is compressed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Network, PrivateKey};
+    ///
+    /// let private = PrivateKey::from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn").unwrap();
+    /// assert_eq!(private.network, Network::Mainnet);
+    /// assert!(private.compressed);
+    /// ```
+    pub fn from_wif(wif: &str) -> Result<Self, Error> {
+        let data = wif.from_base58().map_err(|_| Error::InvalidPrivate)?;
+        Self::from_layout(&data)
+    }
+
+    /// Use a raw 32-byte secret on `network`, with a compressed public key.
+    ///
+    /// Fails with `Error::InvalidSecret` unless the secret is in `[1, n)`,
+    /// `n` being the curve order.
+    pub fn from_slice(secret: &[u8], network: Network) -> Result<Self, Error> {
+        key::SecretKey::from_slice(secret)?;
+        let mut inner = Secret::default();
+        inner.copy_from_slice(secret);
+        Ok(PrivateKey {
+            network,
+            secret: inner,
+            compressed: true,
+        })
+    }
+
+    /// Parse a raw secret written as 64 hex digits, with or without a `0x`
+    /// prefix, as [from_slice](PrivateKey::from_slice) does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Network, PrivateKey};
+    ///
+    /// let hex = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    /// let private = PrivateKey::from_hex(hex, Network::Mainnet).unwrap();
+    /// assert_eq!(private.to_hex(), &hex[2..]);
+    /// ```
+    pub fn from_hex(s: &str, network: Network) -> Result<Self, Error> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        let secret = ct::decode_hex(digits).map_err(|_| Error::InvalidSecret)?;
+        Self::from_slice(&secret, network)
+    }
+
+    /// Use `scalar` as the secret on `network`, with a compressed public
+    /// key.
+    ///
+    /// Fails with `Error::InvalidSecret` if `scalar` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::scalar::Scalar256;
+    /// use keymaker::{Network, PrivateKey};
+    ///
+    /// let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let doubled = private.to_scalar().unwrap().add(&private.to_scalar().unwrap());
+    /// let doubled = PrivateKey::from_scalar(&doubled, Network::Mainnet).unwrap();
+    /// assert_eq!(doubled.secret, [2u8; 32]);
+    /// assert!(PrivateKey::from_scalar(&Scalar256::ZERO, Network::Mainnet).is_err());
+    /// ```
+    pub fn from_scalar(scalar: &Scalar256, network: Network) -> Result<Self, Error> {
+        Self::from_slice(&scalar.to_bytes(), network)
+    }
+
+    /// The secret as a [Scalar256], failing with `Error::InvalidSecret`
+    /// unless it is in `[1, n)`.
+    pub fn to_scalar(&self) -> Result<Scalar256, Error> {
+        Scalar256::from_bytes(&self.secret)
+            .filter(|scalar| !scalar.is_zero())
+            .ok_or(Error::InvalidSecret)
+    }
+
+    /// The raw secret as 64 lowercase hex digits.
+    pub fn to_hex(&self) -> String {
+        self.secret.to_hex()
+    }
+
+    /// The public key, in the compressed or uncompressed form this key is
+    /// flagged with.
+    pub fn public_key(&self) -> Result<PublicKey, Error> {
+        let secret = key::SecretKey::from_slice(&self.secret)?;
+        let public = key::PublicKey::from_secret_key(&SECP256K1, &secret);
+        Ok(if self.compressed {
+            PublicKey::Compressed(public.serialize())
+        } else {
+            PublicKey::Standard(public.serialize_uncompressed())
+        })
+    }
+
+    /// Sign a 32-byte `hash` with an RFC6979 nonce, returning a DER
+    /// signature with a low `s`. The hash is one the caller has already
+    /// computed, such as a transaction sighash, wrapped with
+    /// [Message::from_prehashed](Message::from_prehashed).
+    ///
+    /// Use [sign_data](PrivateKey::sign_data) for data that still needs
+    /// hashing.
+    pub fn sign_hash(&self, hash: &Message) -> Result<Signature, Error> {
+        self.sign_with(hash, SignOptions::default())
+    }
+
+    /// Hash `data` with `hash` and sign the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{DataHash, Network, PrivateKey};
+    ///
+    /// let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let data = b"not a hash";
+    /// assert_eq!(
+    ///     private.sign_data(data, DataHash::Sha256).unwrap(),
+    ///     private.sign_hash(&DataHash::Sha256.hash(data)).unwrap()
+    /// );
+    /// ```
+    pub fn sign_data(&self, data: &[u8], hash: DataHash) -> Result<Signature, Error> {
+        self.sign_hash(&hash.hash(data))
+    }
+
+    /// Sign `message` as [sign_hash](PrivateKey::sign_hash) does, with `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Message, Network, PrivateKey, SignOptions};
+    ///
+    /// let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let options = SignOptions {
+    ///     low_r: true,
+    ///     ..Default::default()
+    /// };
+    /// let message = Message::from_prehashed([7u8; 32]);
+    /// let signature = private.sign_with(&message, options).unwrap();
+    /// assert!(signature.check_low_r() && signature.check_low_s());
+    /// assert!(signature.len() <= 70);
+    /// ```
+    pub fn sign_with(&self, message: &Message, options: SignOptions) -> Result<Signature, Error> {
         let context = &SECP256K1;
         let secret = key::SecretKey::from_slice(&self.secret)?;
-        let message = SecpMessage::from_slice(message)?;
-        let signature = context.sign(&message, &secret);
+        let message = message.to_secp();
+        let signature = match (options.extra_entropy, options.low_r) {
+            (None, false) => context.sign(&message, &secret),
+            (None, true) => context.sign_low_r(&message, &secret),
+            (Some(entropy), false) => ecdsa::sign_with_entropy(&message, &secret, &entropy)?,
+            (Some(entropy), true) => {
+                // Grind on a hash of the entropy and a counter, since the
+                // entropy slot is the one Bitcoin Core grinds with.
+                let mut signature = ecdsa::sign_with_entropy(&message, &secret, &entropy)?;
+                let mut counter = 0u32;
+                while signature.serialize_compact()[0] >= 0x80 {
+                    counter += 1;
+                    let mut data = entropy.to_vec();
+                    data.extend_from_slice(&counter.to_le_bytes());
+                    signature =
+                        ecdsa::sign_with_entropy(&message, &secret, &crypto::sha256(&data))?;
+                }
+                signature
+            }
+        };
         let serialized_sig = signature.serialize_der();
         Ok(Signature::from(serialized_sig))
     }
 
-    pub fn sign_compact(&self, message: &Message) -> Result<CompactSignature, Error> {
+    /// Add `tweak` to the secret modulo the curve order, as done in BIP32
+    /// derivation, pay-to-contract and taproot tweaking.
+    ///
+    /// Fails if `tweak` is not below the curve order or the sum is zero.
+    pub fn add_tweak(&self, tweak: &[u8; 32]) -> Result<PrivateKey, Error> {
+        let tweak = Scalar256::from_bytes(tweak).ok_or(Error::InvalidTweak)?;
+        let sum = self.to_scalar()?.add(&tweak);
+        if sum.is_zero() {
+            return Err(Error::InvalidTweak);
+        }
+        Ok(PrivateKey {
+            secret: sum.to_bytes(),
+            ..*self
+        })
+    }
+
+    /// Sign `message` with a [BIP340] Schnorr signature, for the key's
+    /// x-only public key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Message, Network, PrivateKey};
+    ///
+    /// let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let message = Message::from_prehashed([7u8; 32]);
+    /// let signature = private.sign_schnorr(&message).unwrap();
+    /// let (key, _) = private.public_key().unwrap().x_only().unwrap();
+    /// assert!(signature.verify(&message, &key).unwrap());
+    /// ```
+    ///
+    /// [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    pub fn sign_schnorr(&self, message: &Message) -> Result<SchnorrSignature, Error> {
+        let keypair = schnorrsig::KeyPair::from_seckey_slice(&SECP256K1, &self.secret)?;
+        let message = message.to_secp();
+        let mut aux_rand = [0u8; 32];
+        OsRng.fill_bytes(&mut aux_rand);
+        let signature = SECP256K1.schnorrsig_sign_with_aux_rand(&message, &keypair, &aux_rand);
+        SchnorrSignature::from_slice(&signature[..])
+    }
+
+    /// Sign `message` with a recoverable signature, from which the public
+    /// key can be rebuilt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Message, Network, PrivateKey};
+    ///
+    /// let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let message = Message::from_prehashed([7u8; 32]);
+    /// let signature = private.sign_recoverable(&message).unwrap();
+    /// assert_eq!(
+    ///     signature.recover(&message).unwrap().to_string(),
+    ///     private.public_key().unwrap().to_string()
+    /// );
+    /// ```
+    pub fn sign_recoverable(&self, message: &Message) -> Result<RecoverableSignature, Error> {
         let context = &SECP256K1;
         let secret = key::SecretKey::from_slice(&self.secret)?;
-        let message = SecpMessage::from_slice(message)?;
-        let signature = context.sign_recoverable(&message, &secret);
-        let (recovery_id, data) = signature.serialize_compact();
-        let recovery_id = recovery_id.to_i32() as u8;
-        let mut signature: Hash520Bits = [0u8; 65];
-        signature[1..65].copy_from_slice(&data[0..64]);
-        if self.compressed {
-            signature[0] = 27 + recovery_id + 4;
-        } else {
-            signature[0] = 27 + recovery_id;
-        }
-        Ok(signature.into())
+        let message = message.to_secp();
+        Ok(context.sign_recoverable(&message, &secret).into())
+    }
+
+    /// Sign `message` in the 65-byte form of
+    /// [RecoverableSignature::to_compact], flagged with whether this key is
+    /// compressed.
+    pub fn sign_compact(&self, message: &Message) -> Result<CompactSignature, Error> {
+        Ok(self.sign_recoverable(message)?.to_compact(self.compressed))
     }
 }
 
@@ -54,11 +273,7 @@ impl DisplayLayout for PrivateKey {
 
     fn layout(&self) -> Self::Target {
         let mut result = vec![];
-        let network_byte = match self.network {
-            Network::Mainnet => 128,
-            Network::Testnet => 239,
-        };
-        result.push(network_byte);
+        result.push(self.network.params().wif);
         result.extend(&self.secret);
         if self.compressed {
             result.push(1);
@@ -82,19 +297,16 @@ impl DisplayLayout for PrivateKey {
             return Err(Error::InvalidPrivate);
         }
 
-        let cs = crypto::checksum(&data[..data.len() - 4]);
-        if &data[data.len() - 4..] != &cs {
+        let (payload, cs) = data.split_at(data.len() - 4);
+        if !crypto::verify_checksum(payload, cs) {
             return Err(Error::InvalidChecksum);
         }
 
-        let network = match data[0] {
-            128 => Network::Mainnet,
-            239 => Network::Testnet,
-            _ => return Err(Error::InvalidPrivate),
-        };
+        let network = Network::find(|params| params.wif == data[0]).ok_or(Error::InvalidPrivate)?;
 
         let mut secret = Secret::default();
         secret.copy_from_slice(&data[1..33]);
+        key::SecretKey::from_slice(&secret)?;
 
         let private = PrivateKey {
             network,
@@ -106,6 +318,15 @@ impl DisplayLayout for PrivateKey {
     }
 }
 
+/// Compares the secrets in constant time.
+impl PartialEq for PrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        ct::eq(&self.secret, &other.secret)
+            && self.network == other.network
+            && self.compressed == other.compressed
+    }
+}
+
 impl fmt::Debug for PrivateKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "network: {:?}", self.network)?;
@@ -119,3 +340,250 @@ impl fmt::Display for PrivateKey {
         self.layout().to_base58().fmt(f)
     }
 }
+
+impl str::FromStr for PrivateKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::from_wif(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn wif_round_trip() -> Result<(), Error> {
+        let mut secret = Secret::default();
+        secret[31] = 1;
+        let cases = [
+            (
+                Network::Mainnet,
+                true,
+                "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn",
+            ),
+            (
+                Network::Mainnet,
+                false,
+                "5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf",
+            ),
+            (
+                Network::Testnet,
+                true,
+                "cMahea7zqjxrtgAbB7LSGbcQUr1uX1ojuat9jZodMN87JcbXMTcA",
+            ),
+        ];
+        for (network, compressed, wif) in cases.iter() {
+            let private: PrivateKey = wif.parse()?;
+            assert_eq!(
+                private,
+                PrivateKey {
+                    network: *network,
+                    secret,
+                    compressed: *compressed,
+                }
+            );
+            assert_eq!(private.to_string(), *wif);
+        }
+
+        // A bad checksum, and a zero secret.
+        assert!(matches!(
+            PrivateKey::from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWo"),
+            Err(Error::InvalidChecksum)
+        ));
+        let zero = PrivateKey {
+            network: Network::Mainnet,
+            secret: Secret::default(),
+            compressed: true,
+        };
+        assert!(PrivateKey::from_wif(&zero.to_string()).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "zcash")]
+    #[test]
+    fn zcash_wif_parses_as_mainnet() -> Result<(), Error> {
+        let mut secret = Secret::default();
+        secret[31] = 1;
+        let zcash = PrivateKey {
+            network: Network::Zcash,
+            secret,
+            compressed: true,
+        };
+        let wif = zcash.to_string();
+        assert_eq!(wif, "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn");
+
+        // Zcash shares the 0x80 version byte, so only the network is lost.
+        let parsed = PrivateKey::from_wif(&wif)?;
+        assert_eq!(parsed.network, Network::Mainnet);
+        assert_eq!(
+            parsed,
+            PrivateKey {
+                network: Network::Mainnet,
+                ..zcash
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hex_round_trip() -> Result<(), Error> {
+        let hex = "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35";
+        let private = PrivateKey::from_hex(hex, Network::Testnet)?;
+        assert_eq!(private.to_hex(), hex);
+        assert_eq!(
+            private,
+            PrivateKey::from_hex(&hex.to_uppercase(), Network::Testnet)?
+        );
+
+        // Zero, the curve order, and a short key are all out of range.
+        for bad in &[
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b",
+            "not hex",
+        ] {
+            assert!(matches!(
+                PrivateKey::from_hex(bad, Network::Mainnet),
+                Err(Error::InvalidSecret)
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_conversions_and_tweaks() -> Result<(), Error> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Testnet)?;
+        let scalar = private.to_scalar()?;
+        assert_eq!(PrivateKey::from_scalar(&scalar, Network::Testnet)?, private);
+
+        // Adding n - 1 subtracts one, and adding the negation is zero.
+        let minus_one = Scalar256::ZERO.sub(&Scalar256::ONE);
+        let mut expected = [1u8; 32];
+        expected[31] = 0;
+        assert_eq!(private.add_tweak(&minus_one.to_bytes())?.secret, expected);
+        assert_eq!(
+            private.add_tweak(&scalar.negate().to_bytes()).err(),
+            Some(Error::InvalidTweak)
+        );
+        assert_eq!(
+            private.add_tweak(&crate::scalar::CURVE_ORDER).err(),
+            Some(Error::InvalidTweak)
+        );
+
+        let zero = PrivateKey {
+            secret: Secret::default(),
+            ..private
+        };
+        assert_eq!(zero.to_scalar().err(), Some(Error::InvalidSecret));
+        assert_eq!(zero.add_tweak(&[1u8; 32]).err(), Some(Error::InvalidSecret));
+        Ok(())
+    }
+
+    #[test]
+    fn low_s_and_low_r() -> Result<(), Error> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        let signature = private.sign_hash(&Message::from_prehashed([7u8; 32]))?;
+        assert_eq!(
+            signature,
+            Signature::from("3043021f1af75f8a0ececcc4af0ccdbcccc1a81656eca444e3484151212ad1823ae12202207cc07485ba5a779f1f12089090e4b013322db0dae4e8151592c0b1ee0fd0f74d")
+        );
+        assert!(signature.check_low_s());
+
+        // The same signature with `s` replaced by `n - s`.
+        let high_s = Signature::from("3044021f1af75f8a0ececcc4af0ccdbcccc1a81656eca444e3484151212ad1823ae122022100833f8b7a45a58860e0edf76f6f1b4feb88812c0bca608b262d11ac9ec06549f4");
+        assert!(!high_s.check_low_s());
+
+        let options = SignOptions {
+            low_r: true,
+            ..Default::default()
+        };
+        for i in 0..16u8 {
+            let signature = private.sign_with(&Message::from_prehashed([i; 32]), options)?;
+            assert!(signature.check_low_r() && signature.check_low_s());
+            assert!(signature.len() <= 70);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn extra_entropy() -> Result<(), Error> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        let public = private.public_key()?;
+        let message = Message::from_prehashed([7u8; 32]);
+        let with_entropy = |entropy, low_r| SignOptions {
+            low_r,
+            extra_entropy: Some(entropy),
+        };
+
+        // Still deterministic for the same entropy, but a different nonce.
+        let signature = private.sign_with(&message, with_entropy([1u8; 32], false))?;
+        assert_eq!(
+            signature,
+            private.sign_with(&message, with_entropy([1u8; 32], false))?
+        );
+        assert_ne!(
+            signature,
+            private.sign_with(&message, with_entropy([2u8; 32], false))?
+        );
+        assert_ne!(signature, private.sign_hash(&message)?);
+        assert!(public.verify(&message, &signature)?);
+
+        for i in 0..16u8 {
+            let signature = private.sign_with(&message, with_entropy([i; 32], true))?;
+            assert!(signature.check_low_r());
+            assert!(public.verify(&message, &signature)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn sign_data_hashes() -> Result<(), Error> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        let data = b"hello";
+        // Single and double SHA-256 of "hello".
+        let single = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let double = "9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50";
+        assert_eq!(DataHash::Sha256.hash(data).to_hex(), single);
+        assert_eq!(DataHash::default().hash(data).to_hex(), double);
+
+        let signature = private.sign_data(data, DataHash::DoubleSha256)?;
+        assert_eq!(
+            signature,
+            private.sign_hash(&DataHash::DoubleSha256.hash(data))?
+        );
+        assert_ne!(signature, private.sign_data(data, DataHash::Sha256)?);
+        Ok(())
+    }
+
+    #[test]
+    fn recoverable_signature() -> Result<(), Error> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        let message = Message::from_prehashed([7u8; 32]);
+        let signature = private.sign_recoverable(&message)?;
+
+        let compact = private.sign_compact(&message)?;
+        assert_eq!(compact[0], 31 + signature.recovery_id());
+        assert_eq!(
+            RecoverableSignature::from_compact(&compact)?,
+            (signature, true)
+        );
+        assert_eq!(
+            signature.recover(&message)?.to_string(),
+            private.public_key()?.to_string()
+        );
+
+        // The same r and s as the DER signature.
+        let der = secp256k1::Signature::from_der(&private.sign_hash(&message)?)?;
+        assert_eq!(der.serialize_compact()[..32], signature.r());
+        assert_eq!(der.serialize_compact()[32..], signature.s());
+
+        let id = u64::from(signature.recovery_id());
+        assert_eq!(signature.v(None), 27 + id);
+        assert_eq!(signature.v(Some(1)), 37 + id);
+        assert!(RecoverableSignature::new(4, signature.r(), signature.s()).is_err());
+        Ok(())
+    }
+}
@@ -1,13 +1,265 @@
-use crate::{Hash264Bits, Hash520Bits};
+use crate::crypto;
+#[cfg(feature = "eth")]
+use crate::eth;
+use crate::scalar::FIELD_PRIME;
+use crate::{
+    Address, AddressHash, Error, Fingerprint, Hash264Bits, Hash520Bits, Message, Network,
+    Signature, SECP256K1,
+};
 use secp256k1::bitcoin_hashes::hex::ToHex;
-use std::fmt;
+use secp256k1::key;
+use secp256k1::Signature as SecpSignature;
+use std::{cmp, fmt, hash, ops, str};
+use thiserror::Error;
 
+/// Error originating from [PublicKey::from_slice_strict].
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PublicKeyError {
+    #[error("Invalid length {0}")]
+    InvalidLength(usize),
+    #[error("Invalid prefix byte {0:#04x} for the key length")]
+    InvalidPrefix(u8),
+    #[error("Hybrid encoding with prefix byte {0:#04x}")]
+    HybridEncoding(u8),
+    #[error("Coordinate not below the field prime")]
+    NonCanonical,
+    #[error("Point not on the curve")]
+    NotOnCurve,
+}
+
+/// A secp256k1 public key in its compressed or uncompressed serialization.
+///
+/// Keys compare, hash and sort by their serialized bytes, so the two forms
+/// of one point are different keys. Sorting compressed keys this way is the
+/// [BIP67] multisig order.
+///
+/// [BIP67]: https://github.com/bitcoin/bips/blob/master/bip-0067.mediawiki
 #[derive(Clone)]
 pub enum PublicKey {
     Standard(Hash520Bits),
     Compressed(Hash264Bits),
 }
 
+impl PublicKey {
+    /// Parse a 33-byte compressed or 65-byte uncompressed key, keeping its
+    /// form.
+    ///
+    /// Fails with `Error::InvalidPublic` on any other length or prefix
+    /// byte, or if the point is not on the curve.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        Self::from_slice_strict(data).map_err(|_| Error::InvalidPublic)
+    }
+
+    /// Parse a key as [from_slice](PublicKey::from_slice) does, telling why
+    /// a key is rejected, as is useful for keys received over the network.
+    ///
+    /// Only the SEC1 `0x02` and `0x03` prefixes of 33-byte keys and `0x04`
+    /// of 65-byte keys are accepted, never the hybrid `0x06` and `0x07`.
+    /// Coordinates must be below the field prime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{PublicKey, PublicKeyError};
+    ///
+    /// let mut hybrid = [0u8; 65];
+    /// hybrid[0] = 0x06;
+    /// assert_eq!(
+    ///     PublicKey::from_slice_strict(&hybrid).unwrap_err(),
+    ///     PublicKeyError::HybridEncoding(0x06)
+    /// );
+    /// ```
+    pub fn from_slice_strict(data: &[u8]) -> Result<Self, PublicKeyError> {
+        match (data.len(), data.first()) {
+            (33, Some(0x02)) | (33, Some(0x03)) | (65, Some(0x04)) => {}
+            (65, Some(&prefix)) if prefix == 0x06 || prefix == 0x07 => {
+                return Err(PublicKeyError::HybridEncoding(prefix))
+            }
+            (33, Some(&prefix)) | (65, Some(&prefix)) => {
+                return Err(PublicKeyError::InvalidPrefix(prefix))
+            }
+            (len, _) => return Err(PublicKeyError::InvalidLength(len)),
+        }
+        if data[1..]
+            .chunks(32)
+            .any(|coordinate| coordinate >= &FIELD_PRIME[..])
+        {
+            return Err(PublicKeyError::NonCanonical);
+        }
+        key::PublicKey::from_slice(data).map_err(|_| PublicKeyError::NotOnCurve)?;
+
+        if data.len() == 33 {
+            let mut inner: Hash264Bits = [0u8; 33];
+            inner.copy_from_slice(data);
+            Ok(PublicKey::Compressed(inner))
+        } else {
+            let mut inner: Hash520Bits = [0u8; 65];
+            inner.copy_from_slice(data);
+            Ok(PublicKey::Standard(inner))
+        }
+    }
+
+    /// Verify a DER-encoded ECDSA `signature` of `message` against this key.
+    pub fn verify(&self, message: &Message, signature: &Signature) -> Result<bool, Error> {
+        let public = match self {
+            PublicKey::Standard(inner) => key::PublicKey::from_slice(inner)?,
+            PublicKey::Compressed(inner) => key::PublicKey::from_slice(inner)?,
+        };
+        let message = message.to_secp();
+        let signature = SecpSignature::from_der(signature)?;
+        Ok(SECP256K1.verify(&message, &signature, &public).is_ok())
+    }
+
+    /// Add `tweak * G` to the key, the public counterpart of
+    /// [PrivateKey::add_tweak](crate::PrivateKey::add_tweak). The result keeps
+    /// this key's compressed or uncompressed form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{KeyPair, MasterExtendedKeys, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let keys = MasterExtendedKeys::new(seed.entropy, None, Network::Mainnet, true).unwrap();
+    ///
+    /// let tweak = [1u8; 32];
+    /// let tweaked = keys.privkey().add_tweak(&tweak).unwrap();
+    /// assert_eq!(
+    ///     keys.pubkey().add_tweak(&tweak).unwrap().identifier(),
+    ///     KeyPair::from_private(tweaked, true).unwrap().public().identifier()
+    /// );
+    /// ```
+    pub fn add_tweak(&self, tweak: &[u8; 32]) -> Result<PublicKey, Error> {
+        let mut public = match self {
+            PublicKey::Standard(inner) => key::PublicKey::from_slice(inner)?,
+            PublicKey::Compressed(inner) => key::PublicKey::from_slice(inner)?,
+        };
+        public.add_exp_assign(&SECP256K1, tweak)?;
+
+        Ok(match self {
+            PublicKey::Standard(_) => PublicKey::Standard(public.serialize_uncompressed()),
+            PublicKey::Compressed(_) => PublicKey::Compressed(public.serialize()),
+        })
+    }
+
+    /// The `hash160` of the key in the form it is stored in, which a P2PKH
+    /// address pays to.
+    pub fn address_hash(&self) -> AddressHash {
+        match self {
+            PublicKey::Standard(inner) => crypto::hash160(inner),
+            PublicKey::Compressed(inner) => crypto::hash160(inner),
+        }
+    }
+
+    /// The P2PKH address of the key, as [Address::p2pkh] builds it.
+    pub fn to_p2pkh_address(&self, network: Network) -> Address {
+        Address::p2pkh(self, network)
+    }
+
+    /// The P2WPKH address of the key, as [Address::p2wpkh] builds it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Network, PrivateKey};
+    ///
+    /// let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let address = private.public_key().unwrap().to_p2wpkh_address(Network::Mainnet).unwrap();
+    /// assert!(address.to_string().starts_with("bc1q"));
+    /// ```
+    pub fn to_p2wpkh_address(&self, network: Network) -> Result<Address, Error> {
+        Address::p2wpkh(self, network)
+    }
+
+    /// The `hash160` of the compressed public key, which identifies the key
+    /// regardless of the form it is stored in.
+    pub fn identifier(&self) -> Result<AddressHash, Error> {
+        Ok(crypto::hash160(&self.compressed()?))
+    }
+
+    /// The first 4 bytes of the [identifier](PublicKey::identifier), as used
+    /// in BIP32 parent fingerprints and descriptor key origins.
+    pub fn fingerprint(&self) -> Result<Fingerprint, Error> {
+        let mut fingerprint = Fingerprint::default();
+        fingerprint.copy_from_slice(&self.identifier()?[..4]);
+        Ok(fingerprint)
+    }
+
+    /// The [EIP-55](crate::eth) checksummed Ethereum address of the key.
+    ///
+    /// Requires the `eth` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{eth, ExtendedPrivKey, Network, SeedBuilder};
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+    ///     .unwrap()
+    ///     .derive_path(&"m/44'/60'/0'/0/0".parse().unwrap())
+    ///     .unwrap();
+    ///
+    /// let address = keys.pubkey().to_eth_address().unwrap();
+    /// assert!(eth::validate_address(&address).is_ok());
+    /// ```
+    #[cfg(feature = "eth")]
+    pub fn to_eth_address(&self) -> Result<String, Error> {
+        Ok(eth::to_checksum_address(&self.keccak_identifier()?))
+    }
+
+    /// The last 20 bytes of the Keccak-256 hash of the uncompressed key
+    /// without its 0x04 prefix, which Ethereum and Tron addresses encode.
+    #[cfg(feature = "eth")]
+    pub(crate) fn keccak_identifier(&self) -> Result<AddressHash, Error> {
+        let uncompressed = match self {
+            PublicKey::Standard(inner) => *inner,
+            PublicKey::Compressed(inner) => {
+                key::PublicKey::from_slice(inner)?.serialize_uncompressed()
+            }
+        };
+        let hash = crypto::keccak256(&uncompressed[1..]);
+        let mut address = AddressHash::default();
+        address.copy_from_slice(&hash[12..]);
+        Ok(address)
+    }
+
+    /// The same key in its 33-byte compressed form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{Network, PrivateKey};
+    ///
+    /// let mut private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// private.compressed = false;
+    /// let long = private.public_key().unwrap();
+    /// let short = long.compress().unwrap();
+    /// assert_eq!(short.to_string().len(), 66);
+    /// assert_eq!(short.decompress().unwrap().to_string(), long.to_string());
+    /// ```
+    pub fn compress(&self) -> Result<PublicKey, Error> {
+        Ok(PublicKey::Compressed(self.compressed()?))
+    }
+
+    /// The same key in its 65-byte uncompressed form.
+    pub fn decompress(&self) -> Result<PublicKey, Error> {
+        let uncompressed = match self {
+            PublicKey::Standard(inner) => key::PublicKey::from_slice(inner)?,
+            PublicKey::Compressed(inner) => key::PublicKey::from_slice(inner)?,
+        };
+        Ok(PublicKey::Standard(uncompressed.serialize_uncompressed()))
+    }
+
+    /// The 33-byte compressed serialization of the key.
+    pub(crate) fn compressed(&self) -> Result<Hash264Bits, Error> {
+        match self {
+            PublicKey::Standard(inner) => Ok(key::PublicKey::from_slice(inner)?.serialize()),
+            PublicKey::Compressed(inner) => Ok(*inner),
+        }
+    }
+}
+
 impl fmt::Debug for PublicKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -25,3 +277,240 @@ impl fmt::Display for PublicKey {
         }
     }
 }
+
+impl ops::Deref for PublicKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            PublicKey::Standard(inner) => inner,
+            PublicKey::Compressed(inner) => inner,
+        }
+    }
+}
+
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl Eq for PublicKey {}
+
+impl hash::Hash for PublicKey {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self[..].hash(state)
+    }
+}
+
+impl PartialOrd for PublicKey {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PublicKey {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self[..].cmp(&other[..])
+    }
+}
+
+impl str::FromStr for PublicKey {
+    type Err = Error;
+
+    /// Parse a key written in hex, as [from_slice](PublicKey::from_slice)
+    /// does.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let data = hex::decode(s).map_err(|_| Error::InvalidPublic)?;
+        Self::from_slice(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::scalar::CURVE_ORDER;
+    use crate::PrivateKey;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn identifier_ignores_key_form() -> Result<(), Error> {
+        // BIP32 test vector 1 master key.
+        let compressed =
+            hex::decode("0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2")
+                .unwrap();
+        let public = key::PublicKey::from_slice(&compressed)?;
+
+        let mut inner = [0u8; 33];
+        inner.copy_from_slice(&compressed);
+        let short = PublicKey::Compressed(inner);
+        let long = PublicKey::Standard(public.serialize_uncompressed());
+
+        assert_eq!(
+            hex::encode(short.identifier()?),
+            "3442193e1bb70916e914552172cd4e2dbc9df811"
+        );
+        assert_eq!(long.identifier()?, short.identifier()?);
+        assert_eq!(hex::encode(long.fingerprint()?), "3442193e");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_public_key() -> Result<(), Error> {
+        let hex = "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2";
+        let short: PublicKey = hex.parse()?;
+        assert!(matches!(short, PublicKey::Compressed(_)));
+        assert_eq!(short.to_string(), hex);
+
+        let long_hex = short.decompress()?.to_string();
+        let long: PublicKey = long_hex.parse()?;
+        assert!(matches!(long, PublicKey::Standard(_)));
+        assert_eq!(long.to_string(), long_hex);
+
+        let mut off_curve = hex::decode(hex).unwrap();
+        off_curve[1..].copy_from_slice(&[0xff; 32]);
+        let mut hybrid = hex::decode(&long_hex).unwrap();
+        hybrid[0] = 0x07;
+        for bad in &[
+            off_curve,
+            hybrid,
+            hex::decode(&hex[2..]).unwrap(),
+            hex::decode(&long_hex[..64]).unwrap(),
+        ] {
+            assert!(matches!(
+                PublicKey::from_slice(bad),
+                Err(Error::InvalidPublic)
+            ));
+        }
+        assert!(matches!(
+            "not hex".parse::<PublicKey>(),
+            Err(Error::InvalidPublic)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn address_hash_follows_key_form() -> Result<(), Error> {
+        let mut private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        let short = private.public_key()?;
+        private.compressed = false;
+        let long = private.public_key()?;
+
+        assert_eq!(short.address_hash(), short.identifier()?);
+        assert_ne!(long.address_hash(), short.address_hash());
+        assert_eq!(
+            long.to_p2pkh_address(Network::Mainnet).hash(),
+            &long.address_hash()[..]
+        );
+        // P2WPKH always pays to the compressed key.
+        assert_eq!(
+            long.to_p2wpkh_address(Network::Mainnet)?,
+            short.to_p2wpkh_address(Network::Mainnet)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn keys_order_by_bytes() -> Result<(), Error> {
+        let keys: Vec<PublicKey> = [
+            "03dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "023590a94e768f8e1815c2f24b4d80a8e3149316c3518ce7b7ad338368d038ca66",
+        ]
+        .iter()
+        .map(|key| key.parse())
+        .collect::<Result<_, _>>()?;
+
+        let sorted: BTreeSet<PublicKey> = keys.iter().cloned().collect();
+        let sorted: Vec<_> = sorted.into_iter().collect();
+        assert_eq!(
+            sorted,
+            vec![keys[2].clone(), keys[1].clone(), keys[0].clone()]
+        );
+
+        let mut balances = HashMap::new();
+        balances.insert(keys[0].clone(), 1);
+        assert_eq!(balances.get(&keys[0].to_string().parse()?), Some(&1));
+
+        // Both forms of a point are distinct keys.
+        let long = keys[0].decompress()?;
+        assert_ne!(long, keys[0]);
+        assert_eq!(long.compress()?, keys[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn strict_parsing_errors() {
+        let hex = "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2";
+        let key = hex::decode(hex).unwrap();
+        assert!(PublicKey::from_slice_strict(&key).is_ok());
+
+        let mut wrong_prefix = key.clone();
+        wrong_prefix[0] = 0x04;
+        let mut above_prime = key.clone();
+        above_prime[1..].copy_from_slice(&[0xff; 32]);
+        let mut off_curve = key.clone();
+        off_curve[1..].copy_from_slice(&[0; 32]);
+        let cases = [
+            (key[..32].to_vec(), PublicKeyError::InvalidLength(32)),
+            (vec![], PublicKeyError::InvalidLength(0)),
+            (wrong_prefix, PublicKeyError::InvalidPrefix(0x04)),
+            (above_prime, PublicKeyError::NonCanonical),
+            (off_curve, PublicKeyError::NotOnCurve),
+        ];
+        for (data, error) in cases.iter() {
+            assert_eq!(PublicKey::from_slice_strict(data).unwrap_err(), *error);
+        }
+    }
+
+    #[test]
+    fn compression_round_trip() -> Result<(), Error> {
+        let compressed =
+            hex::decode("0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2")
+                .unwrap();
+        let mut inner = [0u8; 33];
+        inner.copy_from_slice(&compressed);
+        let short = PublicKey::Compressed(inner);
+
+        let long = short.decompress()?;
+        assert!(matches!(long, PublicKey::Standard(_)));
+        assert!(matches!(long.decompress()?, PublicKey::Standard(_)));
+        assert_eq!(long.compress()?.to_string(), short.to_string());
+        assert_eq!(short.compress()?.to_string(), short.to_string());
+
+        // An invalid prefix byte is not a point to re-serialize.
+        inner[0] = 0x05;
+        assert!(PublicKey::Compressed(inner).decompress().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn tweaks_reject_the_curve_order() {
+        let order = CURVE_ORDER;
+        let private = PrivateKey {
+            network: Network::Mainnet,
+            secret: [1u8; 32],
+            compressed: true,
+        };
+        assert_eq!(private.add_tweak(&order).err(), Some(Error::InvalidTweak));
+
+        // n - 1 tweaks a secret of 1 to zero.
+        let mut minus_one = order;
+        minus_one[31] -= 1;
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let unit = PrivateKey {
+            secret: one,
+            ..private
+        };
+        assert!(unit.add_tweak(&minus_one).is_err());
+
+        let secret_key = key::SecretKey::from_slice(&one).unwrap();
+        let generator = PublicKey::Compressed(
+            key::PublicKey::from_secret_key(&SECP256K1, &secret_key).serialize(),
+        );
+        assert!(generator.add_tweak(&order).is_err());
+        assert!(generator.add_tweak(&minus_one).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! QR codes of [addresses](crate::Address), extended public keys and
+//! [BIP21 URIs](crate::bip21::Uri), rendered as SVG documents or PNG images.
+//!
+//! Requires the `qr` feature.
+
+use image::png::PngEncoder;
+use image::{ColorType, Luma};
+use qrcode::{render::svg, QrCode};
+use thiserror::Error;
+
+use crate::bip21::Uri;
+use crate::{Address, ExtendedPubKey};
+
+/// Error originating from [qr](crate::qr) module.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum QrError {
+    #[error("QR encoding failed: {0}")]
+    Encoding(String),
+    #[error("PNG encoding failed: {0}")]
+    Png(String),
+}
+
+/// Render `data` as an SVG document.
+pub fn to_svg(data: &str) -> Result<String, QrError> {
+    Ok(encode(data)?.render::<svg::Color>().build())
+}
+
+/// Render `data` as a grayscale PNG image.
+pub fn to_png(data: &str) -> Result<Vec<u8>, QrError> {
+    let image = encode(data)?.render::<Luma<u8>>().build();
+    let mut png = vec![];
+    PngEncoder::new(&mut png)
+        .encode(&image, image.width(), image.height(), ColorType::L8)
+        .map_err(|e| QrError::Png(e.to_string()))?;
+    Ok(png)
+}
+
+fn encode(data: &str) -> Result<QrCode, QrError> {
+    QrCode::new(data).map_err(|e| QrError::Encoding(e.to_string()))
+}
+
+impl Address {
+    /// Render the address as an SVG QR code.
+    ///
+    /// SegWit addresses are uppercased, as BIP173 suggests, so they fit the
+    /// denser alphanumeric mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::Address;
+    ///
+    /// let address: Address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".parse().unwrap();
+    /// assert!(address.to_qr_svg().unwrap().starts_with("<?xml"));
+    /// ```
+    pub fn to_qr_svg(&self) -> Result<String, QrError> {
+        to_svg(&self.qr_data())
+    }
+
+    /// Render the address as a PNG QR code.
+    pub fn to_qr_png(&self) -> Result<Vec<u8>, QrError> {
+        to_png(&self.qr_data())
+    }
+
+    fn qr_data(&self) -> String {
+        if self.is_segwit() {
+            self.to_string().to_uppercase()
+        } else {
+            self.to_string()
+        }
+    }
+}
+
+impl ExtendedPubKey {
+    /// Render the Base58Check `xpub` as an SVG QR code, to hand a watch-only
+    /// wallet the key.
+    pub fn to_qr_svg(&self) -> Result<String, QrError> {
+        to_svg(&self.to_string())
+    }
+
+    /// Render the Base58Check `xpub` as a PNG QR code.
+    pub fn to_qr_png(&self) -> Result<Vec<u8>, QrError> {
+        to_png(&self.to_string())
+    }
+}
+
+impl Uri {
+    /// Render the URI as an SVG QR code.
+    pub fn to_qr_svg(&self) -> Result<String, QrError> {
+        to_svg(&self.to_string())
+    }
+
+    /// Render the URI as a PNG QR code.
+    pub fn to_qr_png(&self) -> Result<Vec<u8>, QrError> {
+        to_png(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{ExtendedPrivKey, Network, SeedBuilder};
+
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    #[test]
+    fn renders_svg_and_png() -> anyhow::Result<()> {
+        let seed = SeedBuilder::new().build()?;
+        let xpub = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)?.neuter();
+        let address = Address::p2wpkh(&xpub.pubkey(), Network::Mainnet)?;
+        assert_eq!(address.qr_data(), address.to_string().to_uppercase());
+        let uri = Uri::new(address.clone()).amount(1000);
+
+        for svg in &[address.to_qr_svg()?, xpub.to_qr_svg()?, uri.to_qr_svg()?] {
+            assert!(svg.starts_with("<?xml"));
+        }
+        for png in &[address.to_qr_png()?, xpub.to_qr_png()?, uri.to_qr_png()?] {
+            assert!(png.starts_with(PNG_SIGNATURE));
+        }
+
+        assert!(matches!(
+            to_svg(&"x".repeat(3000)),
+            Err(QrError::Encoding(_))
+        ));
+        Ok(())
+    }
+}
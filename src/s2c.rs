@@ -0,0 +1,171 @@
+//! Sign-to-contract ECDSA signing, and the anti-exfil protocol built on it.
+//!
+//! Signing to a contract adds `t = H(R0 || data)` to the RFC6979 nonce `k`,
+//! so the signature's nonce point `R = R0 + tG` commits to 32 bytes of
+//! `data`. Whoever knows `R0` and `data` can check the commitment, which
+//! timestamps `data` inside an ordinary signature.
+//!
+//! The anti-exfil protocol uses this to keep a signing device from leaking
+//! its key through chosen nonces:
+//!
+//! 1. The host picks random `data` and sends its [host_commitment].
+//! 2. The device answers with `R0` from
+//!    [PrivateKey::s2c_nonce_commitment], derived from the commitment
+//!    before it has seen `data`.
+//! 3. The host reveals `data`, and the device signs with
+//!    [PrivateKey::sign_to_contract].
+//! 4. The host checks the signature, that the returned `R0` is the one of
+//!    step 2, and [verify_commitment].
+//!
+//! The device cannot bias `R` without the host noticing, and the host's
+//! randomness alone cannot leak anything.
+
+use secp256k1::key;
+use secp256k1::Signature as SecpSignature;
+
+use crate::{crypto, ecdsa};
+use crate::{Error, Message, PrivateKey, PublicKey, Signature, SECP256K1};
+
+const POINT_TAG: &str = "s2c/ecdsa/point";
+const DATA_TAG: &str = "s2c/ecdsa/data";
+
+/// The commitment to `data` a host sends before revealing it.
+pub fn host_commitment(data: &[u8; 32]) -> [u8; 32] {
+    crypto::tagged_hash(DATA_TAG, data)
+}
+
+impl PrivateKey {
+    /// The nonce point `R0` that [sign_to_contract](PrivateKey::sign_to_contract)
+    /// will tweak when signing `message` with the data behind
+    /// `host_commitment`.
+    pub fn s2c_nonce_commitment(
+        &self,
+        message: &Message,
+        host_commitment: &[u8; 32],
+    ) -> Result<PublicKey, Error> {
+        let mut attempt = 0;
+        loop {
+            let nonce = ecdsa::rfc6979(message.as_bytes(), &self.secret, host_commitment, attempt)
+                .ok_or(Error::InvalidSecret)?;
+            if let Ok(nonce) = key::SecretKey::from_slice(&nonce) {
+                let point = key::PublicKey::from_secret_key(&SECP256K1, &nonce);
+                return Ok(PublicKey::Compressed(point.serialize()));
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sign `message` with a nonce that commits to `data`, returning the
+    /// DER signature and the untweaked nonce point `R0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{s2c, Message, Network, PrivateKey};
+    ///
+    /// let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet).unwrap();
+    /// let message = Message::from_prehashed([7u8; 32]);
+    /// let data = [42u8; 32];
+    ///
+    /// let commitment = private
+    ///     .s2c_nonce_commitment(&message, &s2c::host_commitment(&data))
+    ///     .unwrap();
+    /// let (signature, original_nonce) = private.sign_to_contract(&message, &data).unwrap();
+    /// assert_eq!(original_nonce.to_string(), commitment.to_string());
+    /// assert!(s2c::verify_commitment(&signature, &original_nonce, &data).unwrap());
+    /// ```
+    pub fn sign_to_contract(
+        &self,
+        message: &Message,
+        data: &[u8; 32],
+    ) -> Result<(Signature, PublicKey), Error> {
+        let secret = key::SecretKey::from_slice(&self.secret)?;
+        let host_commitment = host_commitment(data);
+        let mut original_nonce = None;
+        let signature =
+            ecdsa::sign_with_nonce(&message.to_secp(), &secret, |message, secret, attempt| {
+                let mut nonce = ecdsa::rfc6979(message, secret, &host_commitment, attempt)?;
+                // An out of range nonce, or a zero sum, is rejected by
+                // libsecp256k1, which tries again with the next attempt.
+                let mut tweaked = match key::SecretKey::from_slice(&nonce) {
+                    Ok(tweaked) => tweaked,
+                    Err(_) => return Some(nonce),
+                };
+                let point = key::PublicKey::from_secret_key(&SECP256K1, &tweaked);
+                if tweaked.add_assign(&commitment_tweak(&point, data)).is_err() {
+                    return Some([0u8; 32]);
+                }
+                nonce.copy_from_slice(&tweaked[..]);
+                original_nonce = Some(point);
+                Some(nonce)
+            })
+            .map_err(|_| Error::InvalidSignature)?;
+        let original_nonce = original_nonce.ok_or(Error::InvalidSignature)?;
+
+        Ok((
+            Signature::from(signature.serialize_der()),
+            PublicKey::Compressed(original_nonce.serialize()),
+        ))
+    }
+}
+
+/// Check that `signature` was made by tweaking `original_nonce` with `data`.
+///
+/// This says nothing about whether the signature itself is valid, which
+/// must be checked separately.
+pub fn verify_commitment(
+    signature: &Signature,
+    original_nonce: &PublicKey,
+    data: &[u8; 32],
+) -> Result<bool, Error> {
+    let signature = SecpSignature::from_der(signature)?;
+    let original_nonce = match original_nonce {
+        PublicKey::Standard(inner) => key::PublicKey::from_slice(inner)?,
+        PublicKey::Compressed(inner) => key::PublicKey::from_slice(inner)?,
+    };
+    let mut nonce = original_nonce;
+    nonce.add_exp_assign(&SECP256K1, &commitment_tweak(&original_nonce, data))?;
+
+    // r is the x coordinate of R reduced modulo the curve order, which
+    // leaves it unchanged but with negligible probability.
+    Ok(signature.serialize_compact()[..32] == nonce.serialize()[1..])
+}
+
+/// `H(R0 || data)`, with `R0` compressed.
+fn commitment_tweak(original_nonce: &key::PublicKey, data: &[u8; 32]) -> [u8; 32] {
+    let mut preimage = original_nonce.serialize().to_vec();
+    preimage.extend_from_slice(data);
+    crypto::tagged_hash(POINT_TAG, &preimage)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Network;
+
+    #[test]
+    fn sign_and_verify_commitment() -> Result<(), Error> {
+        let private = PrivateKey::from_slice(&[1u8; 32], Network::Mainnet)?;
+        let message = Message::from_prehashed([7u8; 32]);
+        let data = [42u8; 32];
+
+        let commitment = private.s2c_nonce_commitment(&message, &host_commitment(&data))?;
+        let (signature, original_nonce) = private.sign_to_contract(&message, &data)?;
+        assert_eq!(original_nonce.to_string(), commitment.to_string());
+        assert!(verify_commitment(&signature, &original_nonce, &data)?);
+        assert!(!verify_commitment(
+            &signature,
+            &original_nonce,
+            &[43u8; 32]
+        )?);
+
+        // Still an ordinary signature, different from the plain RFC6979 one.
+        assert!(private.public_key()?.verify(&message, &signature)?);
+        assert_ne!(signature, private.sign_hash(&message)?);
+
+        // And deterministic.
+        assert_eq!(private.sign_to_contract(&message, &data)?.0, signature);
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+//! Modular arithmetic on big-endian byte arrays.
+//!
+//! [Scalar256] works modulo the secp256k1 curve order on fixed 32-byte
+//! arrays, in constant time and without allocating. It needs nothing beyond
+//! `core`, and is what the crate's own key arithmetic uses.
+//!
+//! The functions here take any modulus. Operands can have any length, and
+//! leading zero bytes do not matter. Results are padded to the length of the
+//! modulus, so that arithmetic modulo the curve order returns 32-byte
+//! scalars ready for [PrivateKey::from_slice](crate::PrivateKey::from_slice).
+//! They allocate `BigUint`s, and are only built with the `bigint` feature,
+//! which is on by default.
+//!
+//! The secp256k1 domain parameters are here as constants, so range checks
+//! need not spell them out in hex.
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+use thiserror::Error;
+
+mod scalar256;
+
+pub use scalar256::Scalar256;
+
+/// The order `n` of the secp256k1 group, above which no scalar is
+/// canonical.
+pub const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// `n / 2` rounded down, the largest `s` of a low-S signature.
+pub const HALF_CURVE_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// The secp256k1 field prime `p`, above which no coordinate is canonical.
+pub const FIELD_PRIME: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xfc, 0x2f,
+];
+
+/// The generator point `G`, compressed.
+pub const GENERATOR: [u8; 33] = [
+    0x02, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b,
+    0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17,
+    0x98,
+];
+
+/// The generator point `G`, uncompressed.
+pub const GENERATOR_UNCOMPRESSED: [u8; 65] = [
+    0x04, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b,
+    0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17,
+    0x98, 0x48, 0x3a, 0xda, 0x77, 0x26, 0xa3, 0xc4, 0x65, 0x5d, 0xa4, 0xfb, 0xfc, 0x0e, 0x11, 0x08,
+    0xa8, 0xfd, 0x17, 0xb4, 0x48, 0xa6, 0x85, 0x54, 0x19, 0x9c, 0x47, 0xd0, 0x8f, 0xfb, 0x10, 0xd4,
+    0xb8,
+];
+
+/// Error originating from [scalar](crate::scalar) module.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScalarError {
+    #[error("Modulus is zero")]
+    ZeroModulus,
+
+    #[error("Value has no inverse modulo the modulus")]
+    NotInvertible,
+}
+
+/// `a mod m`.
+#[cfg(feature = "bigint")]
+pub fn modulo(a: &[u8], m: &[u8]) -> Result<Vec<u8>, ScalarError> {
+    let m = modulus(m)?;
+    Ok(to_bytes(&(from_bytes(a) % &m), &m))
+}
+
+/// `(a + b) mod m`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::scalar;
+///
+/// assert_eq!(scalar::add(&[0xff], &[0x00, 0x02], &[0x01, 0x00]).unwrap(), vec![0x00, 0x01]);
+/// ```
+#[cfg(feature = "bigint")]
+pub fn add(a: &[u8], b: &[u8], m: &[u8]) -> Result<Vec<u8>, ScalarError> {
+    add_bytes(a, b, Some(m))
+}
+
+/// `a + b`, reduced modulo `m` if there is one.
+///
+/// The shorter operand is left-padded with zeros. Without a modulus the sum
+/// has the length of the longer operand, plus a leading byte if the last
+/// carry overflows it.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::scalar;
+///
+/// assert_eq!(scalar::add_bytes(&[0x01, 0xff], &[0x01], None).unwrap(), vec![0x02, 0x00]);
+/// assert_eq!(scalar::add_bytes(&[0xff, 0xff], &[0x01], None).unwrap(), vec![0x01, 0x00, 0x00]);
+/// assert_eq!(scalar::add_bytes(&[0xff, 0xff], &[0x01], Some(&[0xff])).unwrap(), vec![0x01]);
+/// ```
+#[cfg(feature = "bigint")]
+pub fn add_bytes(a: &[u8], b: &[u8], m: Option<&[u8]>) -> Result<Vec<u8>, ScalarError> {
+    let len = a.len().max(b.len());
+    let byte = |bytes: &[u8], i: usize| {
+        // The i-th byte from the right, or a padding zero.
+        bytes
+            .len()
+            .checked_sub(i + 1)
+            .map_or(0, |at| u16::from(bytes[at]))
+    };
+
+    let mut sum = vec![0u8; len];
+    let mut carry = 0u16;
+    for i in 0..len {
+        let total = byte(a, i) + byte(b, i) + carry;
+        sum[len - 1 - i] = total as u8;
+        carry = total >> 8;
+    }
+    if carry != 0 {
+        sum.insert(0, carry as u8);
+    }
+
+    match m {
+        Some(m) => modulo(&sum, m),
+        None => Ok(sum),
+    }
+}
+
+/// `(a - b) mod m`, wrapping around the modulus when `b` is larger.
+#[cfg(feature = "bigint")]
+pub fn sub(a: &[u8], b: &[u8], m: &[u8]) -> Result<Vec<u8>, ScalarError> {
+    let m = modulus(m)?;
+    let a = from_bytes(a) % &m;
+    let b = from_bytes(b) % &m;
+    Ok(to_bytes(&((a + &m - b) % &m), &m))
+}
+
+/// `(a * b) mod m`.
+#[cfg(feature = "bigint")]
+pub fn mul(a: &[u8], b: &[u8], m: &[u8]) -> Result<Vec<u8>, ScalarError> {
+    let m = modulus(m)?;
+    Ok(to_bytes(&((from_bytes(a) * from_bytes(b)) % &m), &m))
+}
+
+/// `base^exponent mod m`.
+#[cfg(feature = "bigint")]
+pub fn pow(base: &[u8], exponent: &[u8], m: &[u8]) -> Result<Vec<u8>, ScalarError> {
+    let m = modulus(m)?;
+    Ok(to_bytes(
+        &from_bytes(base).modpow(&from_bytes(exponent), &m),
+        &m,
+    ))
+}
+
+/// The `x` with `a * x = 1 mod m`.
+///
+/// Fails with `ScalarError::NotInvertible` if `a` and `m` share a factor,
+/// which for a prime `m` means `a` is a multiple of it.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::scalar;
+///
+/// assert_eq!(scalar::inverse(&[3], &[7]).unwrap(), vec![5]);
+/// assert!(scalar::inverse(&[14], &[7]).is_err());
+/// ```
+#[cfg(feature = "bigint")]
+pub fn inverse(a: &[u8], m: &[u8]) -> Result<Vec<u8>, ScalarError> {
+    let m = modulus(m)?;
+    let inverse = from_bytes(a).modinv(&m).ok_or(ScalarError::NotInvertible)?;
+    Ok(to_bytes(&inverse, &m))
+}
+
+#[cfg(feature = "bigint")]
+fn from_bytes(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes)
+}
+
+#[cfg(feature = "bigint")]
+fn modulus(m: &[u8]) -> Result<BigUint, ScalarError> {
+    let m = from_bytes(m);
+    if m == BigUint::default() {
+        return Err(ScalarError::ZeroModulus);
+    }
+    Ok(m)
+}
+
+/// `value` in big-endian, left-padded to the byte length of `m`.
+#[cfg(feature = "bigint")]
+fn to_bytes(value: &BigUint, m: &BigUint) -> Vec<u8> {
+    let len = m.bits().div_ceil(8) as usize;
+    let bytes = value.to_bytes_be();
+    let mut padded = vec![0u8; len.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
+#[cfg(all(test, feature = "bigint"))]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn arithmetic_modulo_curve_order() -> Result<(), ScalarError> {
+        let n = CURVE_ORDER;
+        let one = [1u8];
+        let n_minus_one = sub(&[], &one, &n)?;
+        assert_eq!(n_minus_one.len(), 32);
+        assert_eq!(n_minus_one[31], 0x40);
+
+        // Mismatched lengths, and wrapping around the order.
+        assert_eq!(add(&n_minus_one, &[0, 0, 2], &n)?, modulo(&one, &n)?);
+        assert_eq!(sub(&one, &[2], &n)?, n_minus_one);
+        assert_eq!(mul(&n_minus_one, &n_minus_one, &n)?, modulo(&one, &n)?);
+        assert_eq!(modulo(&n, &n)?, vec![0u8; 32]);
+
+        let seven = [7u8];
+        let inverse_seven = inverse(&seven, &n)?;
+        assert_eq!(mul(&seven, &inverse_seven, &n)?, modulo(&one, &n)?);
+        // Fermat's little theorem: a^(n - 2) is the inverse of a.
+        let n_minus_two = sub(&[], &[2], &n)?;
+        assert_eq!(pow(&seven, &n_minus_two, &n)?, inverse_seven);
+
+        assert_eq!(inverse(&[], &n), Err(ScalarError::NotInvertible));
+        assert_eq!(
+            add_bytes(&one, &one, Some(&[])),
+            Err(ScalarError::ZeroModulus)
+        );
+        assert_eq!(add(&one, &one, &[0, 0]), Err(ScalarError::ZeroModulus));
+        Ok(())
+    }
+
+    #[test]
+    fn add_bytes_carries() -> Result<(), ScalarError> {
+        // A carry running through every byte.
+        assert_eq!(
+            add_bytes(&[0x00, 0xff, 0xff, 0xff], &[0x01], None)?,
+            vec![0x01, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(add_bytes(&[0x80], &[0x80], None)?, vec![0x01, 0x00]);
+        assert_eq!(add_bytes(&[0x12, 0x34], &[0xab], None)?, vec![0x12, 0xdf]);
+        assert_eq!(add_bytes(&[], &[], None)?, Vec::<u8>::new());
+        // Either operand may be the longer one.
+        assert_eq!(
+            add_bytes(&[0x01], &[0x01, 0xff], None)?,
+            add_bytes(&[0x01, 0xff], &[0x01], None)?
+        );
+
+        let n = CURVE_ORDER;
+        let sum = add_bytes(&n, &n, None)?;
+        assert_eq!(sum.len(), 33);
+        assert_eq!(add_bytes(&n, &n, Some(&n))?, vec![0u8; 32]);
+        Ok(())
+    }
+
+    #[test]
+    fn domain_constants() -> Result<(), ScalarError> {
+        // n = 2 * (n / 2) + 1.
+        let doubled = add_bytes(&HALF_CURVE_ORDER, &HALF_CURVE_ORDER, None)?;
+        assert_eq!(add_bytes(&doubled, &[1], None)?, CURVE_ORDER);
+
+        // G is on y^2 = x^3 + 7.
+        let (x, y) = GENERATOR_UNCOMPRESSED[1..].split_at(32);
+        let x_cubed = mul(&mul(x, x, &FIELD_PRIME)?, x, &FIELD_PRIME)?;
+        assert_eq!(mul(y, y, &FIELD_PRIME)?, add(&x_cubed, &[7], &FIELD_PRIME)?);
+        assert_eq!(GENERATOR[1..], *x);
+        assert_eq!(GENERATOR[0], 0x02 + (y[31] & 1));
+        Ok(())
+    }
+}
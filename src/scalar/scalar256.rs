@@ -0,0 +1,313 @@
+//! Constant-time arithmetic modulo the secp256k1 curve order.
+//!
+//! Nothing here allocates or needs more than `core`, unlike the `BigUint`
+//! functions of the parent module, so signing paths can use it freely and
+//! it builds without the `bigint` feature.
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use super::CURVE_ORDER;
+
+/// The curve order `n` in little-endian 64-bit limbs.
+const N: [u64; 4] = [
+    0xbfd2_5e8c_d036_4141,
+    0xbaae_dce6_af48_a03b,
+    0xffff_ffff_ffff_fffe,
+    0xffff_ffff_ffff_ffff,
+];
+
+/// `2^256 - n`, which `2^256` is congruent to modulo `n`.
+const N_COMPLEMENT: [u64; 4] = [0x402d_a173_2fc9_bebf, 0x4551_2319_50b7_5fc4, 1, 0];
+
+/// An integer modulo the secp256k1 curve order, such as a secret key or a
+/// tweak.
+///
+/// Arithmetic runs in the same time whatever the values, without
+/// allocating, so it is safe on secret key material. Only
+/// [inverse](Scalar256::inverse) reveals anything, namely whether its input
+/// was zero.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::scalar::Scalar256;
+///
+/// let a = Scalar256::from_bytes_mod_order(&[7u8; 32]);
+/// let b = a.inverse().unwrap();
+/// assert_eq!(a.mul(&b), Scalar256::ONE);
+/// assert_eq!(a.add(&a.negate()), Scalar256::ZERO);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Scalar256([u64; 4]);
+
+impl Scalar256 {
+    pub const ZERO: Scalar256 = Scalar256([0; 4]);
+    pub const ONE: Scalar256 = Scalar256([1, 0, 0, 0]);
+
+    /// Read 32 big-endian bytes, or `None` if they are not below the curve
+    /// order.
+    ///
+    /// Use [from_bytes_mod_order](Scalar256::from_bytes_mod_order) for hash
+    /// outputs, which are reduced rather than rejected.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let limbs = limbs_from_bytes(bytes);
+        let (_, borrow) = sub_limbs(&limbs, &N);
+        if borrow == 1 {
+            Some(Scalar256(limbs))
+        } else {
+            None
+        }
+    }
+
+    /// Read 32 big-endian bytes, reducing them modulo the curve order.
+    pub fn from_bytes_mod_order(bytes: &[u8; 32]) -> Self {
+        // Any 256-bit value is below 2n, so one subtraction is enough.
+        Scalar256(reduce_once(limbs_from_bytes(bytes), 0))
+    }
+
+    /// The 32 big-endian bytes of the scalar.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            bytes[24 - 8 * i..32 - 8 * i].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.ct_eq(&Self::ZERO).into()
+    }
+
+    /// `self + other mod n`.
+    pub fn add(&self, other: &Scalar256) -> Scalar256 {
+        let (sum, carry) = add_limbs(&self.0, &other.0);
+        Scalar256(reduce_once(sum, carry))
+    }
+
+    /// `self - other mod n`.
+    pub fn sub(&self, other: &Scalar256) -> Scalar256 {
+        self.add(&other.negate())
+    }
+
+    /// `-self mod n`, which is zero for zero.
+    pub fn negate(&self) -> Scalar256 {
+        let (difference, _) = sub_limbs(&N, &self.0);
+        Scalar256::conditional_select(
+            &Scalar256(difference),
+            &Self::ZERO,
+            Choice::from(u8::from(self.is_zero())),
+        )
+    }
+
+    /// `self * other mod n`.
+    pub fn mul(&self, other: &Scalar256) -> Scalar256 {
+        let mut wide = mul_wide(&self.0, &other.0);
+        // Fold the top half in as a multiple of 2^256 - n. Four folds bring
+        // any 512-bit product below 2^256.
+        for _ in 0..4 {
+            let mut high = [0u64; 4];
+            high.copy_from_slice(&wide[4..]);
+            let folded = mul_wide(&high, &N_COMPLEMENT);
+            let mut low = [0u64; 8];
+            low[..4].copy_from_slice(&wide[..4]);
+            wide = add_wide(&folded, &low);
+        }
+        let mut limbs = [0u64; 4];
+        limbs.copy_from_slice(&wide[..4]);
+        Scalar256(reduce_once(limbs, 0))
+    }
+
+    /// `self^-1 mod n`, or `None` for zero, which has no inverse.
+    ///
+    /// Computed as `self^(n - 2)`, whose square-and-multiply steps depend
+    /// only on the public order.
+    pub fn inverse(&self) -> Option<Scalar256> {
+        if self.is_zero() {
+            return None;
+        }
+        let mut exponent = CURVE_ORDER;
+        exponent[31] -= 2;
+
+        let mut result = Self::ONE;
+        for byte in exponent.iter() {
+            for bit in (0..8).rev() {
+                result = result.mul(&result);
+                if (byte >> bit) & 1 == 1 {
+                    result = result.mul(self);
+                }
+            }
+        }
+        Some(result)
+    }
+}
+
+impl ConstantTimeEq for Scalar256 {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+impl ConditionallySelectable for Scalar256 {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = u64::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+        Scalar256(limbs)
+    }
+}
+
+/// Compared in constant time.
+impl PartialEq for Scalar256 {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for Scalar256 {}
+
+fn limbs_from_bytes(bytes: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let mut be = [0u8; 8];
+        be.copy_from_slice(&bytes[24 - 8 * i..32 - 8 * i]);
+        *limb = u64::from_be_bytes(be);
+    }
+    limbs
+}
+
+/// `limbs + carry * 2^256 - n` if that is not negative, or `limbs`.
+fn reduce_once(limbs: [u64; 4], carry: u64) -> [u64; 4] {
+    let (difference, borrow) = sub_limbs(&limbs, &N);
+    // Keep the difference unless the subtraction went below zero, which a
+    // carry out of the sum makes up for.
+    let keep_limbs = Choice::from((borrow & !carry & 1) as u8);
+    let mut reduced = [0u64; 4];
+    for i in 0..4 {
+        reduced[i] = u64::conditional_select(&difference[i], &limbs[i], keep_limbs);
+    }
+    reduced
+}
+
+fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut sum = [0u64; 4];
+    let mut carry = 0u64;
+    for i in 0..4 {
+        let total = u128::from(a[i]) + u128::from(b[i]) + u128::from(carry);
+        sum[i] = total as u64;
+        carry = (total >> 64) as u64;
+    }
+    (sum, carry)
+}
+
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut difference = [0u64; 4];
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let total = u128::from(a[i])
+            .wrapping_sub(u128::from(b[i]))
+            .wrapping_sub(u128::from(borrow));
+        difference[i] = total as u64;
+        borrow = ((total >> 64) as u64) & 1;
+    }
+    (difference, borrow)
+}
+
+fn mul_wide(a: &[u64; 4], b: &[u64; 4]) -> [u64; 8] {
+    let mut product = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let total = u128::from(a[i]) * u128::from(b[j]) + u128::from(product[i + j]) + carry;
+            product[i + j] = total as u64;
+            carry = total >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+    product
+}
+
+fn add_wide(a: &[u64; 8], b: &[u64; 8]) -> [u64; 8] {
+    let mut sum = [0u64; 8];
+    let mut carry = 0u128;
+    for i in 0..8 {
+        let total = u128::from(a[i]) + u128::from(b[i]) + carry;
+        sum[i] = total as u64;
+        carry = total >> 64;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    #[cfg(feature = "bigint")]
+    use crate::{crypto, scalar};
+
+    #[cfg(feature = "bigint")]
+    fn scalar(seed: u8) -> ([u8; 32], Scalar256) {
+        let mut bytes = crypto::sha256(&[seed]);
+        // Include values close to the order, and above it.
+        if seed % 3 == 0 {
+            bytes[..16].copy_from_slice(&[0xff; 16]);
+        }
+        (bytes, Scalar256::from_bytes_mod_order(&bytes))
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn matches_big_integer_arithmetic() -> Result<(), scalar::ScalarError> {
+        let n = CURVE_ORDER;
+        for seed in 0..24u8 {
+            let (a_bytes, a) = scalar(seed);
+            let (b_bytes, b) = scalar(seed.wrapping_mul(7).wrapping_add(1));
+
+            assert_eq!(a.to_bytes()[..], scalar::modulo(&a_bytes, &n)?[..]);
+            assert_eq!(
+                a.add(&b).to_bytes()[..],
+                scalar::add(&a_bytes, &b_bytes, &n)?[..]
+            );
+            assert_eq!(
+                a.sub(&b).to_bytes()[..],
+                scalar::sub(&a_bytes, &b_bytes, &n)?[..]
+            );
+            assert_eq!(
+                a.mul(&b).to_bytes()[..],
+                scalar::mul(&a_bytes, &b_bytes, &n)?[..]
+            );
+            assert_eq!(
+                a.inverse().unwrap().to_bytes()[..],
+                scalar::inverse(&a_bytes, &n)?[..]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn edge_cases() {
+        let n_minus_one = Scalar256::ZERO.sub(&Scalar256::ONE);
+        assert_eq!(n_minus_one.add(&Scalar256::ONE), Scalar256::ZERO);
+        assert_eq!(n_minus_one.mul(&n_minus_one), Scalar256::ONE);
+        assert_eq!(n_minus_one.inverse(), Some(n_minus_one));
+        assert_eq!(Scalar256::ZERO.negate(), Scalar256::ZERO);
+        assert_eq!(Scalar256::ZERO.inverse(), None);
+
+        assert!(Scalar256::from_bytes_mod_order(&CURVE_ORDER).is_zero());
+        assert_eq!(Scalar256::from_bytes(&CURVE_ORDER), None);
+        let mut order_plus_one = CURVE_ORDER;
+        order_plus_one[31] += 1;
+        assert_eq!(
+            Scalar256::from_bytes_mod_order(&order_plus_one),
+            Scalar256::ONE
+        );
+        assert_eq!(
+            Scalar256::from_bytes(&n_minus_one.to_bytes()),
+            Some(n_minus_one)
+        );
+        assert_eq!(
+            hex::encode(Scalar256::from_bytes_mod_order(&[0xff; 32]).to_bytes()),
+            "000000000000000000000000000000014551231950b75fc4402da1732fc9bebe"
+        );
+    }
+}
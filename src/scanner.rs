@@ -0,0 +1,194 @@
+//! Gap-limit scanning of a derivation chain, as used to restore a wallet.
+//!
+//! A wallet hands out receive keys in order, so once `gap_limit` consecutive
+//! keys are unused no later key is expected to be either. The scan reports the
+//! index of the first fresh key after the last used one.
+
+use anyhow::Result;
+
+use crate::bip32::Bip32Error;
+use crate::path::HARDENED_OFFSET;
+use crate::{Address, AddressType, ExtendedPubKey, Network};
+
+/// Gap limit recommended by BIP44.
+const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// Walk the children of a chain key, such as `m/84'/0'/0'/0`, looking for the
+/// first unused index.
+///
+/// The caller's `is_used` closure receives the `script_type` address of each
+/// child, with P2SH standing for P2SH-P2WPKH as in
+/// [ExtendedPubKey::addresses](ExtendedPubKey::addresses).
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::scanner::Scanner;
+/// use keymaker::{AddressType, ExtendedPrivKey, Network, SeedBuilder};
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let chain = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+///     .unwrap()
+///     .derive_path(&"m/84'/0'/0'/0".parse().unwrap())
+///     .unwrap()
+///     .neuter();
+///
+/// // Nothing has been used, so the wallet starts at index 0.
+/// let next = Scanner::new(chain, AddressType::P2wpkh)
+///     .gap_limit(5)
+///     .scan(|address| {
+///         assert!(address.to_string().starts_with("bc1q"));
+///         false
+///     })
+///     .unwrap();
+/// assert_eq!(next, 0);
+/// ```
+pub struct Scanner {
+    chain: ExtendedPubKey,
+    script_type: AddressType,
+    network: Network,
+    gap_limit: u32,
+}
+
+impl Scanner {
+    /// Scan `chain` for `script_type` addresses on the network of the key.
+    pub fn new(chain: ExtendedPubKey, script_type: AddressType) -> Self {
+        Scanner {
+            network: chain.network(),
+            chain,
+            script_type,
+            gap_limit: DEFAULT_GAP_LIMIT,
+        }
+    }
+
+    /// Set the network of the addresses, for a chain whose extended keys
+    /// share the version bytes of another network, such as Litecoin.
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Set how many consecutive unused keys end the scan. Defaults to 20.
+    pub fn gap_limit(mut self, gap_limit: u32) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Scan the chain and return the index of the next fresh key.
+    ///
+    /// An index whose child key is invalid is skipped, as BIP32 specifies,
+    /// but still counts toward the gap limit.
+    ///
+    /// Fails if the script type cannot be used on the network, such as
+    /// P2WPKH on Dogecoin or P2WSH, which has no single-key form.
+    pub fn scan<F>(&self, mut is_used: F) -> Result<u32>
+    where
+        F: FnMut(&Address) -> bool,
+    {
+        let mut next = 0;
+        let mut index = 0;
+        while index < HARDENED_OFFSET && index - next < self.gap_limit {
+            let child = match self.chain.derive_child(index) {
+                Ok(child) => child,
+                Err(e) if matches!(e.downcast_ref(), Some(Bip32Error::InvalidChild(_))) => {
+                    index += 1;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let address = Address::from_public(&child.pubkey(), self.script_type, self.network)?;
+            if is_used(&address) {
+                next = index + 1;
+            }
+            index += 1;
+        }
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{crypto, DisplayLayout, ExtendedPrivKey};
+    use base58::ToBase58;
+    use std::cell::Cell;
+
+    #[test]
+    fn scan_stops_after_gap() -> Result<()> {
+        let chain = ExtendedPrivKey::new([9u8; 64], None, Network::Mainnet, true)?.neuter();
+        let used: Vec<Address> = [0, 1, 4, 10]
+            .iter()
+            .map(|i| {
+                Ok(Address::p2wpkh(
+                    &chain.derive_child(*i)?.pubkey(),
+                    Network::Mainnet,
+                )?)
+            })
+            .collect::<Result<_>>()?;
+
+        let checked = Cell::new(0);
+        let is_used = |address: &Address| {
+            checked.set(checked.get() + 1);
+            used.contains(address)
+        };
+
+        // Index 10 is six keys past the last used index 4.
+        let scanner = Scanner::new(chain.clone(), AddressType::P2wpkh);
+        assert_eq!(scanner.gap_limit(5).scan(is_used)?, 5);
+        assert_eq!(checked.get(), 10);
+
+        let scanner = Scanner::new(chain.clone(), AddressType::P2wpkh);
+        assert_eq!(scanner.gap_limit(6).scan(is_used)?, 11);
+
+        // The same keys as P2PKH addresses were never used.
+        assert_eq!(
+            Scanner::new(chain.clone(), AddressType::P2pkh).scan(is_used)?,
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scan_skips_invalid_children() -> Result<()> {
+        // A key at depth 255 has no valid children, since their depth would
+        // not fit in a byte, so every index is skipped until the gap ends.
+        let chain = ExtendedPrivKey::new([9u8; 64], None, Network::Mainnet, true)?.neuter();
+        let mut data = chain.layout();
+        data.truncate(78);
+        data[4] = u8::MAX;
+        let cs = crypto::checksum(&data);
+        data.extend_from_slice(&cs);
+        let chain: ExtendedPubKey = data.to_base58().parse()?;
+        assert!(chain.derive_child(0).is_err());
+
+        let mut checked = 0;
+        let next = Scanner::new(chain, AddressType::P2wpkh)
+            .gap_limit(5)
+            .scan(|_| {
+                checked += 1;
+                true
+            })?;
+        assert_eq!(next, 0);
+        assert_eq!(checked, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn scan_uses_the_network() -> Result<()> {
+        let chain = ExtendedPrivKey::new([9u8; 64], None, Network::Mainnet, true)?.neuter();
+        let mut seen = vec![];
+        Scanner::new(chain.clone(), AddressType::P2wpkh)
+            .network(Network::Litecoin)
+            .gap_limit(1)
+            .scan(|address| {
+                seen.push(address.to_string());
+                false
+            })?;
+        assert!(seen[0].starts_with("ltc1q"));
+
+        let dogecoin = Scanner::new(chain, AddressType::P2wpkh).network(Network::Dogecoin);
+        assert!(dogecoin.scan(|_| false).is_err());
+        Ok(())
+    }
+}
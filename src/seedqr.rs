@@ -0,0 +1,206 @@
+//! [SeedQR] and CompactSeedQR encodings of a mnemonic, as used by SeedSigner
+//! and Coldcard.
+//!
+//! * SeedQR concatenates the wordlist index of every word as four decimal
+//!   digits and is encoded as a numeric QR code.
+//! * CompactSeedQR is the raw BIP39 entropy (16 or 32 bytes) encoded as a
+//!   byte-mode QR code.
+//!
+//! Both formats only exist for 12 and 24-word mnemonics.
+//!
+//! Rendering to SVG requires the `qr` feature.
+//!
+//! [SeedQR]: https://github.com/SeedSigner/seedsigner/blob/dev/docs/seed_qr/README.md
+
+use std::str;
+
+use crate::bip39::{self, Bip39Error, Language, Mnemonic, Seed};
+
+#[cfg(feature = "qr")]
+use qrcode::{bits::Bits, render::svg, EcLevel, QrCode, QrResult, Version};
+
+const DIGITS_PER_WORD: usize = 4;
+const WORD_COUNTS: [usize; 2] = [12, 24];
+const COMPACT_LENGTHS: [usize; 2] = [16, 32];
+
+impl Seed {
+    /// Encode the mnemonic as a SeedQR digit string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::bip39::SeedBuilder;
+    /// use keymaker::seedqr;
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let digits = seed.to_seedqr().unwrap();
+    /// assert_eq!(digits.len(), 48);
+    /// assert_eq!(seedqr::from_seedqr(digits.as_bytes()).unwrap(), seed.mnemonic);
+    /// ```
+    ///
+    /// Fails with `Bip39Error::InvalidWordCount` unless the mnemonic has 12
+    /// or 24 words.
+    pub fn to_seedqr(&self) -> Result<String, Bip39Error> {
+        check_length(&self.mnemonic)?;
+        let indices = self.mnemonic.indices()?;
+        Ok(indices.iter().map(|i| format!("{:04}", i)).collect())
+    }
+
+    /// Encode the mnemonic as a CompactSeedQR payload, which is the raw entropy.
+    ///
+    /// Fails as [to_seedqr](Seed::to_seedqr) does.
+    pub fn to_compact_seedqr(&self) -> Result<Vec<u8>, Bip39Error> {
+        check_length(&self.mnemonic)?;
+        Ok(self.mnemonic.entropy().to_vec())
+    }
+
+    /// Render the SeedQR as an SVG document.
+    #[cfg(feature = "qr")]
+    pub fn to_seedqr_svg(&self) -> Result<String, Bip39Error> {
+        let digits = self.to_seedqr()?;
+        render_svg(|bits| bits.push_numeric_data(digits.as_bytes()))
+    }
+
+    /// Render the CompactSeedQR as an SVG document.
+    #[cfg(feature = "qr")]
+    pub fn to_compact_seedqr_svg(&self) -> Result<String, Bip39Error> {
+        let entropy = self.to_compact_seedqr()?;
+        render_svg(|bits| bits.push_byte_data(&entropy))
+    }
+}
+
+/// Decode a scanned SeedQR or CompactSeedQR payload into an English mnemonic.
+///
+/// The format is detected from the payload: 12 or 24 4-digit word indices
+/// are a SeedQR, 16 or 32 raw bytes are a CompactSeedQR.
+pub fn from_seedqr(data: &[u8]) -> Result<Mnemonic, Bip39Error> {
+    let is_seedqr = data.len() % DIGITS_PER_WORD == 0
+        && WORD_COUNTS.contains(&(data.len() / DIGITS_PER_WORD))
+        && data.iter().all(u8::is_ascii_digit);
+
+    if is_seedqr {
+        let indices = data
+            .chunks(DIGITS_PER_WORD)
+            .map(|chunk| {
+                str::from_utf8(chunk)
+                    .ok()
+                    .and_then(|digits| digits.parse::<usize>().ok())
+                    .ok_or(Bip39Error::InvalidSeedQr)
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        let words = bip39::indices_to_mnemonic(&indices, Language::English)?;
+        Mnemonic::from_phrase_in(Language::English, &words.join(" "))
+    } else if COMPACT_LENGTHS.contains(&data.len()) {
+        Mnemonic::from_entropy(data)
+    } else {
+        Err(Bip39Error::InvalidSeedQr)
+    }
+}
+
+/// Only 12 and 24-word mnemonics have a SeedQR, so that every encoded
+/// payload decodes with [from_seedqr](from_seedqr).
+fn check_length(mnemonic: &Mnemonic) -> Result<(), Bip39Error> {
+    if COMPACT_LENGTHS.contains(&mnemonic.entropy().len()) {
+        Ok(())
+    } else {
+        Err(Bip39Error::InvalidWordCount(mnemonic.len()))
+    }
+}
+
+/// Encode with the smallest QR version that fits, at the low error
+/// correction level the SeedQR specification mandates.
+#[cfg(feature = "qr")]
+fn render_svg<F>(push: F) -> Result<String, Bip39Error>
+where
+    F: Fn(&mut Bits) -> QrResult<()>,
+{
+    let mut last_error = None;
+    for version in 1..=10 {
+        let mut bits = Bits::new(Version::Normal(version));
+        let encoded = push(&mut bits)
+            .and_then(|_| bits.push_terminator(EcLevel::L))
+            .and_then(|_| QrCode::with_bits(bits, EcLevel::L));
+        match encoded {
+            Ok(code) => return Ok(code.render::<svg::Color>().build()),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(Bip39Error::QrEncoding(format!("{:?}", last_error)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::bip39::{MnemonicSize, SeedBuilder};
+
+    #[test]
+    fn seedqr_round_trip() -> Result<(), Bip39Error> {
+        let seed = SeedBuilder::new().size(MnemonicSize::Size24Words).build()?;
+
+        let digits = seed.to_seedqr()?;
+        assert_eq!(digits.len(), 96);
+        assert_eq!(from_seedqr(digits.as_bytes())?, seed.mnemonic);
+
+        let compact = seed.to_compact_seedqr()?;
+        assert_eq!(compact.len(), 32);
+        assert_eq!(from_seedqr(&compact)?, seed.mnemonic);
+
+        assert!(from_seedqr(b"0000").is_err());
+        assert!(from_seedqr(&[0u8; 20]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn seedqr_rejects_other_lengths() -> Result<(), Bip39Error> {
+        for (entropy_len, words) in [(20, 15), (24, 18), (28, 21)] {
+            let seed = Seed {
+                mnemonic: Mnemonic::from_entropy(&vec![7u8; entropy_len])?,
+                entropy: [0u8; 64],
+                hex: String::new(),
+            };
+            assert!(matches!(
+                seed.to_seedqr(),
+                Err(Bip39Error::InvalidWordCount(n)) if n == words
+            ));
+            assert!(matches!(
+                seed.to_compact_seedqr(),
+                Err(Bip39Error::InvalidWordCount(n)) if n == words
+            ));
+
+            let digits: String = seed
+                .mnemonic
+                .indices()?
+                .iter()
+                .map(|i| format!("{:04}", i))
+                .collect();
+            assert!(from_seedqr(digits.as_bytes()).is_err());
+            assert!(from_seedqr(seed.mnemonic.entropy()).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn seedqr_known_vector() -> Result<(), Bip39Error> {
+        let seed = Seed {
+            mnemonic: Mnemonic::from_entropy(&[0u8; 16])?,
+            entropy: [0u8; 64],
+            hex: String::new(),
+        };
+        assert_eq!(
+            seed.to_seedqr()?,
+            "000000000000000000000000000000000000000000000003"
+        );
+        assert_eq!(seed.to_compact_seedqr()?, vec![0u8; 16]);
+        Ok(())
+    }
+
+    #[cfg(feature = "qr")]
+    #[test]
+    fn seedqr_svg() -> Result<(), Bip39Error> {
+        let seed = SeedBuilder::new().build()?;
+        assert!(seed.to_seedqr_svg()?.starts_with("<?xml"));
+        assert!(seed.to_compact_seedqr_svg()?.starts_with("<?xml"));
+        Ok(())
+    }
+}
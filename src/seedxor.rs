@@ -0,0 +1,117 @@
+//! Coldcard [Seed XOR] backups.
+//!
+//! The entropy of a mnemonic is split into N shares such that the XOR of all
+//! shares' entropy is the original entropy. Every share is itself a valid
+//! BIP39 mnemonic of the same length, so a single share is indistinguishable
+//! from an ordinary wallet and reveals nothing about the original.
+//!
+//! [Seed XOR]: https://seedxor.com
+
+use rand_core::{OsRng, RngCore};
+
+use crate::bip39::{Bip39Error, Mnemonic};
+
+const MIN_SHARES: usize = 2;
+
+impl Mnemonic {
+    /// Split the mnemonic into `n` Seed XOR shares in the same language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::bip39::SeedBuilder;
+    /// use keymaker::Mnemonic;
+    ///
+    /// let seed = SeedBuilder::new().build().unwrap();
+    /// let shares = seed.mnemonic.xor_split(3).unwrap();
+    /// assert_eq!(shares.len(), 3);
+    /// assert_eq!(Mnemonic::xor_combine(&shares).unwrap(), seed.mnemonic);
+    /// ```
+    pub fn xor_split(&self, n: usize) -> Result<Vec<Mnemonic>, Bip39Error> {
+        if n < MIN_SHARES {
+            return Err(Bip39Error::InvalidShareCount(n));
+        }
+
+        let mut last = self.entropy().to_vec();
+        let mut shares = Vec::with_capacity(n);
+        for _ in 1..n {
+            let mut share = vec![0u8; last.len()];
+            OsRng.fill_bytes(&mut share);
+            xor_into(&mut last, &share);
+            shares.push(Mnemonic::from_entropy_in(self.language(), &share)?);
+        }
+        shares.push(Mnemonic::from_entropy_in(self.language(), &last)?);
+        Ok(shares)
+    }
+
+    /// Recombine Seed XOR shares, in any order, into the original mnemonic,
+    /// in the language of the first share.
+    pub fn xor_combine(shares: &[Mnemonic]) -> Result<Mnemonic, Bip39Error> {
+        if shares.len() < MIN_SHARES {
+            return Err(Bip39Error::InvalidShareCount(shares.len()));
+        }
+
+        if shares.iter().any(|share| share.len() != shares[0].len()) {
+            return Err(Bip39Error::ShareLengthMismatch);
+        }
+
+        let mut entropy = shares[0].entropy().to_vec();
+        for share in &shares[1..] {
+            xor_into(&mut entropy, share.entropy());
+        }
+        Mnemonic::from_entropy_in(shares[0].language(), &entropy)
+    }
+}
+
+fn xor_into(acc: &mut [u8], other: &[u8]) {
+    acc.iter_mut().zip(other).for_each(|(a, b)| *a ^= b);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::bip39::{Language, MnemonicSize, SeedBuilder};
+
+    #[test]
+    fn xor_split_combine() -> Result<(), Bip39Error> {
+        let seed = SeedBuilder::new().size(MnemonicSize::Size24Words).build()?;
+        let mut shares = seed.mnemonic.xor_split(4)?;
+
+        for share in &shares {
+            assert_eq!(share.len(), 24);
+        }
+
+        shares.reverse();
+        assert_eq!(Mnemonic::xor_combine(&shares)?, seed.mnemonic);
+        assert_ne!(Mnemonic::xor_combine(&shares[1..])?, seed.mnemonic);
+
+        assert!(matches!(
+            seed.mnemonic.xor_split(1),
+            Err(Bip39Error::InvalidShareCount(1))
+        ));
+        shares[0] = Mnemonic::from_entropy(&[1u8; 16])?;
+        assert!(matches!(
+            Mnemonic::xor_combine(&shares),
+            Err(Bip39Error::ShareLengthMismatch)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn xor_known_vector() -> Result<(), Bip39Error> {
+        // 0x00.. XOR 0xff.. == 0xff..
+        let zero = Mnemonic::from_entropy(&[0u8; 16])?;
+        let ones = Mnemonic::from_entropy(&[0xffu8; 16])?;
+        assert_eq!(Mnemonic::xor_combine(&[zero.clone(), ones.clone()])?, ones);
+        assert_eq!(Mnemonic::xor_combine(&[ones.clone(), ones])?, zero);
+
+        let spanish = zero.reencode(Language::Spanish)?;
+        let shares = spanish.xor_split(2)?;
+        assert!(shares
+            .iter()
+            .all(|share| share.language() == Language::Spanish));
+        assert_eq!(Mnemonic::xor_combine(&shares)?, spanish);
+        Ok(())
+    }
+}
@@ -2,14 +2,17 @@
 //!
 //! http://bitcoin.stackexchange.com/q/12554/40688
 
-use hex;
 use secp256k1::bitcoin_hashes::hex::ToHex;
+use secp256k1::recovery::{RecoverableSignature as SecpRecoverableSignature, RecoveryId};
+use secp256k1::Message as SecpMessage;
 use secp256k1::SerializedSignature as SecpSerSignature;
 use secp256k1::Signature as SecpSignature;
 use std::convert::TryInto;
 use std::{fmt, ops, str};
 
-use crate::{Error, Hash520Bits};
+use crate::crypto;
+use crate::scalar::HALF_CURVE_ORDER;
+use crate::{Error, Hash256Bits, Hash520Bits, PublicKey, XOnlyPublicKey, SECP256K1};
 
 #[derive(PartialEq)]
 pub struct Signature(pub Vec<u8>);
@@ -72,11 +75,255 @@ impl From<Signature> for Vec<u8> {
 }
 
 impl Signature {
+    /// Parse a DER signature, rejecting any encoding [BIP66] does not allow.
+    ///
+    /// [BIP66]: https://github.com/bitcoin/bips/blob/master/bip-0066.mediawiki
+    pub fn from_der_strict(data: &[u8]) -> Result<Self, Error> {
+        let signature = Signature(data.to_vec());
+        if !signature.is_strict_der() {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(signature)
+    }
+
+    /// Whether this is the single canonical DER encoding of its `r` and
+    /// `s`, as BIP66 requires: consistent lengths, positive integers and no
+    /// needless leading zero bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::Signature;
+    ///
+    /// // r = 1 and s = 1, then r padded with a needless zero byte.
+    /// assert!(Signature::from("3006020101020101").is_strict_der());
+    /// assert!(!Signature::from("300702020001020101").is_strict_der());
+    /// ```
+    pub fn is_strict_der(&self) -> bool {
+        let sig = &self.0;
+        // 0x30 <len> 0x02 <len R> <R> 0x02 <len S> <S>
+        if sig.len() < 8 || sig.len() > 72 {
+            return false;
+        }
+        if sig[0] != 0x30 || usize::from(sig[1]) != sig.len() - 2 {
+            return false;
+        }
+        let len_r = usize::from(sig[3]);
+        if 5 + len_r >= sig.len() {
+            return false;
+        }
+        let len_s = usize::from(sig[5 + len_r]);
+        if len_r + len_s + 6 != sig.len() {
+            return false;
+        }
+
+        let is_canonical_integer = |marker: u8, int: &[u8]| {
+            marker == 0x02
+                && !int.is_empty()
+                // Not negative.
+                && int[0] & 0x80 == 0
+                // No leading zero unless the next byte would read as negative.
+                && !(int.len() > 1 && int[0] == 0x00 && int[1] & 0x80 == 0)
+        };
+        is_canonical_integer(sig[2], &sig[4..4 + len_r])
+            && is_canonical_integer(sig[4 + len_r], &sig[6 + len_r..])
+    }
+
+    /// Build the DER signature of the 64-byte `r || s` form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::Signature;
+    ///
+    /// let mut compact = [0u8; 64];
+    /// compact[31] = 1;
+    /// compact[63] = 1;
+    /// let signature = Signature::from_compact(&compact).unwrap();
+    /// assert_eq!(signature.to_string(), "3006020101020101");
+    /// assert_eq!(signature.to_compact().unwrap()[..], compact[..]);
+    /// ```
+    pub fn from_compact(compact: &[u8; 64]) -> Result<Self, Error> {
+        let signature = SecpSignature::from_compact(compact)?;
+        Ok(signature.serialize_der().into())
+    }
+
+    /// The 64-byte `r || s` form of this DER signature.
+    pub fn to_compact(&self) -> Result<[u8; 64], Error> {
+        Ok(SecpSignature::from_der(&self.0)?.serialize_compact())
+    }
+
+    /// Whether `s` is in the lower half of the curve order, as Bitcoin's
+    /// standardness rules require to rule out the malleated twin `n - s`.
+    ///
+    /// Returns `false` if this is not a valid DER signature.
     pub fn check_low_s(&self) -> bool {
-        unimplemented!();
+        match SecpSignature::from_der(&self.0) {
+            Ok(signature) => signature.serialize_compact()[32..] <= HALF_CURVE_ORDER[..],
+            Err(_) => false,
+        }
+    }
+
+    /// Whether `r` fits in 32 bytes of DER without a sign padding byte, which
+    /// makes the whole signature at most 70 bytes.
+    ///
+    /// Returns `false` if this is not a valid DER signature.
+    pub fn check_low_r(&self) -> bool {
+        match SecpSignature::from_der(&self.0) {
+            Ok(signature) => signature.serialize_compact()[0] < 0x80,
+            Err(_) => false,
+        }
+    }
+
+    /// This DER signature with the `sighash` byte a transaction input's
+    /// script expects after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::{SighashType, Signature};
+    ///
+    /// let signature = Signature::from("3006020101020101");
+    /// let with_sighash = signature.with_sighash(SighashType::AllPlusAnyoneCanPay);
+    /// assert_eq!(with_sighash.to_string(), "300602010102010181");
+    /// assert_eq!(
+    ///     with_sighash.split_sighash().unwrap(),
+    ///     (signature, SighashType::AllPlusAnyoneCanPay)
+    /// );
+    /// ```
+    pub fn with_sighash(&self, sighash: SighashType) -> Signature {
+        let mut data = self.0.clone();
+        data.push(sighash.to_u8());
+        Signature(data)
+    }
+
+    /// Split a signature taken from a transaction input into its DER part
+    /// and its trailing sighash byte.
+    ///
+    /// Fails with `Error::InvalidSignature` if the sighash byte is not a
+    /// defined type, or what precedes it is not strict DER.
+    pub fn split_sighash(&self) -> Result<(Signature, SighashType), Error> {
+        let (sighash, der) = self.0.split_last().ok_or(Error::InvalidSignature)?;
+        let sighash = SighashType::from_u8(*sighash)?;
+        Ok((Signature::from_der_strict(der)?, sighash))
+    }
+}
+
+/// The sighash type of a transaction signature, picking which parts of the
+/// transaction it commits to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SighashType {
+    /// All inputs and outputs.
+    #[default]
+    All,
+    /// All inputs and no outputs.
+    None,
+    /// All inputs and the output at the same index as the signed input.
+    Single,
+    /// Only the signed input, and all outputs.
+    AllPlusAnyoneCanPay,
+    /// Only the signed input, and no outputs.
+    NonePlusAnyoneCanPay,
+    /// Only the signed input, and the output at its index.
+    SinglePlusAnyoneCanPay,
+}
+
+impl SighashType {
+    const ANYONECANPAY: u8 = 0x80;
+
+    /// Parse a sighash byte, failing with `Error::InvalidSignature` for any
+    /// byte other than the six defined ones.
+    pub fn from_u8(byte: u8) -> Result<Self, Error> {
+        match byte {
+            0x01 => Ok(SighashType::All),
+            0x02 => Ok(SighashType::None),
+            0x03 => Ok(SighashType::Single),
+            0x81 => Ok(SighashType::AllPlusAnyoneCanPay),
+            0x82 => Ok(SighashType::NonePlusAnyoneCanPay),
+            0x83 => Ok(SighashType::SinglePlusAnyoneCanPay),
+            _ => Err(Error::InvalidSignature),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            SighashType::All => 0x01,
+            SighashType::None => 0x02,
+            SighashType::Single => 0x03,
+            SighashType::AllPlusAnyoneCanPay => 0x81,
+            SighashType::NonePlusAnyoneCanPay => 0x82,
+            SighashType::SinglePlusAnyoneCanPay => 0x83,
+        }
+    }
+
+    /// Whether only the signed input is committed to.
+    pub fn is_anyone_can_pay(self) -> bool {
+        self.to_u8() & Self::ANYONECANPAY != 0
     }
 }
 
+/// A 32-byte digest to sign or verify.
+///
+/// Only a hash can become a message: hash data with [DataHash::hash], or
+/// wrap a digest computed elsewhere, such as a transaction sighash, with
+/// [from_prehashed](Message::from_prehashed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Message(Hash256Bits);
+
+impl Message {
+    /// Wrap a digest the caller has already computed.
+    pub fn from_prehashed(hash: [u8; 32]) -> Self {
+        Message(hash)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.to_hex()
+    }
+
+    pub(crate) fn to_secp(self) -> SecpMessage {
+        SecpMessage::from_slice(&self.0).expect("a message is 32 bytes")
+    }
+}
+
+/// How [PrivateKey::sign_data](crate::PrivateKey::sign_data) hashes its
+/// input into the 32-byte [Message] that gets signed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DataHash {
+    /// A single SHA-256.
+    Sha256,
+    /// SHA-256 applied twice, as Bitcoin does for transactions and messages.
+    #[default]
+    DoubleSha256,
+}
+
+impl DataHash {
+    /// Hash `data` into a message.
+    pub fn hash(self, data: &[u8]) -> Message {
+        Message(match self {
+            DataHash::Sha256 => crypto::sha256(data),
+            DataHash::DoubleSha256 => crypto::dhash256(data),
+        })
+    }
+}
+
+/// Options for [PrivateKey::sign_with](crate::PrivateKey::sign_with).
+///
+/// Signatures always have a low `s`. The default nonce is plain RFC6979.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SignOptions {
+    /// Grind the RFC6979 nonce with extra data until `r` is low, as Bitcoin
+    /// Core does, saving a byte of DER on about half of all signatures.
+    pub low_r: bool,
+    /// 32 bytes mixed into the RFC6979 nonce as its additional data. Fresh
+    /// randomness here guards against fault attacks on deterministic
+    /// nonces, at the cost of deterministic signatures.
+    pub extra_entropy: Option<[u8; 32]>,
+}
+
 impl<'a> From<&'a [u8]> for Signature {
     fn from(v: &'a [u8]) -> Self {
         Signature(v.to_vec())
@@ -109,11 +356,10 @@ impl ops::Deref for CompactSignature {
 impl str::FromStr for CompactSignature {
     type Err = Error;
 
+    /// Parse the 130 hex characters of a 65-byte signature.
     fn from_str(s: &str) -> Result<Self, Error> {
-        let hash: Hash520Bits = s
-            .as_bytes()
-            .try_into()
-            .map_err(|_| Error::InvalidSignature)?;
+        let data = hex::decode(s).map_err(|_| Error::InvalidSignature)?;
+        let hash: Hash520Bits = data[..].try_into().map_err(|_| Error::InvalidSignature)?;
         Ok(CompactSignature(hash))
     }
 }
@@ -124,8 +370,371 @@ impl From<&'static str> for CompactSignature {
     }
 }
 
+impl CompactSignature {
+    /// Decode the base64 form signed messages are exchanged in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::CompactSignature;
+    ///
+    /// let encoded = "IAEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI=";
+    /// let signature = CompactSignature::from_base64(encoded).unwrap();
+    /// assert_eq!(signature[0], 32);
+    /// assert_eq!(signature.to_base64(), encoded);
+    /// ```
+    pub fn from_base64(s: &str) -> Result<Self, Error> {
+        let data = base64::decode(s).map_err(|_| Error::InvalidSignature)?;
+        let hash: Hash520Bits = data[..].try_into().map_err(|_| Error::InvalidSignature)?;
+        Ok(CompactSignature(hash))
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.0[..])
+    }
+
+    /// The DER signature of the same `r` and `s`, without the recovery
+    /// header.
+    pub fn to_der(&self) -> Result<Signature, Error> {
+        let mut compact = [0u8; 64];
+        compact.copy_from_slice(&self.0[1..]);
+        Signature::from_compact(&compact)
+    }
+}
+
 impl From<Hash520Bits> for CompactSignature {
     fn from(h: Hash520Bits) -> Self {
         CompactSignature(h)
     }
 }
+
+/// An ECDSA signature with the recovery id that picks its public key out of
+/// the up to four keys it verifies against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoverableSignature {
+    recovery_id: u8,
+    r: [u8; 32],
+    s: [u8; 32],
+}
+
+impl RecoverableSignature {
+    /// Fails with `Error::InvalidSignature` unless `recovery_id` is 0 to 3.
+    pub fn new(recovery_id: u8, r: [u8; 32], s: [u8; 32]) -> Result<Self, Error> {
+        if recovery_id > 3 {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(RecoverableSignature { recovery_id, r, s })
+    }
+
+    pub fn recovery_id(&self) -> u8 {
+        self.recovery_id
+    }
+
+    pub fn r(&self) -> [u8; 32] {
+        self.r
+    }
+
+    pub fn s(&self) -> [u8; 32] {
+        self.s
+    }
+
+    /// The Ethereum `v`: the recovery id plus 27, or with a `chain_id`, the
+    /// recovery id plus `chain_id * 2 + 35` of EIP-155 transactions.
+    pub fn v(&self, chain_id: Option<u64>) -> u64 {
+        let recovery_id = u64::from(self.recovery_id);
+        match chain_id {
+            Some(chain_id) => chain_id * 2 + 35 + recovery_id,
+            None => 27 + recovery_id,
+        }
+    }
+
+    /// The 65-byte form of Bitcoin signed messages, whose header byte is
+    /// the recovery id plus 27, plus 4 more for a `compressed` key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::RecoverableSignature;
+    ///
+    /// let signature = RecoverableSignature::new(1, [1u8; 32], [2u8; 32]).unwrap();
+    /// let compact = signature.to_compact(true);
+    /// assert_eq!(compact[0], 32);
+    /// assert_eq!(RecoverableSignature::from_compact(&compact).unwrap(), (signature, true));
+    /// ```
+    pub fn to_compact(&self, compressed: bool) -> CompactSignature {
+        let mut compact: Hash520Bits = [0u8; 65];
+        compact[0] = 27 + self.recovery_id + if compressed { 4 } else { 0 };
+        compact[1..33].copy_from_slice(&self.r);
+        compact[33..].copy_from_slice(&self.s);
+        CompactSignature(compact)
+    }
+
+    /// Split the 65-byte form into the signature and whether the key is
+    /// compressed.
+    ///
+    /// Headers from 35 to 42, which [BIP137] uses for SegWit addresses, are
+    /// read as compressed.
+    ///
+    /// [BIP137]: https://github.com/bitcoin/bips/blob/master/bip-0137.mediawiki
+    pub fn from_compact(compact: &CompactSignature) -> Result<(Self, bool), Error> {
+        let header = compact[0];
+        if !(27..=42).contains(&header) {
+            return Err(Error::InvalidSignature);
+        }
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&compact[1..33]);
+        s.copy_from_slice(&compact[33..]);
+        let signature = RecoverableSignature::new((header - 27) % 4, r, s)?;
+        Ok((signature, header >= 31))
+    }
+
+    /// Recover the public key that made this signature of `message`, in
+    /// its compressed form.
+    pub fn recover(&self, message: &Message) -> Result<PublicKey, Error> {
+        let recovery_id = RecoveryId::from_i32(i32::from(self.recovery_id))?;
+        let mut data = [0u8; 64];
+        data[..32].copy_from_slice(&self.r);
+        data[32..].copy_from_slice(&self.s);
+        let signature = SecpRecoverableSignature::from_compact(&data, recovery_id)?;
+        let public = SECP256K1.recover(&message.to_secp(), &signature)?;
+        Ok(PublicKey::Compressed(public.serialize()))
+    }
+}
+
+impl From<SecpRecoverableSignature> for RecoverableSignature {
+    fn from(signature: SecpRecoverableSignature) -> Self {
+        let (recovery_id, data) = signature.serialize_compact();
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&data[..32]);
+        s.copy_from_slice(&data[32..]);
+        RecoverableSignature {
+            recovery_id: recovery_id.to_i32() as u8,
+            r,
+            s,
+        }
+    }
+}
+
+/// A [BIP340] Schnorr signature, with the sighash byte a taproot
+/// key-path spend appends unless it signs with the default sighash.
+///
+/// [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SchnorrSignature {
+    signature: [u8; 64],
+    sighash: Option<SighashType>,
+}
+
+impl SchnorrSignature {
+    /// Parse 64 bytes, or 65 with a trailing sighash byte.
+    ///
+    /// The sighash byte must be one of the six [SighashType] values. An
+    /// explicit `0x00` is rejected, since [BIP341] spells the default
+    /// sighash by leaving the byte out.
+    ///
+    /// [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        let sighash = match data.len() {
+            64 => None,
+            65 => Some(SighashType::from_u8(data[64])?),
+            _ => return Err(Error::InvalidSignature),
+        };
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(&data[..64]);
+        Ok(SchnorrSignature { signature, sighash })
+    }
+
+    /// The signature with `sighash` appended, or none for the default.
+    pub fn with_sighash(self, sighash: Option<SighashType>) -> Self {
+        SchnorrSignature { sighash, ..self }
+    }
+
+    pub fn sighash(&self) -> Option<SighashType> {
+        self.sighash
+    }
+
+    /// The 64-byte signature without its sighash byte.
+    pub fn signature(&self) -> &[u8; 64] {
+        &self.signature
+    }
+
+    /// The signature as it goes in a witness, sighash byte included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.signature.to_vec();
+        bytes.extend(self.sighash.map(SighashType::to_u8));
+        bytes
+    }
+
+    /// Verify the signature of `message` against `key`.
+    pub fn verify(&self, message: &Message, key: &XOnlyPublicKey) -> Result<bool, Error> {
+        key.verify_schnorr(message, &self.signature)
+    }
+}
+
+impl fmt::Debug for SchnorrSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_bytes().to_hex().fmt(f)
+    }
+}
+
+impl fmt::Display for SchnorrSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_bytes().to_hex().fmt(f)
+    }
+}
+
+impl str::FromStr for SchnorrSignature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let data = hex::decode(s).map_err(|_| Error::InvalidSignature)?;
+        Self::from_slice(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn bip66_encodings() {
+        let valid = [
+            "3006020101020101",
+            // r with its high bit set takes a zero byte.
+            "300702020080020101",
+        ];
+        for hex in valid.iter() {
+            assert!(Signature::from_der_strict(&hex::decode(hex).unwrap()).is_ok());
+        }
+
+        let invalid = [
+            // Too short, wrong marker, wrong total length.
+            "30050201010201",
+            "3106020101020101",
+            "3007020101020101",
+            // R length running past the end, and S length not adding up.
+            "3006020901020101",
+            "300702010102010100",
+            // Zero-length R, negative S, and a needless zero byte in S.
+            "3006020002020101",
+            "3006020101020181",
+            "300702010102020001",
+            // A wrong integer marker.
+            "3006030101020101",
+        ];
+        for hex in invalid.iter() {
+            let data = hex::decode(hex).unwrap();
+            assert!(!Signature(data.clone()).is_strict_der(), "{}", hex);
+            assert!(Signature::from_der_strict(&data).is_err());
+        }
+    }
+
+    #[test]
+    fn compact_der_round_trip() -> Result<(), Error> {
+        let der = Signature::from("3043021f1af75f8a0ececcc4af0ccdbcccc1a81656eca444e3484151212ad1823ae12202207cc07485ba5a779f1f12089090e4b013322db0dae4e8151592c0b1ee0fd0f74d");
+        let compact = der.to_compact()?;
+        // r is zero-padded to 32 bytes.
+        assert_eq!(compact[0], 0);
+        assert_eq!(Signature::from_compact(&compact)?, der);
+
+        let mut recoverable: Hash520Bits = [0u8; 65];
+        recoverable[0] = 31;
+        recoverable[1..].copy_from_slice(&compact);
+        assert_eq!(CompactSignature::from(recoverable).to_der()?, der);
+
+        assert!(Signature::from("3006020101").to_compact().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn schnorr_signature_encoding() -> Result<(), Error> {
+        // Test vector 1 of BIP340.
+        let hex = "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a";
+        let key = XOnlyPublicKey::from_slice(
+            &hex::decode("dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659")
+                .unwrap(),
+        )?;
+        let mut message = [0u8; 32];
+        message.copy_from_slice(
+            &hex::decode("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89")
+                .unwrap(),
+        );
+        let message = Message::from_prehashed(message);
+
+        let signature: SchnorrSignature = hex.parse()?;
+        assert_eq!(signature.sighash(), None);
+        assert_eq!(signature.to_string(), hex);
+        assert!(signature.verify(&message, &key)?);
+
+        let with_sighash: SchnorrSignature = format!("{}83", hex).parse()?;
+        assert_eq!(
+            with_sighash.sighash(),
+            Some(SighashType::SinglePlusAnyoneCanPay)
+        );
+        assert_eq!(
+            with_sighash,
+            signature.with_sighash(Some(SighashType::SinglePlusAnyoneCanPay))
+        );
+        assert_eq!(with_sighash.to_bytes().len(), 65);
+        assert!(with_sighash.verify(&message, &key)?);
+
+        for byte in &["00", "04", "80", "84", "ff"] {
+            assert!(format!("{}{}", hex, byte)
+                .parse::<SchnorrSignature>()
+                .is_err());
+        }
+        assert!(hex[..126].parse::<SchnorrSignature>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sighash_round_trip() -> Result<(), Error> {
+        let signature = Signature::from("3006020101020101");
+        for byte in [0x01, 0x02, 0x03, 0x81, 0x82, 0x83].iter() {
+            let sighash = SighashType::from_u8(*byte)?;
+            assert_eq!(sighash.to_u8(), *byte);
+            assert_eq!(sighash.is_anyone_can_pay(), *byte > 0x80);
+
+            let with_sighash = signature.with_sighash(sighash);
+            assert_eq!(with_sighash.len(), signature.len() + 1);
+            assert_eq!(
+                with_sighash.split_sighash()?,
+                (Signature::from("3006020101020101"), sighash)
+            );
+        }
+
+        assert!(SighashType::from_u8(0x00).is_err());
+        assert!(SighashType::from_u8(0x84).is_err());
+        assert!(Signature::from("300602010102010104")
+            .split_sighash()
+            .is_err());
+        // The DER part must be strict.
+        assert!(Signature::from("30070201010201010001")
+            .split_sighash()
+            .is_err());
+        assert!(Signature(vec![]).split_sighash().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn compact_signature_encodings() -> Result<(), Error> {
+        let hex = format!("20{}{}", "01".repeat(32), "02".repeat(32));
+        let signature: CompactSignature = hex.parse()?;
+        assert_eq!(signature[0], 32);
+        assert_eq!(signature.to_string(), hex);
+        assert_eq!(
+            CompactSignature::from_base64(&signature.to_base64())?,
+            signature
+        );
+
+        assert!(hex[..128].parse::<CompactSignature>().is_err());
+        assert!(format!("{}00", hex).parse::<CompactSignature>().is_err());
+        assert!(hex.replace("01", "zz").parse::<CompactSignature>().is_err());
+        assert!(CompactSignature::from_base64("AAAA").is_err());
+        assert!(CompactSignature::from_base64("not base64!").is_err());
+        Ok(())
+    }
+}
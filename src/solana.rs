@@ -0,0 +1,143 @@
+//! Solana accounts, derived with the [SLIP-0010](crate::bip32::Ed25519ExtendedKey)
+//! ed25519 backend.
+//!
+//! An account's address is its 32-byte public key in Base58, and wallets
+//! derive account `i` at `m/44'/501'/i'/0'`. `solana-keygen` stores a keypair
+//! as a JSON array of its 64 bytes: the secret followed by the public key.
+
+use base58::ToBase58;
+use ring::signature::{Ed25519KeyPair, KeyPair as _};
+
+use crate::bip32::{Bip32Error, Ed25519ExtendedKey};
+use crate::{ChildNumber, CoinType, DerivationPath, Error, Purpose, Secret};
+
+/// The path of account `account`, `m/44'/501'/account'/0'`.
+///
+/// Fails with `Bip32Error::InvalidChildNumber` if `account` is 2^31 or more.
+pub fn path(account: u32) -> Result<DerivationPath, Bip32Error> {
+    Ok(
+        DerivationPath::account(Purpose::Bip44, CoinType::Solana, account)?
+            .child(ChildNumber::Hardened(0)),
+    )
+}
+
+/// An ed25519 key pair of a Solana account.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::bip32::Ed25519ExtendedKey;
+/// use keymaker::solana::{self, Keypair};
+/// use keymaker::SeedBuilder;
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let master = Ed25519ExtendedKey::new(&seed.entropy).unwrap();
+/// let keypair = Keypair::from_extended(&master.derive_path(&solana::path(0).unwrap()).unwrap());
+///
+/// let json = keypair.to_json();
+/// assert_eq!(Keypair::from_json(&json).unwrap(), keypair);
+/// println!("{}", keypair.address());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Keypair {
+    secret: Secret,
+    public: [u8; 32],
+}
+
+impl Keypair {
+    pub fn from_extended(key: &Ed25519ExtendedKey) -> Self {
+        Keypair {
+            secret: key.secret(),
+            public: key.public_key(),
+        }
+    }
+
+    /// Parse the 64-byte secret and public key, checking that they match.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 64 {
+            return Err(Error::InvalidSecret);
+        }
+        let (secret, public) = bytes.split_at(32);
+        let pair = Ed25519KeyPair::from_seed_and_public_key(secret, public)
+            .map_err(|_| Error::InvalidSecret)?;
+
+        let mut keypair = Keypair {
+            secret: Secret::default(),
+            public: [0u8; 32],
+        };
+        keypair.secret.copy_from_slice(secret);
+        keypair.public.copy_from_slice(pair.public_key().as_ref());
+        Ok(keypair)
+    }
+
+    /// Parse a `solana-keygen` keypair file.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let bytes = json
+            .trim()
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or(Error::InvalidSecret)?
+            .split(',')
+            .map(|b| b.trim().parse::<u8>().map_err(|_| Error::InvalidSecret))
+            .collect::<Result<Vec<u8>, _>>()?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// The secret followed by the public key.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.secret);
+        bytes[32..].copy_from_slice(&self.public);
+        bytes
+    }
+
+    /// The keypair in the JSON format of `solana-keygen`.
+    pub fn to_json(&self) -> String {
+        let bytes: Vec<String> = self.to_bytes().iter().map(u8::to_string).collect();
+        format!("[{}]", bytes.join(","))
+    }
+
+    pub fn secret(&self) -> Secret {
+        self.secret
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public
+    }
+
+    /// The Base58 address of the account.
+    pub fn address(&self) -> String {
+        self.public.to_base58()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn account_of_known_seed() -> anyhow::Result<()> {
+        // The BIP39 seed of the all-abandon mnemonic.
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4")?;
+        let master = Ed25519ExtendedKey::new(&seed)?;
+        assert_eq!(path(0).unwrap().to_string(), "m/44'/501'/0'/0'");
+
+        let keypair = Keypair::from_extended(&master.derive_path(&path(0)?)?);
+        assert_eq!(
+            keypair.address(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+
+        let json = keypair.to_json();
+        assert!(json.starts_with('[') && json.matches(',').count() == 63);
+        assert_eq!(Keypair::from_json(&json)?, keypair);
+
+        // A public key that does not belong to the secret.
+        let mut bytes = keypair.to_bytes();
+        bytes[63] ^= 1;
+        assert!(Keypair::from_bytes(&bytes).is_err());
+        assert!(Keypair::from_json("[1,2,3]").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+//! Tron account addresses.
+//!
+//! An address holds the same 20 bytes as the Ethereum address of the key,
+//! behind a `0x41` version byte and in Base58Check, so it always starts with
+//! a `T`. Keys for an account come from the `m/44'/195'/0'/0/i` BIP44 path.
+//! Like the [eth](crate::eth) module, it needs the `eth` feature.
+
+use base58::{FromBase58, ToBase58};
+
+use crate::crypto;
+use crate::{AddressHash, Error, PublicKey};
+
+const ADDRESS_VERSION: u8 = 0x41;
+
+/// Encode the address of `public`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{tron, CoinType, DerivationPath, ExtendedPrivKey, Network, Purpose, SeedBuilder};
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let path = DerivationPath::address(Purpose::Bip44, CoinType::Tron, 0, false, 0).unwrap();
+/// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+///     .unwrap()
+///     .derive_path(&path)
+///     .unwrap();
+///
+/// let address = tron::to_address(&keys.pubkey()).unwrap();
+/// assert!(address.starts_with('T'));
+/// ```
+pub fn to_address(public: &PublicKey) -> Result<String, Error> {
+    let mut data = vec![ADDRESS_VERSION];
+    data.extend_from_slice(&public.keccak_identifier()?);
+    data.extend_from_slice(&crypto::checksum(&data));
+    Ok(data.to_base58())
+}
+
+/// Parse an address into the 20 bytes it shares with the Ethereum address.
+pub fn validate_address(s: &str) -> Result<AddressHash, Error> {
+    let data = s.from_base58().map_err(|_| Error::InvalidAddress)?;
+    if data.len() != 25 || data[0] != ADDRESS_VERSION {
+        return Err(Error::InvalidAddress);
+    }
+    if !crypto::verify_checksum(&data[..21], &data[21..]) {
+        return Err(Error::InvalidChecksum);
+    }
+    let mut address = AddressHash::default();
+    address.copy_from_slice(&data[1..21]);
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{eth, Network, PrivateKey};
+
+    #[test]
+    fn address_of_public_key() -> Result<(), Error> {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let private = PrivateKey {
+            network: Network::Mainnet,
+            secret,
+            compressed: true,
+        };
+        let public = private.public_key()?;
+        let address = to_address(&public)?;
+        assert_eq!(address, "TMVQGm1qAQYVdetCeGRRkTWYYrLXuHK2HC");
+        assert_eq!(
+            eth::to_checksum_address(&validate_address(&address)?),
+            public.to_eth_address()?
+        );
+
+        assert!(matches!(
+            validate_address("TMVQGm1qAQYVdetCeGRRkTWYYrLXuHK2HD"),
+            Err(Error::InvalidChecksum)
+        ));
+        assert!(matches!(
+            validate_address("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"),
+            Err(Error::InvalidAddress)
+        ));
+        Ok(())
+    }
+}
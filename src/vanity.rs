@@ -0,0 +1,333 @@
+//! Search for a vanity address, one that starts with a chosen prefix or
+//! matches a regular expression.
+//!
+//! Keys are drawn at random on every worker thread until one of them hits, so
+//! the expected number of attempts grows 58-fold with each Base58 character
+//! of the prefix and 32-fold with each bech32 one.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{Address, AddressType, KeyPair, KeyPairBuilder, Network};
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Error originating from [vanity](crate::vanity) module.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum VanityError {
+    #[error("Prefix {0:?} cannot occur in this kind of address")]
+    InvalidPrefix(String),
+    #[error("Invalid regular expression: {0}")]
+    InvalidRegex(String),
+    #[error("Unsupported address type {0:?}")]
+    UnsupportedAddressType(AddressType),
+}
+
+/// What a vanity address must look like.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// The address starts with this string. Bech32 prefixes are matched
+    /// case-insensitively.
+    Prefix(String),
+    /// The address matches this regular expression.
+    Regex(Regex),
+}
+
+/// A vanity address search over P2PKH, P2WPKH or P2TR addresses.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::vanity::VanitySearch;
+/// use keymaker::{Address, AddressType, Network};
+///
+/// let search = VanitySearch::prefix("bc1qq").address_type(AddressType::P2wpkh);
+/// assert_eq!(search.difficulty().unwrap(), 32.0);
+///
+/// let keys = search.search().unwrap();
+/// let address = Address::p2wpkh(keys.pubkey(), Network::Mainnet).unwrap();
+/// assert!(address.to_string().starts_with("bc1qq"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VanitySearch {
+    pattern: Pattern,
+    address_type: AddressType,
+    network: Network,
+    threads: usize,
+}
+
+impl VanitySearch {
+    /// Search for addresses starting with `prefix`, including the leading
+    /// characters every address of its kind shares, e.g. `1` or `bc1q`.
+    pub fn prefix(prefix: &str) -> Self {
+        Self::new(Pattern::Prefix(prefix.to_string()))
+    }
+
+    /// Search for addresses matching the regular expression `pattern`.
+    pub fn regex(pattern: &str) -> Result<Self, VanityError> {
+        let regex = Regex::new(pattern).map_err(|e| VanityError::InvalidRegex(e.to_string()))?;
+        Ok(Self::new(Pattern::Regex(regex)))
+    }
+
+    /// Instantiate a search for mainnet P2PKH addresses on all available
+    /// cores.
+    pub fn new(pattern: Pattern) -> Self {
+        VanitySearch {
+            pattern,
+            address_type: AddressType::P2pkh,
+            network: Network::Mainnet,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Set the kind of address to search. Defaults to P2PKH.
+    pub fn address_type(mut self, address_type: AddressType) -> Self {
+        self.address_type = address_type;
+        self
+    }
+
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Set the number of worker threads. Defaults to the available
+    /// parallelism.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// The expected number of keys to try before a prefix matches, or `None`
+    /// for a regular expression, whose odds cannot be known in general.
+    ///
+    /// Base58 digits are not spread evenly at every position, so this is an
+    /// estimate for P2PKH prefixes.
+    pub fn difficulty(&self) -> Option<f64> {
+        let prefix = match &self.pattern {
+            Pattern::Prefix(prefix) => prefix,
+            Pattern::Regex(_) => return None,
+        };
+        let fixed = self.fixed_prefixes().ok()?;
+        let (radix, leading) = match self.address_type {
+            AddressType::P2pkh => (58f64, fixed.len() as f64),
+            _ => (32f64, 1f64),
+        };
+        let free = prefix.chars().count().saturating_sub(fixed[0].len());
+        Some(radix.powi(free as i32) * leading)
+    }
+
+    /// The expected time to a match when trying `keys_per_second` keys.
+    pub fn eta(&self, keys_per_second: f64) -> Option<Duration> {
+        let difficulty = self.difficulty()?;
+        if keys_per_second <= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(difficulty / keys_per_second))
+    }
+
+    /// Draw random keys on every worker thread until one's address matches,
+    /// and return it.
+    ///
+    /// Fails early if the prefix cannot occur in the chosen kind of address.
+    pub fn search(&self) -> Result<KeyPair> {
+        self.check()?;
+
+        let found = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let workers = (0..self.threads)
+            .map(|_| {
+                let search = self.clone();
+                let found = Arc::clone(&found);
+                let sender = sender.clone();
+                thread::spawn(move || -> Result<()> {
+                    while !found.load(Ordering::Relaxed) {
+                        let keys = KeyPairBuilder::new().network(search.network).build();
+                        let hit = keys.and_then(|keys| {
+                            let address = search.address(&keys)?;
+                            Ok(Some(keys).filter(|_| search.matches(&address)))
+                        });
+                        match hit {
+                            Ok(None) => {}
+                            Ok(Some(keys)) => {
+                                found.store(true, Ordering::Relaxed);
+                                // Only the first match is received.
+                                let _ = sender.send(keys);
+                            }
+                            Err(e) => {
+                                found.store(true, Ordering::Relaxed);
+                                return Err(e);
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(sender);
+
+        let keys = receiver.recv();
+        found.store(true, Ordering::Relaxed);
+        for worker in workers {
+            worker.join().expect("vanity worker panicked")?;
+        }
+        // Nothing is received only if a worker failed, and its error was
+        // returned above.
+        Ok(keys?)
+    }
+
+    fn address(&self, keys: &KeyPair) -> Result<Address> {
+        Ok(Address::from_public(
+            keys.pubkey(),
+            self.address_type,
+            self.network,
+        )?)
+    }
+
+    fn matches(&self, address: &Address) -> bool {
+        let address = address.to_string();
+        match &self.pattern {
+            Pattern::Prefix(prefix) if self.address_type == AddressType::P2pkh => {
+                address.starts_with(prefix.as_str())
+            }
+            Pattern::Prefix(prefix) => address.starts_with(&prefix.to_lowercase()),
+            Pattern::Regex(regex) => regex.is_match(&address),
+        }
+    }
+
+    /// Check that the prefix only uses the address alphabet and starts like
+    /// addresses of its kind do.
+    fn check(&self) -> Result<(), VanityError> {
+        let fixed = self.fixed_prefixes()?;
+        let prefix = match &self.pattern {
+            Pattern::Prefix(prefix) => prefix,
+            Pattern::Regex(_) => return Ok(()),
+        };
+
+        let (prefix, alphabet) = match self.address_type {
+            AddressType::P2pkh => (prefix.clone(), BASE58_ALPHABET),
+            _ => (prefix.to_lowercase(), BECH32_CHARSET),
+        };
+        let invalid = || VanityError::InvalidPrefix(prefix.clone());
+        let start = fixed
+            .iter()
+            .find(|start| prefix.starts_with(start.as_str()) || start.starts_with(&prefix))
+            .ok_or_else(invalid)?;
+        if prefix.len() > start.len()
+            && !prefix[start.len()..].chars().all(|c| alphabet.contains(c))
+        {
+            return Err(invalid());
+        }
+        Ok(())
+    }
+
+    /// The possible leading characters shared by every address of the
+    /// chosen kind: the Base58 digit its version byte implies, or the bech32
+    /// human-readable part, separator and witness version.
+    fn fixed_prefixes(&self) -> Result<Vec<String>, VanityError> {
+        let (len, bech32) = match self.address_type {
+            AddressType::P2pkh => (20, false),
+            AddressType::P2wpkh => (20, true),
+            AddressType::P2tr => (32, true),
+            address_type => return Err(VanityError::UnsupportedAddressType(address_type)),
+        };
+        let lowest = Address::from_parts(self.network, self.address_type, &vec![0; len]);
+        let highest = Address::from_parts(self.network, self.address_type, &vec![0xff; len]);
+        let (lowest, highest) = (lowest.to_string(), highest.to_string());
+        if bech32 {
+            let end = lowest.rfind('1').map(|i| i + 2).unwrap_or_default();
+            return Ok(vec![lowest[..end].to_string()]);
+        }
+
+        let digit = |s: &str| BASE58_ALPHABET.find(&s[..1]).unwrap_or_default();
+        Ok(BASE58_ALPHABET[digit(&lowest)..=digit(&highest)]
+            .chars()
+            .map(String::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn finds_prefix_and_regex() -> Result<()> {
+        let keys = VanitySearch::prefix("1A").threads(2).search()?;
+        let address = Address::p2pkh(keys.pubkey(), Network::Mainnet);
+        assert!(address.to_string().starts_with("1A"));
+
+        let keys = VanitySearch::regex("k$")?
+            .network(Network::Testnet)
+            .search()?;
+        assert_eq!(keys.privkey().network, Network::Testnet);
+        let address = Address::p2pkh(keys.pubkey(), Network::Testnet);
+        assert!(address.to_string().ends_with('k'));
+
+        let keys = VanitySearch::prefix("BC1PZ")
+            .address_type(AddressType::P2tr)
+            .search()?;
+        let address = Address::p2tr(keys.pubkey(), None, Network::Mainnet)?;
+        assert!(address.to_string().starts_with("bc1pz"));
+        Ok(())
+    }
+
+    #[test]
+    fn difficulty_and_eta() {
+        let search = VanitySearch::prefix("1Abc");
+        assert_eq!(search.difficulty(), Some(58f64.powi(3)));
+        assert_eq!(search.eta(58.0), Some(Duration::from_secs(58 * 58)));
+
+        // Testnet P2PKH addresses start with either m or n.
+        let testnet = VanitySearch::prefix("mA").network(Network::Testnet);
+        assert_eq!(testnet.difficulty(), Some(116.0));
+
+        let bech32 = VanitySearch::prefix("tb1qxy")
+            .address_type(AddressType::P2wpkh)
+            .network(Network::Testnet);
+        assert_eq!(bech32.difficulty(), Some(1024.0));
+
+        assert_eq!(VanitySearch::regex("^1").unwrap().difficulty(), None);
+    }
+
+    #[test]
+    fn rejects_impossible_patterns() {
+        for (search, invalid) in [
+            (VanitySearch::prefix("1O"), "1O"),
+            (VanitySearch::prefix("3A"), "3A"),
+            (
+                VanitySearch::prefix("bc1qb").address_type(AddressType::P2wpkh),
+                "bc1qb",
+            ),
+            (
+                VanitySearch::prefix("bc1p").address_type(AddressType::P2wpkh),
+                "bc1p",
+            ),
+        ]
+        .iter()
+        {
+            let err = search.search().unwrap_err();
+            assert_eq!(
+                err.downcast::<VanityError>().unwrap(),
+                VanityError::InvalidPrefix(invalid.to_string())
+            );
+        }
+
+        assert!(matches!(
+            VanitySearch::regex("("),
+            Err(VanityError::InvalidRegex(_))
+        ));
+        assert!(VanitySearch::prefix("3")
+            .address_type(AddressType::P2sh)
+            .search()
+            .is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! [BIP340] x-only public keys and the [BIP341] taproot tweak.
+//!
+//! An x-only key is the 32-byte X coordinate of a point, standing for the
+//! point with an even Y. Tweaking a key with `t = H_TapTweak(P || root)`
+//! gives the output key of a taproot output, and the [Parity] of the tweaked
+//! point, which spending through a script path needs. Schnorr signatures
+//! are verified against x-only keys.
+//!
+//! [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+//! [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+
+use secp256k1::bitcoin_hashes::hex::ToHex;
+use secp256k1::key;
+use secp256k1::schnorrsig;
+use std::convert::TryFrom;
+use std::{fmt, ops};
+
+use crate::crypto;
+use crate::{Error, Message, PrivateKey, PublicKey, Secret, SECP256K1};
+
+/// The parity of the Y coordinate of a point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct XOnlyPublicKey([u8; 32]);
+
+impl XOnlyPublicKey {
+    /// Parse a 32-byte X coordinate, failing with `Error::InvalidPublic` if
+    /// no point on the curve has it.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        let key = schnorrsig::PublicKey::from_slice(data).map_err(|_| Error::InvalidPublic)?;
+        Ok(XOnlyPublicKey(key.serialize()))
+    }
+
+    /// Tweak the key into a taproot output key committing to `merkle_root`,
+    /// or to no script at all when it is `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use keymaker::XOnlyPublicKey;
+    ///
+    /// let internal = hex::decode("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d").unwrap();
+    /// let internal = XOnlyPublicKey::from_slice(&internal).unwrap();
+    /// let (output, _) = internal.tap_tweak(None).unwrap();
+    /// assert_eq!(
+    ///     output.to_string(),
+    ///     "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+    /// );
+    /// ```
+    pub fn tap_tweak(
+        &self,
+        merkle_root: Option<&[u8; 32]>,
+    ) -> Result<(XOnlyPublicKey, Parity), Error> {
+        let mut key = schnorrsig::PublicKey::from_slice(&self.0)?;
+        let odd = key.tweak_add_assign(&SECP256K1, &tap_tweak_hash(&self.0, merkle_root))?;
+        Ok((XOnlyPublicKey(key.serialize()), parity(odd)))
+    }
+
+    /// Verify a 64-byte [BIP340] Schnorr `signature` of `message`.
+    ///
+    /// A malformed signature is an error, while a well-formed one by
+    /// another key is `Ok(false)`.
+    ///
+    /// [BIP340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    pub fn verify_schnorr(&self, message: &Message, signature: &[u8]) -> Result<bool, Error> {
+        let key = schnorrsig::PublicKey::from_slice(&self.0)?;
+        let signature = schnorrsig::Signature::from_slice(signature)?;
+        let message = message.to_secp();
+        Ok(SECP256K1
+            .schnorrsig_verify(&signature, &message, &key)
+            .is_ok())
+    }
+
+    pub fn serialize(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl TryFrom<&PublicKey> for XOnlyPublicKey {
+    type Error = Error;
+
+    /// Drop the parity of `public`, as [PublicKey::x_only] does.
+    fn try_from(public: &PublicKey) -> Result<Self, Error> {
+        Ok(public.x_only()?.0)
+    }
+}
+
+impl PublicKey {
+    /// The x-only form of the key, and the parity it drops.
+    pub fn x_only(&self) -> Result<(XOnlyPublicKey, Parity), Error> {
+        let compressed = self.compressed()?;
+        let mut x = [0u8; 32];
+        x.copy_from_slice(&compressed[1..]);
+        Ok((XOnlyPublicKey(x), parity(compressed[0] == 0x03)))
+    }
+}
+
+impl PrivateKey {
+    /// Tweak the key as [XOnlyPublicKey::tap_tweak] tweaks its public key,
+    /// for signing with the output key of a taproot output.
+    ///
+    /// The secret is negated first if its point has an odd Y, so that the
+    /// result matches the x-only output key. The returned parity is that of
+    /// the tweaked point.
+    pub fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<(PrivateKey, Parity), Error> {
+        let mut secret_key = key::SecretKey::from_slice(&self.secret)?;
+        let (internal, internal_parity) = self.public_key()?.x_only()?;
+        if internal_parity == Parity::Odd {
+            secret_key.negate_assign();
+        }
+        secret_key.add_assign(&tap_tweak_hash(&internal.0, merkle_root))?;
+
+        let mut secret = Secret::default();
+        secret.copy_from_slice(&secret_key[..]);
+        let tweaked = PrivateKey {
+            secret,
+            compressed: true,
+            ..*self
+        };
+        let (_, parity) = tweaked.public_key()?.x_only()?;
+        Ok((tweaked, parity))
+    }
+}
+
+/// `H_TapTweak(P || merkle_root)`.
+fn tap_tweak_hash(x_only: &[u8; 32], merkle_root: Option<&[u8; 32]>) -> [u8; 32] {
+    let mut data = x_only.to_vec();
+    if let Some(root) = merkle_root {
+        data.extend_from_slice(root);
+    }
+    crypto::tagged_hash("TapTweak", &data)
+}
+
+fn parity(odd: bool) -> Parity {
+    if odd {
+        Parity::Odd
+    } else {
+        Parity::Even
+    }
+}
+
+impl fmt::Debug for XOnlyPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.to_hex().fmt(f)
+    }
+}
+
+impl fmt::Display for XOnlyPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.to_hex().fmt(f)
+    }
+}
+
+impl ops::Deref for XOnlyPublicKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Network;
+
+    #[test]
+    fn bip341_vectors() -> Result<(), Error> {
+        let cases = [
+            (
+                "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+                Some("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"),
+                "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+            ),
+            (
+                "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+                None,
+                "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+            ),
+        ];
+        for (internal, root, output) in cases.iter() {
+            let internal = XOnlyPublicKey::from_slice(&hex::decode(internal).unwrap())?;
+            let mut merkle_root = [0u8; 32];
+            let merkle_root = root.map(|root| {
+                merkle_root.copy_from_slice(&hex::decode(root).unwrap());
+                &merkle_root
+            });
+            assert_eq!(internal.tap_tweak(merkle_root)?.0.to_string(), *output);
+        }
+        assert!(XOnlyPublicKey::from_slice(&[0u8; 32]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn bip340_verification() -> Result<(), Error> {
+        // Test vector 1 of BIP340.
+        let private = PrivateKey::from_hex(
+            "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+            Network::Mainnet,
+        )?;
+        let key = XOnlyPublicKey::try_from(&private.public_key()?)?;
+        assert_eq!(
+            key.to_string(),
+            "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659"
+        );
+
+        let mut message = [0u8; 32];
+        message.copy_from_slice(
+            &hex::decode("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89")
+                .unwrap(),
+        );
+        let message = Message::from_prehashed(message);
+        let mut signature = hex::decode("6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a").unwrap();
+        assert!(key.verify_schnorr(&message, &signature)?);
+
+        signature[63] ^= 1;
+        assert!(!key.verify_schnorr(&message, &signature)?);
+        assert!(key.verify_schnorr(&message, &signature[..63]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn private_tweak_matches_public() -> Result<(), Error> {
+        let private = PrivateKey::from_hex(
+            "6b973d88838f27366ed61c9ad6367663045cb456e28335c109e30717ae0c6baa",
+            Network::Mainnet,
+        )?;
+        let (tweaked, parity) = private.tap_tweak(None)?;
+        assert_eq!(
+            tweaked.to_hex(),
+            "2405b971772ad26915c8dcdf10f238753a9b837e5f8e6a86fd7c0cce5b7296d9"
+        );
+
+        let (internal, _) = private.public_key()?.x_only()?;
+        let (output, output_parity) = internal.tap_tweak(None)?;
+        assert_eq!(tweaked.public_key()?.x_only()?, (output, output_parity));
+        assert_eq!(parity, output_parity);
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+//! XRP Ledger account addresses and family seeds.
+//!
+//! The ledger uses Base58Check with its own alphabet, starting with `r`, so a
+//! classic address of account ID `hash160(public key)` always starts with an
+//! `r` and a family seed with an `s`. [X-addresses] pack the account ID
+//! together with an optional destination tag.
+//!
+//! Keys for an account come from the `m/44'/144'/0'/0/i` BIP44 path.
+//!
+//! [X-addresses]: https://xrpaddress.info
+
+use base58::{FromBase58, ToBase58};
+
+use crate::crypto;
+use crate::{AddressHash, Error, Network, PublicKey};
+
+const BITCOIN_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const RIPPLE_ALPHABET: &[u8; 58] = b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+
+const ACCOUNT_ID_VERSION: u8 = 0x00;
+const FAMILY_SEED_VERSION: u8 = 0x21;
+const X_ADDRESS_MAINNET: [u8; 2] = [0x05, 0x44];
+const X_ADDRESS_TESTNET: [u8; 2] = [0x04, 0x93];
+
+/// Encode the classic `r...` address of `public`.
+///
+/// # Examples
+///
+/// ```
+/// use keymaker::{xrp, CoinType, DerivationPath, ExtendedPrivKey, Network, Purpose, SeedBuilder};
+///
+/// let seed = SeedBuilder::new().build().unwrap();
+/// let path = DerivationPath::address(Purpose::Bip44, CoinType::Ripple, 0, false, 0).unwrap();
+/// let keys = ExtendedPrivKey::new(seed.entropy, None, Network::Mainnet, true)
+///     .unwrap()
+///     .derive_path(&path)
+///     .unwrap();
+///
+/// let address = xrp::to_classic_address(&keys.pubkey()).unwrap();
+/// assert!(address.starts_with('r'));
+/// ```
+pub fn to_classic_address(public: &PublicKey) -> Result<String, Error> {
+    Ok(encode(ACCOUNT_ID_VERSION, &public.identifier()?))
+}
+
+/// Parse a classic address into its account ID.
+pub fn decode_classic_address(s: &str) -> Result<AddressHash, Error> {
+    let payload = decode(s, &[ACCOUNT_ID_VERSION])?;
+    let mut account = AddressHash::default();
+    if payload.len() != account.len() {
+        return Err(Error::InvalidAddress);
+    }
+    account.copy_from_slice(&payload);
+    Ok(account)
+}
+
+/// Encode an X-address of `account` on the main or test `network`, with an
+/// optional destination `tag`.
+pub fn to_x_address(
+    account: &AddressHash,
+    tag: Option<u32>,
+    network: Network,
+) -> Result<String, Error> {
+    let prefix = match network {
+        Network::Mainnet => X_ADDRESS_MAINNET,
+        Network::Testnet => X_ADDRESS_TESTNET,
+        _ => return Err(Error::InvalidNetwork),
+    };
+    let mut data = prefix.to_vec();
+    data.extend_from_slice(account);
+    data.push(tag.is_some() as u8);
+    // The tag takes 8 bytes, of which only the low 4 are in use.
+    data.extend_from_slice(&u64::from(tag.unwrap_or_default()).to_le_bytes());
+    Ok(encode_raw(&data))
+}
+
+/// Parse an X-address into its account ID, destination tag and network.
+pub fn decode_x_address(s: &str) -> Result<(AddressHash, Option<u32>, Network), Error> {
+    let data = decode_raw(s)?;
+    if data.len() != 31 {
+        return Err(Error::InvalidAddress);
+    }
+    let network = match [data[0], data[1]] {
+        X_ADDRESS_MAINNET => Network::Mainnet,
+        X_ADDRESS_TESTNET => Network::Testnet,
+        _ => return Err(Error::InvalidAddress),
+    };
+
+    let mut account = AddressHash::default();
+    account.copy_from_slice(&data[2..22]);
+    let mut tag = [0u8; 8];
+    tag.copy_from_slice(&data[23..]);
+    let tag = u64::from_le_bytes(tag);
+    let tag = match data[22] {
+        0 if tag == 0 => None,
+        1 if tag <= u64::from(u32::MAX) => Some(tag as u32),
+        _ => return Err(Error::InvalidAddress),
+    };
+    Ok((account, tag, network))
+}
+
+/// Encode 16 bytes of entropy as an `s...` family seed.
+pub fn encode_seed(entropy: &[u8; 16]) -> String {
+    encode(FAMILY_SEED_VERSION, entropy)
+}
+
+/// Parse a family seed into its 16 bytes of entropy.
+pub fn decode_seed(s: &str) -> Result<[u8; 16], Error> {
+    let payload = decode(s, &[FAMILY_SEED_VERSION]).map_err(|_| Error::InvalidSecret)?;
+    let mut entropy = [0u8; 16];
+    if payload.len() != entropy.len() {
+        return Err(Error::InvalidSecret);
+    }
+    entropy.copy_from_slice(&payload);
+    Ok(entropy)
+}
+
+fn encode(version: u8, payload: &[u8]) -> String {
+    let mut data = vec![version];
+    data.extend_from_slice(payload);
+    encode_raw(&data)
+}
+
+/// Check the version byte of a decoded string and return what follows it.
+fn decode(s: &str, version: &[u8]) -> Result<Vec<u8>, Error> {
+    let data = decode_raw(s)?;
+    if !data.starts_with(version) {
+        return Err(Error::InvalidAddress);
+    }
+    Ok(data[version.len()..].to_vec())
+}
+
+/// Base58Check in the ledger's alphabet.
+fn encode_raw(data: &[u8]) -> String {
+    let mut data = data.to_vec();
+    data.extend_from_slice(&crypto::checksum(&data));
+    translate(&data.to_base58(), BITCOIN_ALPHABET, RIPPLE_ALPHABET)
+}
+
+fn decode_raw(s: &str) -> Result<Vec<u8>, Error> {
+    let data = translate(s, RIPPLE_ALPHABET, BITCOIN_ALPHABET)
+        .from_base58()
+        .map_err(|_| Error::InvalidAddress)?;
+    if data.len() < 5 {
+        return Err(Error::InvalidAddress);
+    }
+    let (data, checksum) = data.split_at(data.len() - 4);
+    if !crypto::verify_checksum(data, checksum) {
+        return Err(Error::InvalidChecksum);
+    }
+    Ok(data.to_vec())
+}
+
+/// Map every character of `s` from one Base58 alphabet to another. A
+/// character in neither is kept, for the decoder to reject.
+fn translate(s: &str, from: &[u8; 58], to: &[u8; 58]) -> String {
+    s.bytes()
+        .map(|b| match from.iter().position(|c| *c == b) {
+            Some(i) => to[i] as char,
+            None => b as char,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn classic_and_x_addresses() -> Result<(), Error> {
+        // The genesis account of the `masterpassphrase` seed.
+        let compressed =
+            hex::decode("0330e7fc9d56bb25d6893ba3f317ae5bcf33b3291bd63db32654a313222f7fd020")
+                .unwrap();
+        let mut inner = [0u8; 33];
+        inner.copy_from_slice(&compressed);
+        let classic = to_classic_address(&PublicKey::Compressed(inner))?;
+        assert_eq!(classic, "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+
+        let account = decode_classic_address("r9cZA1mLK5R5Am25ArfXFmqgNwjZgnfk59")?;
+        let cases = [
+            (
+                None,
+                Network::Mainnet,
+                "X7AcgcsBL6XDcUb289X4mJ8djcdyKaB5hJDWMArnXr61cqZ",
+            ),
+            (
+                Some(1),
+                Network::Mainnet,
+                "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGZMhc9YTE92ehJ2Fu",
+            ),
+            (
+                None,
+                Network::Testnet,
+                "T719a5UwUCnEs54UsxG9CJYYDhwmFCqkr7wxCcNcfZ6p5GZ",
+            ),
+        ];
+        for (tag, network, expected) in cases.iter() {
+            assert_eq!(to_x_address(&account, *tag, *network)?, *expected);
+            assert_eq!(decode_x_address(expected)?, (account, *tag, *network));
+        }
+
+        assert!(matches!(
+            decode_classic_address("rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTi"),
+            Err(Error::InvalidChecksum)
+        ));
+        assert!(to_x_address(&account, None, Network::Litecoin).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn family_seed() -> Result<(), Error> {
+        let entropy = hex::decode("dedce9ce67b451d852fd4e846fcde31c").unwrap();
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&entropy);
+        assert_eq!(encode_seed(&bytes), "snoPBrXtMeMyMHUVTgbuqAfg1SUTb");
+        assert_eq!(decode_seed("snoPBrXtMeMyMHUVTgbuqAfg1SUTb")?, bytes);
+        assert!(decode_seed("rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh").is_err());
+        Ok(())
+    }
+}